@@ -0,0 +1,159 @@
+//! Audible alarm sounding when a probe reaches the target temperature.
+
+use std::collections::BTreeSet;
+
+#[cfg(feature = "device")]
+use anyhow::Context;
+
+/// Frequency of the square wave driving a passive piezo buzzer in Hz.
+#[cfg(feature = "device")]
+const BUZZER_FREQUENCY: f64 = 2000.0;
+
+/// Piezo buzzer connected to a GPIO pin.
+pub struct Buzzer {
+    #[cfg(feature = "device")]
+    pin: rppal::gpio::OutputPin,
+    on: bool,
+}
+
+impl Buzzer {
+    #[cfg(feature = "device")]
+    pub fn new(pin: u8) -> anyhow::Result<Self> {
+        let pin = rppal::gpio::Gpio::new()
+            .context("unable to access GPIO")?
+            .get(pin)
+            .with_context(|| format!("unable to get GPIO pin {pin}"))?
+            .into_output_low();
+
+        Ok(Self { pin, on: false })
+    }
+
+    #[cfg(not(feature = "device"))]
+    pub fn new(pin: u8) -> anyhow::Result<Self> {
+        tracing::info!(pin, "no GPIO available, buzzer is only logged");
+        Ok(Self { on: false })
+    }
+
+    /// Turn the buzzer on or off.
+    pub fn set(&mut self, on: bool) -> anyhow::Result<()> {
+        if self.on == on {
+            return Ok(());
+        }
+
+        tracing::info!(on, "switching buzzer");
+
+        #[cfg(feature = "device")]
+        if on {
+            self.pin
+                .set_pwm_frequency(BUZZER_FREQUENCY, 0.5)
+                .context("unable to start buzzer")?;
+        } else {
+            self.pin.clear_pwm().context("unable to stop buzzer")?;
+        }
+
+        self.on = on;
+        Ok(())
+    }
+}
+
+/// Return the target temperature of `probe` out of `targets`, the n-th applies to the n-th probe,
+/// the last to all others.
+pub fn target(targets: &[f32], probe: usize) -> Option<f32> {
+    targets.get(probe).or(targets.last()).copied()
+}
+
+/// Sounds the buzzer while any probe is at or above its target temperature or its ambient
+/// temperature is outside of the configured band.
+///
+/// Once triggered, a probe has to drop more than `hysteresis` degrees below the target before the
+/// alarm stops, so readings fluctuating around the target do not make the buzzer chatter. The same
+/// applies to the ambient band.
+pub struct Alarm {
+    /// Target temperatures in °C, the n-th applies to the n-th probe, the last to all others.
+    targets: Vec<f32>,
+    hysteresis: f32,
+    triggered: BTreeSet<usize>,
+    /// Lowest and highest allowed ambient temperature in °C.
+    ambient_min: Option<f32>,
+    ambient_max: Option<f32>,
+    /// Probes with an ambient temperature outside of the band.
+    out_of_band: BTreeSet<usize>,
+    buzzer: Buzzer,
+}
+
+impl Alarm {
+    pub fn new(targets: Vec<f32>, hysteresis: f32, buzzer: Buzzer) -> Self {
+        Self {
+            targets,
+            hysteresis,
+            triggered: BTreeSet::new(),
+            ambient_min: None,
+            ambient_max: None,
+            out_of_band: BTreeSet::new(),
+            buzzer,
+        }
+    }
+
+    /// Also sound the buzzer while the ambient temperature is below `min` or above `max` °C.
+    pub fn ambient(mut self, min: Option<f32>, max: Option<f32>) -> Self {
+        self.ambient_min = min;
+        self.ambient_max = max;
+        self
+    }
+
+    /// Return the target temperature of `probe`.
+    fn target(&self, probe: usize) -> Option<f32> {
+        target(&self.targets, probe)
+    }
+
+    /// Replace the target temperatures given in °C.
+    pub fn set_targets(&mut self, targets: Vec<f32>) {
+        self.targets = targets;
+    }
+
+    /// Update the alarm state from `event`.
+    pub fn update(&mut self, event: &meater::Event) -> anyhow::Result<()> {
+        match event {
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => {
+                if let Some(target) = self.target(*probe) {
+                    if *tip >= target {
+                        if self.triggered.insert(*probe) {
+                            tracing::info!(probe, tip, "target temperature reached");
+                        }
+                    } else if *tip < target - self.hysteresis {
+                        self.triggered.remove(probe);
+                    }
+                }
+
+                let below = self.ambient_min.is_some_and(|min| *ambient < min);
+                let above = self.ambient_max.is_some_and(|max| *ambient > max);
+
+                if below || above {
+                    if self.out_of_band.insert(*probe) {
+                        tracing::info!(probe, ambient, "ambient temperature out of range");
+                    }
+                } else if self
+                    .ambient_min
+                    .is_none_or(|min| *ambient >= min + self.hysteresis)
+                    && self
+                        .ambient_max
+                        .is_none_or(|max| *ambient <= max - self.hysteresis)
+                {
+                    self.out_of_band.remove(probe);
+                }
+            }
+            meater::Event::State(meater::State::Disconnected) => {
+                self.triggered.clear();
+                self.out_of_band.clear();
+            }
+            _ => {}
+        }
+
+        self.buzzer
+            .set(!self.triggered.is_empty() || !self.out_of_band.is_empty())
+    }
+}
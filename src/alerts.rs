@@ -0,0 +1,612 @@
+//! Alert rules declared in the configuration and evaluated against the event stream.
+//!
+//! A rule compares a quantity with a threshold, e.g. `tip >= 95`, `ambient < 100`,
+//! `battery <= 20` or `disconnected > 2min`. Temperatures are given in the configured unit,
+//! durations with an `s`, `min` or `h` suffix. A rule fires once when its condition starts to
+//! hold and only clears again after the value moved `hysteresis` back past the threshold. A
+//! cleared rule does not fire again before its cooldown passed, so values wobbling around the
+//! threshold do not cause alert storms. A condition still holding once the cooldown passed fires
+//! then.
+//!
+//! Regardless of the rules, an alert is raised whenever the battery of a probe runs low so it
+//! never dies mid-cook unnoticed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::units::Unit;
+
+/// Interval in which time based rules are evaluated.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Quantity a rule looks at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Quantity {
+    Tip,
+    Ambient,
+    Battery,
+    /// Time since the last probe disconnected.
+    Disconnected,
+}
+
+/// Comparison of the quantity with the threshold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Comparison {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+        }
+    }
+
+    fn holds(self, value: f32, threshold: f32) -> bool {
+        match self {
+            Self::Less => value < threshold,
+            Self::LessEqual => value <= threshold,
+            Self::Greater => value > threshold,
+            Self::GreaterEqual => value >= threshold,
+        }
+    }
+
+    /// Return `true` if `value` moved back more than `hysteresis` past `threshold`.
+    fn clears(self, value: f32, threshold: f32, hysteresis: f32) -> bool {
+        match self {
+            Self::Less | Self::LessEqual => value > threshold + hysteresis,
+            Self::Greater | Self::GreaterEqual => value < threshold - hysteresis,
+        }
+    }
+}
+
+/// Condition like `tip >= 95`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Condition {
+    text: String,
+    quantity: Quantity,
+    comparison: Comparison,
+    /// Threshold in the configured unit, percent or seconds.
+    threshold: f32,
+}
+
+impl FromStr for Condition {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.split_whitespace();
+
+        let (Some(quantity), Some(comparison), Some(threshold), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("expected `<quantity> <comparison> <threshold>` but got `{text}`");
+        };
+
+        let quantity = match quantity {
+            "tip" => Quantity::Tip,
+            "ambient" => Quantity::Ambient,
+            "battery" => Quantity::Battery,
+            "disconnected" => Quantity::Disconnected,
+            _ => bail!("unknown quantity `{quantity}`"),
+        };
+
+        let comparison = match comparison {
+            "<" => Comparison::Less,
+            "<=" => Comparison::LessEqual,
+            ">" => Comparison::Greater,
+            ">=" => Comparison::GreaterEqual,
+            _ => bail!("unknown comparison `{comparison}`"),
+        };
+
+        let threshold = if quantity == Quantity::Disconnected {
+            parse_duration(threshold)?.as_secs_f32()
+        } else {
+            threshold
+                .parse()
+                .map_err(|_| anyhow!("invalid threshold `{threshold}`"))?
+        };
+
+        Ok(Self {
+            text: text.to_string(),
+            quantity,
+            comparison,
+            threshold,
+        })
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Parse durations like `30s`, `2min` or `1h`.
+fn parse_duration(text: &str) -> anyhow::Result<Duration> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .ok_or_else(|| anyhow!("duration `{text}` lacks a unit (s, min or h)"))?;
+
+    let (value, unit) = text.split_at(split);
+
+    let value: f32 = value
+        .parse()
+        .map_err(|_| anyhow!("invalid duration `{text}`"))?;
+
+    let factor = match unit {
+        "s" => 1.0,
+        "min" => 60.0,
+        "h" => 3600.0,
+        _ => bail!("unknown unit `{unit}` of duration `{text}`"),
+    };
+
+    Ok(Duration::from_secs_f32(value * factor))
+}
+
+/// Alert rule as given in the configuration.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Rule {
+    /// Condition the rule fires on, e.g. `tip >= 95`.
+    pub condition: Condition,
+    /// Only look at this probe, numbered from one, instead of all.
+    #[serde(default)]
+    pub probe: Option<usize>,
+    /// Amount the value has to move back past the threshold before the rule clears.
+    #[serde(default = "default_hysteresis")]
+    pub hysteresis: f32,
+    /// Minutes before a cleared rule may fire again.
+    #[serde(default = "default_cooldown")]
+    pub cooldown_minutes: f32,
+}
+
+/// Return rules firing when the ambient temperature leaves the band between `min` and `max`.
+///
+/// Like all rules, the thresholds and the `hysteresis` are given in the configured unit and only
+/// converted by the [`Engine`].
+pub fn ambient_band(min: Option<f32>, max: Option<f32>, hysteresis: f32) -> Vec<Rule> {
+    let rule = |comparison: Comparison, threshold: f32| Rule {
+        condition: Condition {
+            text: format!("ambient {} {threshold}", comparison.symbol()),
+            quantity: Quantity::Ambient,
+            comparison,
+            threshold,
+        },
+        probe: None,
+        hysteresis,
+        cooldown_minutes: default_cooldown(),
+    };
+
+    min.map(|min| rule(Comparison::Less, min))
+        .into_iter()
+        .chain(max.map(|max| rule(Comparison::Greater, max)))
+        .collect()
+}
+
+fn default_hysteresis() -> f32 {
+    1.0
+}
+
+fn default_cooldown() -> f32 {
+    5.0
+}
+
+/// What raised an alert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A configured rule.
+    Rule,
+    /// The battery of the probe ran low, at this level in percent if known.
+    LowBattery { percent: Option<u16> },
+}
+
+/// Alert raised by a rule or a low battery.
+#[derive(Clone, Debug)]
+pub struct Alert {
+    #[cfg_attr(not(feature = "notify"), allow(dead_code))]
+    pub kind: Kind,
+    /// Condition of the rule, as configured.
+    pub condition: String,
+    /// Probe the value belongs to, numbered from zero.
+    pub probe: Option<usize>,
+    /// Value that triggered the rule, formatted with its unit.
+    pub value: String,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(probe) = self.probe {
+            write!(f, "Probe {}: ", probe + 1)?;
+        }
+
+        write!(f, "{} (now {})", self.condition, self.value)
+    }
+}
+
+/// Rule with thresholds converted to °C, percent and seconds.
+struct Compiled {
+    condition: Condition,
+    threshold: f32,
+    hysteresis: f32,
+    cooldown: Duration,
+    probe: Option<usize>,
+}
+
+/// Whether a rule currently holds and when it last fired.
+#[derive(Default)]
+struct Trigger {
+    active: bool,
+    fired: Option<Instant>,
+}
+
+/// Evaluates rules against events.
+pub struct Engine {
+    unit: Unit,
+    rules: Vec<Compiled>,
+    /// Triggers of each rule and probe, time based rules use no probe.
+    triggers: BTreeMap<(usize, Option<usize>), Trigger>,
+    /// Time the last probe disconnected at.
+    disconnected: Option<Instant>,
+    /// Latest battery level of each probe.
+    batteries: BTreeMap<usize, u16>,
+    /// Probes with a low battery that was already alerted.
+    low: BTreeSet<usize>,
+}
+
+impl Engine {
+    pub fn new(rules: &[Rule], unit: Unit) -> Self {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let condition = &rule.condition;
+
+                let (threshold, hysteresis) = match condition.quantity {
+                    Quantity::Tip | Quantity::Ambient => (
+                        unit.to_celsius(condition.threshold),
+                        unit.difference_to_celsius(rule.hysteresis),
+                    ),
+                    Quantity::Battery => (condition.threshold, rule.hysteresis),
+                    // Clears as soon as a probe connects again.
+                    Quantity::Disconnected => (condition.threshold, 0.0),
+                };
+
+                Compiled {
+                    condition: condition.clone(),
+                    threshold,
+                    hysteresis,
+                    cooldown: Duration::from_secs_f32(rule.cooldown_minutes.max(0.0) * 60.0),
+                    probe: rule.probe.map(|probe| probe.saturating_sub(1)),
+                }
+            })
+            .collect();
+
+        Self {
+            unit,
+            rules,
+            triggers: BTreeMap::new(),
+            disconnected: None,
+            batteries: BTreeMap::new(),
+            low: BTreeSet::new(),
+        }
+    }
+
+    /// Evaluate all rules on `quantity` of `probe` with `value` and return the alerts fired.
+    fn evaluate(
+        &mut self,
+        quantity: Quantity,
+        probe: Option<usize>,
+        value: f32,
+        now: Instant,
+    ) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            if rule.condition.quantity != quantity
+                || rule.probe.is_some_and(|only| probe != Some(only))
+            {
+                continue;
+            }
+
+            let trigger = self.triggers.entry((index, probe)).or_default();
+
+            if trigger.active {
+                if rule
+                    .condition
+                    .comparison
+                    .clears(value, rule.threshold, rule.hysteresis)
+                {
+                    trigger.active = false;
+                }
+
+                continue;
+            }
+
+            if !rule.condition.comparison.holds(value, rule.threshold) {
+                continue;
+            }
+
+            // Still inactive during the cooldown, so the rule fires once it passed.
+            if trigger
+                .fired
+                .is_some_and(|fired| now.duration_since(fired) < rule.cooldown)
+            {
+                continue;
+            }
+
+            trigger.active = true;
+            trigger.fired = Some(now);
+
+            let value = match quantity {
+                Quantity::Tip | Quantity::Ambient => {
+                    format!("{:.1}{}", self.unit.convert(value), self.unit.symbol())
+                }
+                Quantity::Battery => format!("{value:.0}%"),
+                Quantity::Disconnected => format!("{:.0} min", value / 60.0),
+            };
+
+            alerts.push(Alert {
+                kind: Kind::Rule,
+                condition: rule.condition.to_string(),
+                probe,
+                value,
+            });
+        }
+
+        alerts
+    }
+
+    /// Evaluate the rules affected by `event` received at `now`.
+    pub fn update(&mut self, event: &meater::Event, now: Instant) -> Vec<Alert> {
+        match *event {
+            meater::Event::State(meater::State::Connected) => {
+                self.disconnected = None;
+                self.evaluate(Quantity::Disconnected, None, 0.0, now)
+            }
+            meater::Event::State(meater::State::Disconnected) => {
+                self.disconnected.get_or_insert(now);
+                Vec::new()
+            }
+            meater::Event::State(_)
+            | meater::Event::Rssi { .. }
+            | meater::Event::Sensors { .. }
+            | meater::Event::ProbeRemoved { .. }
+            | meater::Event::ProbeInserted { .. }
+            | meater::Event::DecodeError { .. }
+            | meater::Event::DeviceInfo { .. }
+            | meater::Event::Error { .. }
+            | meater::Event::SessionEnded => Vec::new(),
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => {
+                let mut alerts = self.evaluate(Quantity::Tip, Some(probe), tip, now);
+                alerts.extend(self.evaluate(Quantity::Ambient, Some(probe), ambient, now));
+                alerts
+            }
+            meater::Event::Battery { probe, percent } => {
+                self.batteries.insert(probe, percent);
+                self.evaluate(Quantity::Battery, Some(probe), f32::from(percent), now)
+            }
+            meater::Event::BatteryState { probe, state } => self.battery_state(probe, state),
+        }
+    }
+
+    /// Return the alert of a battery of `probe` that just ran low according to `state`.
+    fn battery_state(&mut self, probe: usize, state: meater::BatteryState) -> Vec<Alert> {
+        if state != meater::BatteryState::Low {
+            self.low.remove(&probe);
+            return Vec::new();
+        }
+
+        // Sent again after reconnecting, which is no news.
+        if !self.low.insert(probe) {
+            return Vec::new();
+        }
+
+        let percent = self.batteries.get(&probe).copied();
+
+        let value = match percent {
+            Some(percent) => format!("{percent}%"),
+            None => "-".to_string(),
+        };
+
+        vec![Alert {
+            kind: Kind::LowBattery { percent },
+            condition: "battery low".to_string(),
+            probe: Some(probe),
+            value,
+        }]
+    }
+
+    /// Evaluate the time based rules at `now`.
+    pub fn tick(&mut self, now: Instant) -> Vec<Alert> {
+        match self.disconnected {
+            Some(since) => {
+                let elapsed = now.duration_since(since).as_secs_f32();
+                self.evaluate(Quantity::Disconnected, None, elapsed, now)
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Evaluate the rules of `engine` against the events received on `receiver`, log all alerts and
+/// forward them to `sender` if given.
+pub async fn run(
+    mut engine: Engine,
+    mut receiver: mpsc::Receiver<meater::Event>,
+    sender: Option<mpsc::Sender<Alert>>,
+) {
+    let mut interval = tokio::time::interval(TICK_INTERVAL);
+
+    loop {
+        let alerts = tokio::select! {
+            event = receiver.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                engine.update(&event, Instant::now())
+            }
+            _ = interval.tick() => engine.tick(Instant::now()),
+        };
+
+        for alert in alerts {
+            tracing::warn!("alert: {alert}");
+
+            if let Some(sender) = &sender {
+                if sender.try_send(alert).is_err() {
+                    tracing::warn!("notifications are lagging behind, dropping alert");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(condition: &str, cooldown_minutes: f32) -> Rule {
+        Rule {
+            condition: condition.parse().unwrap(),
+            probe: None,
+            hysteresis: 1.0,
+            cooldown_minutes,
+        }
+    }
+
+    fn tip(tip: f32) -> meater::Event {
+        meater::Event::Temperature {
+            probe: 0,
+            tip,
+            ambient: 100.0,
+        }
+    }
+
+    fn minutes(start: Instant, minutes: u64) -> Instant {
+        start + Duration::from_secs(minutes * 60)
+    }
+
+    #[test]
+    fn fire_once_while_holding() {
+        let mut engine = Engine::new(&[rule("tip >= 95", 5.0)], Unit::Celsius);
+        let start = Instant::now();
+
+        assert!(engine.update(&tip(90.0), start).is_empty());
+
+        let alerts = engine.update(&tip(95.0), minutes(start, 1));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].condition, "tip >= 95");
+        assert_eq!(alerts[0].probe, Some(0));
+        assert_eq!(alerts[0].value, "95.0°C");
+
+        assert!(engine.update(&tip(96.0), minutes(start, 2)).is_empty());
+        assert!(engine.update(&tip(97.0), minutes(start, 20)).is_empty());
+    }
+
+    #[test]
+    fn clear_after_hysteresis() {
+        let mut engine = Engine::new(&[rule("tip >= 95", 0.0)], Unit::Celsius);
+        let start = Instant::now();
+
+        assert_eq!(engine.update(&tip(95.0), start).len(), 1);
+
+        // Within the hysteresis, the rule stays active.
+        assert!(engine.update(&tip(94.5), minutes(start, 1)).is_empty());
+        assert!(engine.update(&tip(95.0), minutes(start, 2)).is_empty());
+
+        // Moved back past the hysteresis, the rule clears and fires again.
+        assert!(engine.update(&tip(93.5), minutes(start, 3)).is_empty());
+        assert_eq!(engine.update(&tip(95.0), minutes(start, 4)).len(), 1);
+    }
+
+    #[test]
+    fn clear_after_hysteresis_in_fahrenheit() {
+        let mut engine = Engine::new(&[rule("tip >= 203", 0.0)], Unit::Fahrenheit);
+        let start = Instant::now();
+
+        assert_eq!(engine.update(&tip(95.0), start).len(), 1);
+
+        // 0.5 °C are 0.9 °F, within the hysteresis of 1 °F.
+        assert!(engine.update(&tip(94.5), minutes(start, 1)).is_empty());
+        assert!(engine.update(&tip(95.0), minutes(start, 2)).is_empty());
+        assert!(engine.update(&tip(94.0), minutes(start, 3)).is_empty());
+        assert_eq!(engine.update(&tip(95.0), minutes(start, 4)).len(), 1);
+    }
+
+    #[test]
+    fn clear_ambient_band_after_hysteresis_in_fahrenheit() {
+        let rules = ambient_band(None, Some(212.0), 2.0);
+        let mut engine = Engine::new(&rules, Unit::Fahrenheit);
+        let start = Instant::now();
+        let ambient = |ambient| meater::Event::Temperature {
+            probe: 0,
+            tip: 50.0,
+            ambient,
+        };
+
+        assert_eq!(engine.update(&ambient(101.0), start).len(), 1);
+
+        // 0.5 °C are 0.9 °F, within the hysteresis of 2 °F, 1.5 °C are 2.7 °F past it.
+        assert!(engine.update(&ambient(99.5), minutes(start, 1)).is_empty());
+        assert!(engine.update(&ambient(101.0), minutes(start, 2)).is_empty());
+        assert!(engine.update(&ambient(98.5), minutes(start, 3)).is_empty());
+        assert_eq!(engine.update(&ambient(101.0), minutes(start, 8)).len(), 1);
+    }
+
+    #[test]
+    fn delay_alerts_during_cooldown() {
+        let mut engine = Engine::new(&[rule("tip >= 95", 5.0)], Unit::Celsius);
+        let start = Instant::now();
+
+        assert_eq!(engine.update(&tip(95.0), start).len(), 1);
+        assert!(engine.update(&tip(90.0), minutes(start, 1)).is_empty());
+
+        // Holds again during the cooldown, fires only once it passed.
+        assert!(engine.update(&tip(95.0), minutes(start, 2)).is_empty());
+        assert!(engine.update(&tip(96.0), minutes(start, 4)).is_empty());
+        assert_eq!(engine.update(&tip(96.0), minutes(start, 5)).len(), 1);
+        assert!(engine.update(&tip(96.0), minutes(start, 6)).is_empty());
+    }
+
+    #[test]
+    fn fire_time_based_rules() {
+        let mut engine = Engine::new(&[rule("disconnected > 2min", 0.0)], Unit::Celsius);
+        let start = Instant::now();
+        let disconnected = meater::Event::State(meater::State::Disconnected);
+        let connected = meater::Event::State(meater::State::Connected);
+
+        assert!(engine.tick(start).is_empty());
+        assert!(engine.update(&disconnected, start).is_empty());
+        assert!(engine.tick(minutes(start, 1)).is_empty());
+        assert_eq!(engine.tick(minutes(start, 3)).len(), 1);
+        assert!(engine.tick(minutes(start, 4)).is_empty());
+
+        assert!(engine.update(&connected, minutes(start, 5)).is_empty());
+        assert!(engine.tick(minutes(start, 6)).is_empty());
+        assert!(engine.update(&disconnected, minutes(start, 6)).is_empty());
+        assert_eq!(engine.tick(minutes(start, 9)).len(), 1);
+    }
+}
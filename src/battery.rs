@@ -0,0 +1,93 @@
+//! Detection of a probe running low on battery or charging.
+//!
+//! The probe reports its level in steps of ten percent. While in use the level only drops, so a
+//! rising level means the probe is back in its charger, until the level drops again.
+
+use crate::client::BatteryState;
+
+/// Tracks the battery level of a single probe.
+#[derive(Clone)]
+pub(crate) struct Detector {
+    /// Level in percent below which the battery counts as low.
+    low: u16,
+    level: Option<u16>,
+    state: Option<BatteryState>,
+}
+
+impl Detector {
+    pub(crate) fn new(low: u16) -> Self {
+        Self {
+            low,
+            level: None,
+            state: None,
+        }
+    }
+
+    /// Update with the battery level `percent` and return the state if it changed, including the
+    /// initial one.
+    pub(crate) fn update(&mut self, percent: u16) -> Option<BatteryState> {
+        let previous = self.level.replace(percent);
+        let rising = previous.is_some_and(|previous| percent > previous);
+        let falling = previous.is_some_and(|previous| percent < previous);
+
+        let state = match self.state {
+            Some(BatteryState::Charging) if !falling => BatteryState::Charging,
+            _ if rising => BatteryState::Charging,
+            _ if percent < self.low => BatteryState::Low,
+            _ => BatteryState::Normal,
+        };
+
+        (self.state.replace(state) != Some(state)).then_some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_initial_state() {
+        assert_eq!(Detector::new(20).update(10), Some(BatteryState::Low));
+        assert_eq!(Detector::new(20).update(20), Some(BatteryState::Normal));
+        assert_eq!(Detector::new(20).update(100), Some(BatteryState::Normal));
+    }
+
+    #[test]
+    fn fall_below_low() {
+        let mut detector = Detector::new(20);
+
+        assert_eq!(detector.update(40), Some(BatteryState::Normal));
+        assert_eq!(detector.update(30), None);
+        assert_eq!(detector.update(20), None);
+        assert_eq!(detector.update(10), Some(BatteryState::Low));
+        assert_eq!(detector.update(10), None);
+        assert_eq!(detector.update(0), None);
+    }
+
+    #[test]
+    fn charge_while_rising() {
+        let mut detector = Detector::new(20);
+
+        assert_eq!(detector.update(10), Some(BatteryState::Low));
+        assert_eq!(detector.update(20), Some(BatteryState::Charging));
+        assert_eq!(detector.update(30), None);
+        // Full batteries stay at the same level while charging.
+        assert_eq!(detector.update(100), None);
+        assert_eq!(detector.update(100), None);
+    }
+
+    #[test]
+    fn stop_charging_when_falling() {
+        let mut detector = Detector::new(20);
+
+        assert_eq!(detector.update(80), Some(BatteryState::Normal));
+        assert_eq!(detector.update(90), Some(BatteryState::Charging));
+        assert_eq!(detector.update(80), Some(BatteryState::Normal));
+
+        let mut detector = Detector::new(20);
+
+        assert_eq!(detector.update(10), Some(BatteryState::Low));
+        assert_eq!(detector.update(20), Some(BatteryState::Charging));
+        assert_eq!(detector.update(10), Some(BatteryState::Low));
+    }
+}
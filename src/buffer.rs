@@ -0,0 +1,274 @@
+//! Buffering of events between the thermometer, the UI and the sinks.
+//!
+//! Every consumer gets its own buffer so a slow display or sink neither holds up the Bluetooth
+//! listener nor the other consumers. Once a buffer is full the oldest sample, i.e. temperatures,
+//! battery levels and signal strength, is dropped to make room. Everything else, e.g. state
+//! changes and errors, is never dropped, it is rare and consumers rely on seeing it.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tokio::sync::{mpsc, Notify};
+
+/// Minimum time between two warnings about dropped samples of the same consumer.
+const WARN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Event buffer settings.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Events buffered for the UI and each sink before the oldest samples are dropped.
+    pub capacity: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { capacity: 64 }
+    }
+}
+
+/// Return whether `event` is a sample that may be dropped when the buffer is full.
+fn is_sample(event: &meater::Event) -> bool {
+    matches!(
+        event,
+        meater::Event::Temperature { .. }
+            | meater::Event::Battery { .. }
+            | meater::Event::Rssi { .. }
+            | meater::Event::Sensors { .. }
+    )
+}
+
+/// Events not yet taken by the consumer, oldest first.
+struct Buffer {
+    events: VecDeque<meater::Event>,
+    capacity: usize,
+    /// Whether the sender is gone, the remaining events are still delivered.
+    closed: bool,
+    /// Samples dropped since the last warning and when it was logged.
+    dropped: usize,
+    warned: Option<Instant>,
+}
+
+impl Buffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            capacity: capacity.max(1),
+            closed: false,
+            dropped: 0,
+            warned: None,
+        }
+    }
+
+    fn push(&mut self, event: meater::Event) {
+        if self.events.len() >= self.capacity {
+            let oldest = self.events.iter().position(is_sample);
+
+            match oldest {
+                Some(index) => {
+                    self.events.remove(index);
+                    self.drop_sample();
+                }
+                // Only events that must not be lost are buffered, keep them all.
+                None if !is_sample(&event) => {}
+                None => {
+                    self.drop_sample();
+                    return;
+                }
+            }
+        }
+
+        self.events.push_back(event);
+    }
+
+    /// Count a dropped sample and warn about the samples dropped since the last warning, at most
+    /// once per [`WARN_INTERVAL`].
+    fn drop_sample(&mut self) {
+        self.dropped += 1;
+
+        let now = Instant::now();
+
+        if self
+            .warned
+            .is_some_and(|warned| now.duration_since(warned) < WARN_INTERVAL)
+        {
+            return;
+        }
+
+        tracing::warn!(
+            dropped = self.dropped,
+            "consumer is lagging behind, dropping oldest samples"
+        );
+
+        self.dropped = 0;
+        self.warned = Some(now);
+    }
+}
+
+struct Shared {
+    buffer: Mutex<Buffer>,
+    changed: Notify,
+}
+
+/// Sending half of a buffered channel, sending never waits for the consumer.
+pub struct Sender {
+    shared: Arc<Shared>,
+}
+
+impl Sender {
+    pub fn send(&self, event: meater::Event) {
+        if let Ok(mut buffer) = self.shared.buffer.lock() {
+            buffer.push(event);
+        }
+
+        self.shared.changed.notify_one();
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        if let Ok(mut buffer) = self.shared.buffer.lock() {
+            buffer.closed = true;
+        }
+
+        self.shared.changed.notify_one();
+    }
+}
+
+/// Create a channel buffering up to `capacity` samples for a slow consumer.
+///
+/// The events are passed on to the returned receiver by a task, which ends once the sender is
+/// dropped and all events are delivered or the receiver is dropped.
+pub fn channel(capacity: usize) -> (Sender, mpsc::Receiver<meater::Event>) {
+    let shared = Arc::new(Shared {
+        buffer: Mutex::new(Buffer::new(capacity)),
+        changed: Notify::new(),
+    });
+
+    let (sender, receiver) = mpsc::channel(1);
+    let forwarded = shared.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let next = match forwarded.buffer.lock() {
+                Ok(mut buffer) => match buffer.events.pop_front() {
+                    Some(event) => Some(event),
+                    None if buffer.closed => return,
+                    None => None,
+                },
+                Err(_) => return,
+            };
+
+            match next {
+                Some(event) => {
+                    if sender.send(event).await.is_err() {
+                        return;
+                    }
+                }
+                None => forwarded.changed.notified().await,
+            }
+        }
+    });
+
+    (Sender { shared }, receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temperature(tip: f32) -> meater::Event {
+        meater::Event::Temperature {
+            probe: 0,
+            tip,
+            ambient: 100.0,
+        }
+    }
+
+    fn state(state: meater::State) -> meater::Event {
+        meater::Event::State(state)
+    }
+
+    fn tips(buffer: &Buffer) -> Vec<Option<f32>> {
+        buffer
+            .events
+            .iter()
+            .map(|event| match event {
+                meater::Event::Temperature { tip, .. } => Some(*tip),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn drop_oldest_sample() {
+        let mut buffer = Buffer::new(3);
+
+        for tip in [1.0, 2.0, 3.0, 4.0, 5.0] {
+            buffer.push(temperature(tip));
+        }
+
+        assert_eq!(tips(&buffer), [Some(3.0), Some(4.0), Some(5.0)]);
+        // The first drop was warned about right away, the second waits for the next warning.
+        assert_eq!(buffer.dropped, 1);
+    }
+
+    #[test]
+    fn keep_other_events() {
+        let mut buffer = Buffer::new(3);
+
+        buffer.push(temperature(1.0));
+        buffer.push(state(meater::State::Connected));
+        buffer.push(temperature(2.0));
+        buffer.push(state(meater::State::Disconnected));
+
+        // The oldest sample made room, the state changes are kept.
+        assert_eq!(tips(&buffer), [None, Some(2.0), None]);
+
+        buffer.push(state(meater::State::Connecting));
+        assert_eq!(tips(&buffer), [None, None, None]);
+        // The first drop was warned about right away, the second waits for the next warning.
+        assert_eq!(buffer.dropped, 1);
+    }
+
+    #[test]
+    fn exceed_capacity_with_other_events() {
+        let mut buffer = Buffer::new(2);
+
+        buffer.push(state(meater::State::Connecting));
+        buffer.push(state(meater::State::Connected));
+        buffer.push(state(meater::State::Disconnected));
+
+        // Nothing may be dropped to make room, the buffer grows instead.
+        assert_eq!(buffer.events.len(), 3);
+
+        // No room for samples, the new one is dropped.
+        buffer.push(temperature(1.0));
+        assert_eq!(tips(&buffer), [None, None, None]);
+    }
+
+    #[tokio::test]
+    async fn deliver_remaining_events_after_close() {
+        let (sender, mut receiver) = channel(2);
+
+        sender.send(state(meater::State::Connected));
+        sender.send(temperature(1.0));
+        sender.send(temperature(2.0));
+        sender.send(temperature(3.0));
+        drop(sender);
+
+        let mut events = Vec::new();
+
+        while let Some(event) = receiver.recv().await {
+            events.push(event);
+        }
+
+        assert!(matches!(
+            events[0],
+            meater::Event::State(meater::State::Connected)
+        ));
+        assert!(matches!(events[1..], [meater::Event::Temperature { tip, .. }] if tip == 3.0));
+    }
+}
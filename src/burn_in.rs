@@ -0,0 +1,96 @@
+//! Mitigation of OLED burn-in during long cooks.
+//!
+//! The same digits sitting in the same place for hours wear out the pixels unevenly. The rendered
+//! content is therefore moved around by a few pixels every few minutes and optionally inverted
+//! from time to time.
+
+use std::time::{Duration, Instant};
+
+use embedded_graphics::geometry::Point;
+use serde::Deserialize;
+
+use crate::display::Canvas;
+
+/// Burn-in mitigation settings.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Maximum number of pixels the content is moved in each direction, zero disables shifting.
+    pub shift: u8,
+    /// Minutes between two shifts.
+    pub shift_minutes: u64,
+    /// Minutes after which the display is inverted or back to normal, disabled if not set.
+    pub invert_minutes: Option<u64>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            shift: 0,
+            shift_minutes: 5,
+            invert_minutes: None,
+        }
+    }
+}
+
+/// Transformation applied to a frame before it is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Transform {
+    offset: Point,
+    inverted: bool,
+}
+
+impl Transform {
+    /// Apply the transformation to `canvas`.
+    pub fn apply(&self, canvas: &mut Canvas) {
+        if self.offset != Point::zero() {
+            canvas.translate(self.offset);
+        }
+
+        if self.inverted {
+            canvas.invert();
+        }
+    }
+}
+
+/// Computes the current [`Transform`] from the time since start.
+pub struct BurnIn {
+    shift: i32,
+    shift_interval: Duration,
+    invert_interval: Option<Duration>,
+    started: Instant,
+}
+
+impl BurnIn {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            shift: options.shift.into(),
+            shift_interval: Duration::from_secs(options.shift_minutes.max(1) * 60),
+            invert_interval: options
+                .invert_minutes
+                .map(|minutes| Duration::from_secs(minutes.max(1) * 60)),
+            started: Instant::now(),
+        }
+    }
+
+    /// Transformation to apply right now.
+    ///
+    /// The offset walks row by row through all positions of the square spanned by `shift`.
+    pub fn transform(&self) -> Transform {
+        let elapsed = self.started.elapsed();
+        let side = (2 * self.shift + 1) as u64;
+        let step = elapsed.as_secs() / self.shift_interval.as_secs();
+        let position = step % (side * side);
+
+        let offset = Point::new(
+            (position % side) as i32 - self.shift,
+            (position / side) as i32 - self.shift,
+        );
+
+        let inverted = self
+            .invert_interval
+            .is_some_and(|interval| (elapsed.as_secs() / interval.as_secs()) % 2 == 1);
+
+        Transform { offset, inverted }
+    }
+}
@@ -0,0 +1,62 @@
+//! Fan-out of the events of the thermometer to all subsystems.
+//!
+//! The display, alarm, loggers and exporters each subscribe to the bus and get their own
+//! [buffered](crate::buffer) receiver, so one falling behind neither holds up the thermometer nor
+//! the others.
+
+use tokio::sync::mpsc;
+
+use crate::buffer;
+use crate::smoothing::Smoother;
+
+/// Events of the thermometer passed on to every subscriber.
+pub struct Bus {
+    subscribers: Vec<buffer::Sender>,
+    capacity: usize,
+}
+
+impl Bus {
+    /// Create a bus buffering up to `capacity` samples for each subscriber.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            subscribers: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Return a receiver getting all events published from now on.
+    pub fn subscribe(&mut self) -> mpsc::Receiver<meater::Event> {
+        let (sender, receiver) = buffer::channel(self.capacity);
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Pass `event` on to all subscribers.
+    pub fn publish(&self, event: &meater::Event) {
+        for subscriber in &self.subscribers {
+            subscriber.send(event.clone());
+        }
+    }
+
+    /// Publish all events from `receiver` with temperatures smoothed by `smoother`, along with
+    /// the events the program itself sends on `own`, e.g. when the user ends a session.
+    ///
+    /// Events are taken as soon as they arrive so the thermometer never waits. The receivers of
+    /// the subscribers are closed once `receiver` is.
+    pub async fn run(
+        self,
+        mut receiver: mpsc::Receiver<meater::Event>,
+        mut own: mpsc::Receiver<meater::Event>,
+        mut smoother: Smoother,
+    ) {
+        loop {
+            tokio::select! {
+                event = receiver.recv() => match event {
+                    Some(event) => self.publish(&smoother.apply(event)),
+                    None => break,
+                },
+                Some(event) = own.recv() => self.publish(&event),
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Push buttons connected to GPIO pins.
+
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "device")]
+use anyhow::Context;
+
+/// Time the level of a pin has to be stable to count as a change.
+const DEBOUNCE: Duration = Duration::from_millis(30);
+
+/// Push button connected to a GPIO pin and ground.
+pub struct Button {
+    #[cfg(feature = "device")]
+    pin: rppal::gpio::InputPin,
+    /// Debounced state.
+    pressed: bool,
+    /// Raw state when last polled and since when it is unchanged.
+    reading: bool,
+    changed: Instant,
+}
+
+impl Button {
+    #[cfg(feature = "device")]
+    pub fn new(pin: u8) -> anyhow::Result<Self> {
+        let pin = rppal::gpio::Gpio::new()
+            .context("unable to access GPIO")?
+            .get(pin)
+            .with_context(|| format!("unable to get GPIO pin {pin}"))?
+            .into_input_pullup();
+
+        Ok(Self {
+            pin,
+            pressed: false,
+            reading: false,
+            changed: Instant::now(),
+        })
+    }
+
+    #[cfg(not(feature = "device"))]
+    pub fn new(pin: u8) -> anyhow::Result<Self> {
+        tracing::info!(pin, "no GPIO available, button is ignored");
+
+        Ok(Self {
+            pressed: false,
+            reading: false,
+            changed: Instant::now(),
+        })
+    }
+
+    /// Return `true` while the pin is pulled low, without debouncing.
+    fn is_low(&self) -> bool {
+        #[cfg(feature = "device")]
+        return self.pin.is_low();
+
+        #[cfg(not(feature = "device"))]
+        return false;
+    }
+
+    /// Return `true` if the button went down since the last call.
+    ///
+    /// Has to be called regularly and more often than [`DEBOUNCE`] to catch short presses.
+    pub fn was_pressed(&mut self) -> bool {
+        let reading = self.is_low();
+
+        if reading != self.reading {
+            self.reading = reading;
+            self.changed = Instant::now();
+        }
+
+        if self.reading == self.pressed || self.changed.elapsed() < DEBOUNCE {
+            return false;
+        }
+
+        self.pressed = self.reading;
+        self.pressed
+    }
+}
@@ -0,0 +1,108 @@
+//! Measuring the calibration offsets of probes sitting in a bath of known temperature.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use anyhow::anyhow;
+
+use crate::config::Config;
+use crate::probes;
+
+/// Tip readings of a probe averaged into its offset.
+const SAMPLES: usize = 30;
+
+/// Maximum spread in °C of the averaged readings, a larger one means the probe has not settled.
+const MAX_SPREAD: f32 = 0.3;
+
+/// Bath of known temperature the probes sit in.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum Reference {
+    /// Stirred ice water at 0 °C.
+    Ice,
+    /// Boiling water at 100 °C, only exact at sea level.
+    Boiling,
+}
+
+impl Reference {
+    fn celsius(self) -> f32 {
+        match self {
+            Self::Ice => 0.0,
+            Self::Boiling => 100.0,
+        }
+    }
+}
+
+/// Return whether the last readings of a probe are complete and steady.
+fn settled(readings: &VecDeque<f32>) -> bool {
+    let min = readings.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = readings.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    readings.len() == SAMPLES && max - min <= MAX_SPREAD
+}
+
+/// Read the probes without calibration until their tip temperatures settled in the `reference`
+/// bath and print the offsets to paste into the configuration file.
+///
+/// The ambient sensor sits in the handle outside of the bath, its configured offsets are kept.
+pub async fn run(mut config: Config, reference: Reference) -> anyhow::Result<()> {
+    let ambient: Vec<f32> = config
+        .probe
+        .calibration
+        .drain(..)
+        .map(|calibration| calibration.ambient)
+        .collect();
+
+    let (source, mut receiver) = probes::create(&config)?;
+
+    tracing::info!(
+        "put the probes into the {reference:?} bath, waiting for {SAMPLES} steady readings each"
+    );
+
+    let measure = async {
+        let mut readings: BTreeMap<usize, VecDeque<f32>> = BTreeMap::new();
+
+        while let Some(event) = receiver.recv().await {
+            let meater::Event::Temperature { probe, tip, .. } = event else {
+                continue;
+            };
+
+            tracing::info!(probe = probe + 1, tip, "reading");
+
+            let window = readings.entry(probe).or_default();
+            window.push_back(tip);
+
+            if window.len() > SAMPLES {
+                window.pop_front();
+            }
+
+            if readings.values().all(settled) {
+                return Some(readings);
+            }
+        }
+
+        None
+    };
+
+    let readings = tokio::select! {
+        readings = measure => readings,
+        result = source.run() => {
+            result?;
+            None
+        }
+    }
+    .ok_or_else(|| anyhow!("probes went away before their readings settled"))?;
+
+    let last = readings.keys().copied().max().unwrap_or_default();
+
+    for probe in 0..=last {
+        let tip = readings.get(&probe).map_or(0.0, |readings| {
+            reference.celsius() - readings.iter().sum::<f32>() / readings.len() as f32
+        });
+        let ambient = ambient.get(probe).copied().unwrap_or_default();
+
+        println!("[[probe.calibration]]");
+        println!("tip = {tip:.2}");
+        println!("ambient = {ambient:.2}");
+        println!();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+//! Prediction of the carryover cooking of meat resting after it was pulled from the heat.
+//!
+//! A sharp drop of the ambient temperature means the probe was pulled from the grill. The tip keeps
+//! rising for a while because the outer layers still pass their heat on to the center, with a rate
+//! of rise decaying roughly exponentially. The peak is predicted from the current rate of rise and
+//! that decay.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Drop of the ambient temperature in °C within [`DROP_WINDOW`] that counts as pulled from heat.
+const DROP: f32 = 20.0;
+
+/// Time span the ambient temperature has to drop by [`DROP`] in.
+const DROP_WINDOW: Duration = Duration::from_secs(2 * 60);
+
+/// Time span of tip temperatures the current rate of rise is estimated from.
+const RATE_WINDOW: Duration = Duration::from_secs(2 * 60);
+
+/// Minimum time span of tip temperatures before the rate of rise is used.
+const MIN_SPAN: Duration = Duration::from_secs(30);
+
+/// Time constant of the exponential decay of the rate of rise while resting.
+const DECAY: Duration = Duration::from_secs(5 * 60);
+
+/// Values since the meat was pulled from the heat.
+struct Rest {
+    /// Lowest ambient temperature in °C.
+    ambient: f32,
+    /// Highest tip temperature in °C.
+    peak: f32,
+}
+
+/// Predicts the peak tip temperature of a resting probe.
+#[derive(Default)]
+pub struct Predictor {
+    ambients: VecDeque<(Instant, f32)>,
+    tips: VecDeque<(Instant, f32)>,
+    rest: Option<Rest>,
+}
+
+/// Drop all samples of `samples` older than `window` before `at`.
+fn trim(samples: &mut VecDeque<(Instant, f32)>, at: Instant, window: Duration) {
+    while samples
+        .front()
+        .is_some_and(|(first, _)| at.duration_since(*first) > window)
+    {
+        samples.pop_front();
+    }
+}
+
+impl Predictor {
+    /// Add the `tip` and `ambient` temperatures measured at `at`.
+    pub fn push(&mut self, at: Instant, tip: f32, ambient: f32) {
+        self.ambients.push_back((at, ambient));
+        self.tips.push_back((at, tip));
+        trim(&mut self.ambients, at, DROP_WINDOW);
+        trim(&mut self.tips, at, RATE_WINDOW);
+
+        if let Some(rest) = &mut self.rest {
+            rest.ambient = rest.ambient.min(ambient);
+            rest.peak = rest.peak.max(tip);
+
+            // Back on the heat.
+            if ambient >= rest.ambient + DROP {
+                tracing::info!("meat is back on the heat");
+                self.rest = None;
+            }
+
+            return;
+        }
+
+        let highest = self
+            .ambients
+            .iter()
+            .map(|(_, ambient)| *ambient)
+            .fold(f32::MIN, f32::max);
+
+        if highest - ambient >= DROP {
+            tracing::info!("meat was pulled from the heat and rests");
+            self.rest = Some(Rest { ambient, peak: tip });
+        }
+    }
+
+    /// Predicted peak tip temperature in °C if the meat rests.
+    pub fn peak(&self) -> Option<f32> {
+        let rest = self.rest.as_ref()?;
+
+        let (Some((first_at, first)), Some((last_at, last))) =
+            (self.tips.front(), self.tips.back())
+        else {
+            return Some(rest.peak);
+        };
+
+        let span = last_at.duration_since(*first_at);
+
+        if span < MIN_SPAN {
+            return Some(rest.peak);
+        }
+
+        // The remaining rise of a rate decaying exponentially is the rate times its time constant.
+        let rate = (last - first) / span.as_secs_f32();
+        Some(rest.peak.max(last + rate.max(0.0) * DECAY.as_secs_f32()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(start: Instant, seconds: u64) -> Instant {
+        start + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn predict_peak_once_pulled() {
+        let mut predictor = Predictor::default();
+        let start = Instant::now();
+
+        predictor.push(start, 55.0, 150.0);
+        predictor.push(seconds(start, 30), 56.0, 150.0);
+        assert_eq!(predictor.peak(), None);
+
+        // Rising by 1 °C per 30 seconds, decaying over five minutes.
+        predictor.push(seconds(start, 60), 57.0, 120.0);
+        let peak = predictor.peak().unwrap();
+        assert!((peak - 67.0).abs() < 0.01, "{peak}");
+
+        // Never below the highest temperature seen.
+        predictor.push(seconds(start, 90), 54.0, 100.0);
+        assert_eq!(predictor.peak(), Some(57.0));
+    }
+
+    #[test]
+    fn end_rest_back_on_heat() {
+        let mut predictor = Predictor::default();
+        let start = Instant::now();
+
+        predictor.push(start, 55.0, 150.0);
+        predictor.push(seconds(start, 30), 56.0, 120.0);
+        assert!(predictor.peak().is_some());
+
+        predictor.push(seconds(start, 60), 56.0, 145.0);
+        assert_eq!(predictor.peak(), None);
+    }
+
+    #[test]
+    fn keep_rest_across_gap() {
+        let mut predictor = Predictor::default();
+        let start = Instant::now();
+
+        predictor.push(start, 55.0, 150.0);
+        predictor.push(seconds(start, 30), 60.0, 120.0);
+
+        // No readings for a while, e.g. while the probe was out of range.
+        predictor.push(seconds(start, 600), 58.0, 40.0);
+        assert_eq!(predictor.peak(), Some(60.0));
+    }
+
+    #[test]
+    fn discard_stale_samples() {
+        let mut predictor = Predictor::default();
+        let start = Instant::now();
+
+        predictor.push(start, 40.0, 150.0);
+
+        // The drop from before the gap is too old to count as pulled from heat.
+        predictor.push(seconds(start, 300), 55.0, 125.0);
+        assert_eq!(predictor.peak(), None);
+
+        predictor.push(seconds(start, 330), 56.0, 100.0);
+        let peak = predictor.peak().unwrap();
+
+        // Only the rise after the gap counts.
+        assert!((peak - 56.0 - 300.0 / 30.0).abs() < 0.01, "{peak}");
+    }
+}
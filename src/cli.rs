@@ -0,0 +1,260 @@
+//! Command line arguments.
+//!
+//! All settings can also be given in the configuration file, see [`crate::config`]. Defaults are
+//! listed in the help text because they are applied by the configuration.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::calibrate;
+use crate::display;
+#[cfg(feature = "json")]
+use crate::json;
+use crate::led;
+use crate::presets::Preset;
+use crate::probes;
+use crate::smoothing::Filter;
+use crate::units::Unit;
+
+/// Display MEATER temperatures on a small OLED.
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// Configuration file [default: /etc/meater/config.toml].
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Display to render on.
+    #[arg(long, value_enum)]
+    pub display: Option<display::Kind>,
+
+    /// Run without a display, same as `--display none`.
+    #[arg(long, conflicts_with = "display")]
+    pub no_display: bool,
+
+    /// I2C bus the display is connected to [default: 1].
+    #[arg(long)]
+    pub i2c_bus: Option<u8>,
+
+    /// Save every frame of the simulator as PNG into this directory.
+    #[cfg(feature = "host")]
+    #[arg(long)]
+    pub screenshots: Option<PathBuf>,
+
+    /// Unit temperatures are shown and target temperatures are given in [default: celsius].
+    #[arg(long, value_enum)]
+    pub units: Option<Unit>,
+
+    /// Filter smoothing noisy temperature readings [default: none].
+    #[arg(long, value_enum)]
+    pub smoothing: Option<Filter>,
+
+    /// Maximum level of log messages [default: info].
+    #[arg(long)]
+    pub log_level: Option<tracing::Level>,
+
+    /// Log to the systemd journal with all fields of the messages instead of to stderr.
+    #[cfg(feature = "journald")]
+    #[arg(long)]
+    pub journald: bool,
+
+    /// Thermometer to read temperatures from [default: meater].
+    #[arg(long, value_enum)]
+    pub probe: Option<probes::Kind>,
+
+    /// Index or name (e.g. hci1) of the Bluetooth adapter to scan with [default: 0].
+    #[arg(long)]
+    pub adapter: Option<meater::Adapter>,
+
+    /// Only connect to the probe with this address instead of any device named MEATER.
+    #[arg(long, value_name = "MAC")]
+    pub device: Option<meater::BDAddr>,
+
+    /// Recognize probes named like this besides by their service, either an exact name,
+    /// `prefix:<text>` or `regex:<expression>`. May be given multiple times [default: MEATER].
+    #[arg(long = "name", value_name = "PATTERN")]
+    pub names: Vec<meater::protocol::NamePattern>,
+
+    /// File to remember the last connected probe in for faster reconnects.
+    #[arg(long)]
+    pub last_device: Option<PathBuf>,
+
+    /// Connect to MEATER probes through this ESPHome Bluetooth proxy instead of a local adapter.
+    #[cfg(feature = "esphome")]
+    #[arg(long, value_name = "HOST[:PORT]")]
+    pub esphome_proxy: Option<String>,
+
+    /// Only read the temperatures MEATER probes advertise instead of connecting to them, so the
+    /// phone app can stay connected.
+    #[arg(long)]
+    pub passive: bool,
+
+    /// File to record the raw notifications of MEATER probes to.
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Read temperatures from a file recorded with --record instead of a thermometer.
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Measure the calibration offsets of the probes sitting in ice or boiling water and print
+    /// them instead of showing temperatures.
+    #[arg(long, value_enum, value_name = "BATH")]
+    pub calibrate: Option<calibrate::Reference>,
+
+    /// Print every event on stdout in this format, e.g. to pipe it into a script.
+    #[cfg(feature = "json")]
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub output: Option<json::Output>,
+
+    /// Maximum seconds to wait between attempts to connect a probe [default: 60].
+    #[arg(long)]
+    pub reconnect_max: Option<f32>,
+
+    /// GPIO pin (BCM numbering) of a push button showing the next page.
+    #[arg(long)]
+    pub next_pin: Option<u8>,
+
+    /// GPIO pin (BCM numbering) of a push button showing the previous page.
+    #[arg(long)]
+    pub previous_pin: Option<u8>,
+
+    /// Turn the display off after this many minutes without meaningful temperature changes.
+    #[arg(long, value_name = "MINUTES")]
+    pub sleep_after: Option<u64>,
+
+    /// GPIO pin (BCM numbering) of a push button waking up the display.
+    #[arg(long)]
+    pub wake_pin: Option<u8>,
+
+    /// Status LED showing the connection state, a GPIO pin (BCM numbering) or the name of an LED
+    /// of the board, e.g. ACT.
+    #[arg(long, value_name = "PIN|NAME")]
+    pub led: Option<led::Output>,
+
+    /// Battery percentage below which a low battery warning is shown and an alert raised
+    /// [default: 20].
+    #[arg(long)]
+    pub low_battery: Option<u16>,
+
+    /// Move the content up to this many pixels every few minutes against OLED burn-in [default: 0].
+    #[arg(long, value_name = "PIXELS")]
+    pub burn_in_shift: Option<u8>,
+
+    /// Invert the display every this many minutes against OLED burn-in.
+    #[arg(long, value_name = "MINUTES")]
+    pub burn_in_invert: Option<u64>,
+
+    /// Tip temperature at which the buzzer sounds. Given once, it applies to all probes, given
+    /// multiple times, the n-th value applies to the n-th probe and the last one to all others.
+    #[arg(long = "target")]
+    pub targets: Vec<f32>,
+
+    /// Degrees the tip has to fall below the target before the buzzer stops again [default: 1].
+    #[arg(long)]
+    pub alarm_hysteresis: Option<f32>,
+
+    /// GPIO pin (BCM numbering) the piezo buzzer is connected to [default: 18].
+    #[arg(long)]
+    pub buzzer_pin: Option<u8>,
+
+    /// Set the target temperature of all probes from a doneness preset instead of --target.
+    #[arg(long, value_enum)]
+    pub preset: Option<Preset>,
+
+    /// GPIO pin (BCM numbering) of a push button cycling through the presets.
+    #[arg(long)]
+    pub preset_pin: Option<u8>,
+
+    /// Sound the buzzer and raise an alert while the ambient temperature is below this.
+    #[arg(long)]
+    pub ambient_min: Option<f32>,
+
+    /// Sound the buzzer and raise an alert while the ambient temperature is above this.
+    #[arg(long)]
+    pub ambient_max: Option<f32>,
+
+    /// Also write log messages as JSON lines to this file, rotated by size and day.
+    #[arg(long, value_name = "FILE")]
+    pub log_file: Option<PathBuf>,
+
+    /// Log events to CSV files named after this path, a new one for each cook session.
+    #[arg(long)]
+    pub log_csv: Option<PathBuf>,
+
+    /// File to keep statistics of all cooks in across restarts.
+    #[arg(long, value_name = "FILE")]
+    pub state: Option<PathBuf>,
+
+    /// Store temperatures and cook sessions in this SQLite database.
+    #[cfg(feature = "sqlite")]
+    #[arg(long)]
+    pub history: Option<PathBuf>,
+
+    /// MQTT broker to publish events to.
+    #[cfg(feature = "mqtt")]
+    #[arg(long)]
+    pub mqtt_host: Option<String>,
+
+    /// Port of the MQTT broker [default: 1883].
+    #[cfg(feature = "mqtt")]
+    #[arg(long)]
+    pub mqtt_port: Option<u16>,
+
+    /// Prefix of all published topics [default: meater].
+    #[cfg(feature = "mqtt")]
+    #[arg(long)]
+    pub mqtt_prefix: Option<String>,
+
+    /// Announce sensors via Home Assistant MQTT discovery.
+    #[cfg(feature = "mqtt")]
+    #[arg(long)]
+    pub homeassistant: bool,
+
+    /// Topic prefix Home Assistant listens on for discovery messages [default: homeassistant].
+    #[cfg(feature = "mqtt")]
+    #[arg(long)]
+    pub homeassistant_prefix: Option<String>,
+
+    /// InfluxDB server to write samples to, e.g. http://localhost:8086.
+    #[cfg(feature = "influxdb")]
+    #[arg(long, value_name = "URL")]
+    pub influxdb_url: Option<String>,
+
+    /// InfluxDB bucket to write samples to [default: meater].
+    #[cfg(feature = "influxdb")]
+    #[arg(long)]
+    pub influxdb_bucket: Option<String>,
+
+    /// StatsD or Graphite server to send metrics to via UDP, e.g. localhost:8125.
+    #[cfg(feature = "statsd")]
+    #[arg(long, value_name = "ADDRESS")]
+    pub statsd_host: Option<String>,
+
+    /// OpenTelemetry collector to export traces and metrics to via OTLP/HTTP, e.g.
+    /// http://localhost:4318.
+    #[cfg(feature = "otel")]
+    #[arg(long, value_name = "URL")]
+    pub otlp_endpoint: Option<String>,
+
+    /// Re-advertise the temperatures as Environmental Sensing Service for other devices to read.
+    #[cfg(feature = "peripheral")]
+    #[arg(long)]
+    pub peripheral: bool,
+
+    /// Also send notifications to this ntfy topic URL, e.g. https://ntfy.sh/my-grill.
+    #[cfg(feature = "notify")]
+    #[arg(long)]
+    pub ntfy: Option<String>,
+
+    /// Also post notifications as JSON to this URL.
+    #[cfg(feature = "notify")]
+    #[arg(long)]
+    pub webhook: Option<String>,
+
+    /// Serve the web dashboard on this address, e.g. 0.0.0.0:8080.
+    #[cfg(feature = "web")]
+    #[arg(long)]
+    pub web_listen: Option<std::net::SocketAddr>,
+}
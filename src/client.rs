@@ -0,0 +1,1171 @@
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use btleplug::api::{
+    BDAddr, Central, CentralEvent, CharPropFlags, Manager, Peripheral, ScanFilter,
+    ValueNotification,
+};
+use btleplug::platform;
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use rand::Rng;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::decoder::Decoder;
+use crate::error::MeaterError;
+use crate::protocol::{self, Layout, NamePattern};
+use crate::recording::Recorder;
+use crate::source::ProbeSource;
+
+#[cfg(feature = "esphome")]
+mod esphome;
+
+/// Initial delay before setting up Bluetooth again after a failure.
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Maximum delay before setting up Bluetooth again after a failure.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Interval in which the signal strength of connected probes is reported.
+const RSSI_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time without temperatures after which a connection is considered stale if not configured.
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Time scanning finds no probe after which it is restarted if not configured.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time scanning for MEATER services finds no probe after which all devices are scanned for if not
+/// configured.
+const UNFILTERED_AFTER: Duration = Duration::from_secs(60);
+
+/// Battery level in percent below which a probe counts as low on battery if not configured.
+pub(crate) const LOW_BATTERY: u16 = 20;
+
+/// Failures of the notification listener in a row before the probe is connected again.
+const MAX_RESTARTS: u32 = 3;
+
+/// Delay before the notification listener is restarted after a failure.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Time the notification listener has to run for to no longer count as failing in a row.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// State the MEATER device may be in, new states have to be added to [`State::ALL`] as well.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Disconnected,
+    /// No probe was found for `elapsed` since scanning started, scanning is restarted periodically.
+    Searching {
+        elapsed: Duration,
+    },
+    Connecting,
+    /// A probe is connected and subscribed to notifications.
+    Connected,
+    /// Connecting failed `attempt` times in a row and is retried after a delay.
+    Retrying {
+        attempt: u32,
+    },
+    /// A probe is connected but stopped sending temperatures and is connected again.
+    Stale,
+}
+
+impl State {
+    /// One of each state, e.g. to announce all values a state may be shown as.
+    pub const ALL: [Self; 6] = [
+        Self::Disconnected,
+        Self::Searching {
+            elapsed: Duration::ZERO,
+        },
+        Self::Connecting,
+        Self::Connected,
+        Self::Retrying { attempt: 0 },
+        Self::Stale,
+    ];
+}
+
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Disconnected => write!(f, "disconnected"),
+            Self::Searching { .. } => write!(f, "searching"),
+            Self::Connecting => write!(f, "connecting"),
+            Self::Connected => write!(f, "connected"),
+            Self::Retrying { .. } => write!(f, "retrying"),
+            Self::Stale => write!(f, "stale"),
+        }
+    }
+}
+
+/// Condition of the battery of a probe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatteryState {
+    Normal,
+    /// The level fell below the configured limit.
+    Low,
+    /// The level is rising, the probe is back in its charger.
+    Charging,
+}
+
+impl std::fmt::Display for BatteryState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Normal => write!(f, "normal"),
+            Self::Low => write!(f, "low"),
+            Self::Charging => write!(f, "charging"),
+        }
+    }
+}
+
+/// An event emitted by the MEATER client.
+///
+/// Probes are numbered in the order they were discovered, starting from zero. A MEATER Block
+/// exposes each of its probes as a separate peripheral, so each one gets its own index.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// State changed.
+    State(State),
+    /// Temperature of a probe changed.
+    Temperature {
+        probe: usize,
+        tip: f32,
+        ambient: f32,
+    },
+    /// Battery level of a probe changed.
+    Battery { probe: usize, percent: u16 },
+    /// Battery of a probe ran low, started or stopped charging. Sent along with the first battery
+    /// level after connecting as well.
+    BatteryState { probe: usize, state: BatteryState },
+    /// Signal strength of a probe in dBm, reported periodically.
+    Rssi { probe: usize, rssi: i16 },
+    /// Temperatures of all sensors of a probe with several ones, from the tip towards the handle,
+    /// and the temperature at the surface of the meat if the probe determines it.
+    Sensors {
+        probe: usize,
+        temperatures: Vec<f32>,
+        surface: Option<f32>,
+    },
+    /// Tip and ambient temperature of a probe converged quickly, it was pulled out of the meat.
+    ProbeRemoved { probe: usize },
+    /// Tip and ambient temperature of a removed probe moved apart, it is inside the meat again.
+    ProbeInserted { probe: usize },
+    /// A notification of a probe could not be decoded into sensible values and was dropped.
+    DecodeError { probe: usize, value: Vec<u8> },
+    /// Firmware revision and serial number a probe reported after connecting, if it has them.
+    DeviceInfo {
+        probe: usize,
+        firmware: Option<String>,
+        serial: Option<String>,
+    },
+    /// Something failed, logged along with the random `id` and told apart by `code`, see
+    /// [`MeaterError::code`].
+    Error {
+        probe: Option<usize>,
+        code: u16,
+        id: u16,
+    },
+    /// The user ended the cook session while probes are still connected. Never sent by the
+    /// clients, only passed on by programs tracking sessions along with the other events.
+    SessionEnded,
+}
+
+/// Delays between failed attempts to connect a probe.
+///
+/// The delay doubles with every attempt up to `max` and is randomly varied by up to `jitter` times
+/// its value so several probes do not retry in lockstep.
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    /// Delay after the first failed attempt.
+    pub initial: Duration,
+    /// Upper bound of the delay.
+    pub max: Duration,
+    /// Fraction of the delay it is randomly varied by, between 0 and 1.
+    pub jitter: f32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before the next attempt after `attempt` failed ones.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self.initial.saturating_mul(1 << exponent).min(self.max);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        delay.mul_f32(factor).min(self.max)
+    }
+}
+
+/// Offsets in °C added to the temperatures of a probe to correct its sensors, e.g. `-0.8` if it
+/// reads 0.8 °C high.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Calibration {
+    pub tip: f32,
+    pub ambient: f32,
+}
+
+/// Bluetooth adapter to scan with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Adapter {
+    /// The `n`-th adapter in the order reported by the system.
+    Index(usize),
+    /// The adapter with the given name, e.g. `hci1` on Linux.
+    Name(String),
+}
+
+impl Default for Adapter {
+    fn default() -> Self {
+        Self::Index(0)
+    }
+}
+
+impl std::fmt::Display for Adapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{index}"),
+            Self::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl std::str::FromStr for Adapter {
+    type Err = std::convert::Infallible;
+
+    /// Numbers select adapters by index, everything else by name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.parse()
+            .map(Self::Index)
+            .unwrap_or_else(|_| Self::Name(s.to_string())))
+    }
+}
+
+impl Adapter {
+    /// Pick the matching adapter out of `adapters`.
+    pub(crate) async fn select(
+        &self,
+        adapters: Vec<platform::Adapter>,
+    ) -> Result<platform::Adapter, MeaterError> {
+        for (index, adapter) in adapters.into_iter().enumerate() {
+            // The info looks like "hci0 (usb:v1D6Bp0246d0537)" with BlueZ.
+            let info = adapter.adapter_info().await?;
+            tracing::debug!(index, info, "found bluetooth adapter");
+
+            let matches = match self {
+                Self::Index(wanted) => index == *wanted,
+                Self::Name(name) => info.split_whitespace().next() == Some(name.as_str()),
+            };
+
+            if matches {
+                tracing::info!(info, "using bluetooth adapter");
+                return Ok(adapter);
+            }
+        }
+
+        Err(MeaterError::AdapterNotFound(self.clone()))
+    }
+}
+
+pub struct Client {
+    sender: mpsc::Sender<Event>,
+    adapter: Adapter,
+    backoff: Backoff,
+    device: Option<BDAddr>,
+    last_device: Option<PathBuf>,
+    record: Option<PathBuf>,
+    stale_after: Duration,
+    scan_timeout: Duration,
+    unfiltered_after: Duration,
+    passive: bool,
+    #[cfg(feature = "esphome")]
+    proxy: Option<esphome::Proxy>,
+    calibrations: Vec<Calibration>,
+    low_battery: u16,
+    names: Vec<NamePattern>,
+}
+
+impl Client {
+    pub fn new() -> (Self, mpsc::Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel(16);
+
+        let client = Self {
+            sender,
+            adapter: Adapter::default(),
+            backoff: Backoff::default(),
+            device: None,
+            last_device: None,
+            record: None,
+            stale_after: STALE_AFTER,
+            scan_timeout: SCAN_TIMEOUT,
+            unfiltered_after: UNFILTERED_AFTER,
+            passive: false,
+            #[cfg(feature = "esphome")]
+            proxy: None,
+            calibrations: Vec::new(),
+            low_battery: LOW_BATTERY,
+            names: vec![NamePattern::Exact(protocol::NAME.to_string())],
+        };
+
+        (client, receiver)
+    }
+
+    /// Scan with `adapter` instead of the first one.
+    pub fn adapter(mut self, adapter: Adapter) -> Self {
+        self.adapter = adapter;
+        self
+    }
+
+    /// Wait according to `backoff` between failed attempts to connect a probe.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Only connect to the probe with the given `address` instead of any MEATER device.
+    pub fn device(mut self, address: BDAddr) -> Self {
+        self.device = Some(address);
+        self
+    }
+
+    /// Store the address of the last connected probe in `path` and connect to it right away on the
+    /// next start instead of waiting for it to be discovered.
+    pub fn last_device(mut self, path: PathBuf) -> Self {
+        self.last_device = Some(path);
+        self
+    }
+
+    /// Write the raw notifications of all probes to `path` so they can be fed back with
+    /// [`Replay`](crate::replay::Replay).
+    pub fn record(mut self, path: PathBuf) -> Self {
+        self.record = Some(path);
+        self
+    }
+
+    /// Connect a probe again if it sent no temperature for `timeout` although it is connected.
+    pub fn stale_after(mut self, timeout: Duration) -> Self {
+        self.stale_after = timeout;
+        self
+    }
+
+    /// Restart scanning every `timeout` as long as no probe is found.
+    pub fn scan_timeout(mut self, timeout: Duration) -> Self {
+        self.scan_timeout = timeout;
+        self
+    }
+
+    /// Scan for all devices and tell MEATER devices apart by name and services if scanning for
+    /// their services found no probe within `timeout`.
+    ///
+    /// Some adapters and BlueZ versions do not report MEATER advertisements when filtering.
+    pub fn unfiltered_after(mut self, timeout: Duration) -> Self {
+        self.unfiltered_after = timeout;
+        self
+    }
+
+    /// Only decode the temperatures probes include in their advertisements instead of connecting
+    /// to them, so the phone app can stay connected.
+    ///
+    /// Only firmware advertising the value of the temperature characteristic as service data is
+    /// supported, battery levels and signal strength are not reported.
+    pub fn passive(mut self, passive: bool) -> Self {
+        self.passive = passive;
+        self
+    }
+
+    /// Connect to probes through the ESPHome Bluetooth proxy at `address`, e.g. `grill.local` or
+    /// `192.168.1.20:6053`, instead of a local adapter, logging in with `password` if given.
+    ///
+    /// The proxy has to be an active one with encryption of its native API disabled.
+    #[cfg(feature = "esphome")]
+    pub fn esphome_proxy(mut self, address: String, password: Option<String>) -> Self {
+        self.proxy = Some(esphome::Proxy { address, password });
+        self
+    }
+
+    /// Correct the temperatures of the n-th probe by the n-th of `calibrations`.
+    pub fn calibrations(mut self, calibrations: Vec<Calibration>) -> Self {
+        self.calibrations = calibrations;
+        self
+    }
+
+    /// Report a battery below `percent` as [`BatteryState::Low`].
+    pub fn low_battery(mut self, percent: u16) -> Self {
+        self.low_battery = percent;
+        self
+    }
+
+    /// Recognize probes by a name matching any of `names` instead of exactly `MEATER`, besides
+    /// the service they advertise. Without any names probes are only recognized by their service.
+    pub fn names(mut self, names: Vec<NamePattern>) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Return a decoder for the n-th `probe` with `layout`.
+    fn decoder(&self, layout: Layout, probe: usize) -> Decoder {
+        let calibration = self.calibrations.get(probe).copied().unwrap_or_default();
+        Decoder::new(layout, probe, calibration, self.low_battery)
+    }
+
+    /// Address of the probe connected last time, if known.
+    fn read_last_device(&self) -> Option<BDAddr> {
+        let path = self.last_device.as_ref()?;
+        let content = std::fs::read_to_string(path).ok()?;
+
+        match content.trim().parse() {
+            Ok(address) => Some(address),
+            Err(err) => {
+                tracing::warn!("ignoring {}: {err}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Remember `address` as the probe connected last.
+    fn write_last_device(&self, address: BDAddr) {
+        if let Some(path) = &self.last_device {
+            if let Err(err) = std::fs::write(path, format!("{address}\n")) {
+                tracing::warn!("unable to write {}: {err}", path.display());
+            }
+        }
+    }
+
+    /// Scan for probes and stream their events until the receiver is dropped.
+    ///
+    /// If the Bluetooth adapter disappears, e.g. because bluetoothd restarted or the USB dongle was
+    /// reset, everything is set up again after a growing delay.
+    pub async fn run(self) -> Result<(), MeaterError> {
+        let mut indices = HashMap::new();
+        #[cfg(feature = "esphome")]
+        let mut addresses = HashMap::new();
+        let mut backoff = MIN_BACKOFF;
+
+        let recorder = match &self.record {
+            Some(path) => Some(Arc::new(Recorder::create(path)?)),
+            None => None,
+        };
+
+        loop {
+            self.sender.send(Event::State(State::Disconnected)).await?;
+
+            let started = Instant::now();
+
+            #[cfg(feature = "esphome")]
+            let result = match &self.proxy {
+                Some(proxy) => {
+                    esphome::monitor(proxy, &self, &mut addresses, recorder.as_ref()).await
+                }
+                None => self.scan(&mut indices, recorder.as_ref()).await,
+            };
+
+            #[cfg(not(feature = "esphome"))]
+            let result = self.scan(&mut indices, recorder.as_ref()).await;
+
+            match result {
+                Err(MeaterError::Closed(_)) => return Ok(()),
+                Err(err) => {
+                    // Fails only if the receiver is gone, which is handled below.
+                    let _ = self.sender.send(err.report(None)).await;
+                }
+                Ok(()) => {}
+            }
+
+            if self.sender.is_closed() {
+                return Ok(());
+            }
+
+            // Only back off further if things keep failing right away.
+            if started.elapsed() > MAX_BACKOFF {
+                backoff = MIN_BACKOFF;
+            }
+
+            tracing::info!("restarting bluetooth in {backoff:?}");
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// Set up the adapter and monitor probes until the adapter goes away.
+    #[tracing::instrument(skip_all)]
+    async fn scan(
+        &self,
+        indices: &mut HashMap<platform::PeripheralId, usize>,
+        recorder: Option<&Arc<Recorder>>,
+    ) -> Result<(), MeaterError> {
+        let manager = platform::Manager::new().await?;
+
+        // This sometimes fails as well ...
+        let central = self.adapter.select(manager.adapters().await?).await?;
+
+        if self.passive {
+            observe(&central, self, indices, recorder).await
+        } else {
+            monitor(&central, self, indices, recorder).await
+        }
+    }
+}
+
+impl ProbeSource for Client {
+    fn name(&self) -> &'static str {
+        "MEATER"
+    }
+
+    fn run(self: Box<Self>) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(async move { Ok(Client::run(*self).await?) })
+    }
+}
+
+/// Peripherals seen by the adapter and what they turned out to be.
+///
+/// Devices keep sending updates, so their properties are only queried once from BlueZ, which is
+/// slow and may change them meanwhile, instead of on every event.
+struct Peripherals<'a> {
+    central: &'a platform::Adapter,
+    device: Option<BDAddr>,
+    names: &'a [NamePattern],
+    resolved: HashMap<platform::PeripheralId, Option<(platform::Peripheral, Layout)>>,
+}
+
+impl<'a> Peripherals<'a> {
+    fn new(
+        central: &'a platform::Adapter,
+        device: Option<BDAddr>,
+        names: &'a [NamePattern],
+    ) -> Self {
+        Self {
+            central,
+            device,
+            names,
+            resolved: HashMap::new(),
+        }
+    }
+
+    /// Return `Ok(Some((meater, layout)))` if `id` is a MEATER probe.
+    ///
+    /// If `device` is given, only the peripheral with that address is accepted regardless of its
+    /// name.
+    async fn meater(
+        &mut self,
+        id: &platform::PeripheralId,
+    ) -> Result<Option<(platform::Peripheral, Layout)>, MeaterError> {
+        if let Some(resolved) = self.resolved.get(id) {
+            return Ok(resolved.clone());
+        }
+
+        let peripheral = self.central.peripheral(id).await?;
+
+        let Some(props) = peripheral.properties().await? else {
+            return Ok(None);
+        };
+
+        let layout = protocol::layout(props.local_name.as_deref(), &props.services, self.names);
+
+        let layout = match self.device {
+            // The name may not be advertised yet, so assume a probe.
+            Some(address) => (props.address == address).then(|| layout.unwrap_or(protocol::PROBE)),
+            // Without a name it may still turn out to be a MEATER, so ask again next time.
+            None if layout.is_none() && props.local_name.is_none() => return Ok(None),
+            None => layout,
+        };
+
+        tracing::debug!(id = ?id, meater = layout.is_some(), "peripheral resolved");
+
+        let resolved = layout.map(|layout| (peripheral, layout));
+        self.resolved.insert(id.clone(), resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Return the address of `id`.
+    async fn address(&self, id: &platform::PeripheralId) -> Result<BDAddr, MeaterError> {
+        match self.resolved.get(id) {
+            Some(Some((peripheral, _))) => Ok(peripheral.address()),
+            _ => Ok(self.central.peripheral(id).await?.address()),
+        }
+    }
+}
+
+/// Connect to the meater and subscribe to all notification characteristics.
+///
+/// Failed attempts are retried after a delay given by `backoff`, each one announced as
+/// [`State::Retrying`] through `reporter`.
+#[tracing::instrument(skip(meater, backoff, reporter))]
+async fn connect(
+    meater: &platform::Peripheral,
+    probe: usize,
+    backoff: Backoff,
+    reporter: &Reporter,
+) -> Result<(), MeaterError> {
+    let mut attempt = 0;
+
+    loop {
+        tracing::info!(probe, "connecting MEATER");
+
+        match meater.connect().await {
+            Ok(_) => break,
+            Err(err) => {
+                attempt += 1;
+                let delay = backoff.delay(attempt);
+                tracing::warn!(
+                    probe,
+                    attempt,
+                    "unable to connect: {err}, retrying in {delay:?}"
+                );
+                reporter.state(probe, State::Retrying { attempt }).await?;
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+
+    tracing::debug!("discovering services");
+    meater
+        .discover_services()
+        .await
+        .map_err(MeaterError::ConnectFailed)?;
+
+    subscribe(meater).await
+}
+
+/// Subscribe to all notification characteristics of the connected `meater`.
+#[tracing::instrument(skip_all)]
+async fn subscribe(meater: &platform::Peripheral) -> Result<(), MeaterError> {
+    tracing::debug!("subscribing to characteristics");
+
+    for characteristic in meater.characteristics() {
+        if characteristic.properties.contains(CharPropFlags::NOTIFY) {
+            tracing::debug!(characteristic = ?characteristic, "subscribing");
+            meater
+                .subscribe(&characteristic)
+                .await
+                .map_err(MeaterError::SubscribeFailed)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the string characteristic `uuid` of the connected `meater`, `None` if it has no such
+/// characteristic or reading it fails.
+async fn read_string(meater: &platform::Peripheral, uuid: uuid::Uuid) -> Option<String> {
+    let characteristic = meater
+        .characteristics()
+        .into_iter()
+        .find(|characteristic| characteristic.uuid == uuid)?;
+
+    match meater.read(&characteristic).await {
+        Ok(value) => protocol::decode_string(&value),
+        Err(err) => {
+            tracing::warn!(uuid = ?uuid, "unable to read characteristic: {err}");
+            None
+        }
+    }
+}
+
+/// Listen to notifications on the characteristics `decoder` decodes and send out temperature and
+/// battery values of probe `probe`, writing them to `recorder` as well if given.
+///
+/// Fails with [`MeaterError::Stale`] if no temperature arrived for `stale_after`.
+#[tracing::instrument(skip(meater, decoder, stale_after, recorder, sender))]
+async fn listen(
+    meater: platform::Peripheral,
+    mut decoder: Decoder,
+    probe: usize,
+    stale_after: Duration,
+    recorder: Option<Arc<Recorder>>,
+    sender: mpsc::Sender<Event>,
+) -> Result<(), MeaterError> {
+    let mut notifications = meater
+        .notifications()
+        .await
+        .map_err(MeaterError::SubscribeFailed)?;
+    let mut deadline = tokio::time::Instant::now() + stale_after;
+
+    loop {
+        let Ok(notification) = tokio::time::timeout_at(deadline, notifications.next()).await else {
+            tracing::warn!(probe, "no temperature for {stale_after:?}");
+            return Err(MeaterError::Stale(stale_after));
+        };
+
+        let Some(ValueNotification { value, uuid }) = notification else {
+            return Ok(());
+        };
+
+        let events = tracing::info_span!("notification", uuid = %uuid).in_scope(|| {
+            tracing::info!(value = ?value, "received notification value");
+
+            if let Some(recorder) = &recorder {
+                recorder.write(probe, uuid, &value);
+            }
+
+            decoder.decode(Instant::now(), uuid, &value)
+        });
+
+        for event in events {
+            if matches!(event, Event::Temperature { .. }) {
+                deadline = tokio::time::Instant::now() + stale_after;
+            }
+
+            sender.send(event).await?;
+        }
+    }
+}
+
+/// Periodically send out the signal strength of probe `probe`.
+async fn poll_rssi(
+    meater: platform::Peripheral,
+    probe: usize,
+    sender: mpsc::Sender<Event>,
+) -> Result<(), MeaterError> {
+    let mut interval = tokio::time::interval(RSSI_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Some(rssi) = meater.properties().await?.and_then(|props| props.rssi) {
+            tracing::debug!(probe, rssi, "read signal strength");
+            sender.send(Event::Rssi { probe, rssi }).await?;
+        }
+    }
+}
+
+/// Run the notification listener of probe `probe`, restarting it with fresh subscriptions if it
+/// fails, panics or its stream ends.
+///
+/// Gives up after [`MAX_RESTARTS`] failures in a row or once the connection went stale so the probe
+/// can be connected again.
+async fn supervise(
+    meater: &platform::Peripheral,
+    decoder: &Decoder,
+    probe: usize,
+    stale_after: Duration,
+    recorder: Option<Arc<Recorder>>,
+    sender: &mpsc::Sender<Event>,
+) -> Result<(), MeaterError> {
+    let mut failures = 0;
+
+    loop {
+        let started = Instant::now();
+        // Every listener starts with a fresh decoder.
+        let listener = listen(
+            meater.clone(),
+            decoder.clone(),
+            probe,
+            stale_after,
+            recorder.clone(),
+            sender.clone(),
+        );
+
+        match AssertUnwindSafe(listener).catch_unwind().await {
+            // Restarting does not help against these.
+            Ok(Err(err @ (MeaterError::Closed(_) | MeaterError::Stale(_)))) => return Err(err),
+            Ok(Err(err)) => tracing::warn!(probe, "listener failed: {err}"),
+            Ok(Ok(())) => tracing::warn!(probe, "notifications ended"),
+            Err(_) => tracing::error!(probe, "listener panicked"),
+        }
+
+        if started.elapsed() > STABLE_AFTER {
+            failures = 0;
+        }
+
+        failures += 1;
+
+        if failures >= MAX_RESTARTS {
+            return Err(MeaterError::ListenerFailed(failures));
+        }
+
+        tracing::info!(probe, "restarting listener in {RESTART_DELAY:?}");
+        tokio::time::sleep(RESTART_DELAY).await;
+        subscribe(meater).await?;
+    }
+}
+
+/// Connect to a probe and stream its notifications until the connection is lost.
+///
+/// If listening keeps failing or no temperature arrived for `stale_after`, the probe is
+/// disconnected to be connected again once it is discovered anew.
+#[tracing::instrument(
+    name = "probe",
+    skip(meater, decoder, backoff, stale_after, recorder, reporter)
+)]
+async fn run_probe(
+    meater: platform::Peripheral,
+    decoder: Decoder,
+    probe: usize,
+    backoff: Backoff,
+    stale_after: Duration,
+    recorder: Option<Arc<Recorder>>,
+    reporter: Reporter,
+) -> Result<(), MeaterError> {
+    connect(&meater, probe, backoff, &reporter).await?;
+
+    // Only now notifications can arrive, the link alone is not enough.
+    reporter.state(probe, State::Connected).await?;
+
+    let sender = &reporter.sender;
+
+    let firmware = read_string(&meater, protocol::FIRMWARE_REVISION_UUID).await;
+    let serial = read_string(&meater, protocol::SERIAL_NUMBER_UUID).await;
+    tracing::info!(probe, firmware = ?firmware, serial = ?serial, "read device information");
+
+    sender
+        .send(Event::DeviceInfo {
+            probe,
+            firmware,
+            serial,
+        })
+        .await?;
+
+    let result = tokio::select! {
+        result = supervise(&meater, &decoder, probe, stale_after, recorder, sender) => result,
+        result = poll_rssi(meater.clone(), probe, sender.clone()) => result,
+    };
+
+    if matches!(result, Err(MeaterError::Stale(_))) {
+        reporter.state(probe, State::Stale).await?;
+    }
+
+    if matches!(
+        result,
+        Err(MeaterError::ListenerFailed(_) | MeaterError::Stale(_))
+    ) {
+        tracing::warn!(probe, "disconnecting to connect again");
+
+        if let Err(err) = meater.disconnect().await {
+            tracing::warn!(probe, "unable to disconnect: {err}");
+        }
+    }
+
+    result
+}
+
+/// Sends the events of all probes, but the state of a single probe only while no other probe is
+/// streaming, so one probe connecting or going stale does not hide the temperatures of another.
+#[derive(Clone)]
+struct Reporter {
+    sender: mpsc::Sender<Event>,
+    /// Probes connected and subscribed to notifications.
+    streaming: Arc<Mutex<HashSet<usize>>>,
+}
+
+impl Reporter {
+    fn new(sender: mpsc::Sender<Event>) -> Self {
+        Self {
+            sender,
+            streaming: Arc::default(),
+        }
+    }
+
+    /// Send `state` of `probe` unless another probe is streaming.
+    async fn state(&self, probe: usize, state: State) -> Result<(), MeaterError> {
+        if self.update(probe, state) {
+            self.sender.send(Event::State(state)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Record `state` of `probe`, returning `true` if it is to be sent.
+    fn update(&self, probe: usize, state: State) -> bool {
+        let mut streaming = self.streaming.lock().unwrap_or_else(|err| err.into_inner());
+
+        if state == State::Connected {
+            streaming.insert(probe);
+            true
+        } else {
+            streaming.remove(&probe);
+            streaming.is_empty()
+        }
+    }
+
+    /// Forget `probe` once its task ended.
+    fn stop(&self, probe: usize) {
+        self.update(probe, State::Disconnected);
+    }
+}
+
+/// A probe seen during scanning and the task currently handling it, if any.
+struct Probe {
+    index: usize,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Probe {
+    fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Filter for the services advertised by MEATER devices.
+fn service_filter() -> ScanFilter {
+    ScanFilter {
+        services: vec![protocol::PROBE.service],
+    }
+}
+
+/// Decode the temperatures MEATER devices advertise as service data without connecting to them.
+///
+/// The devices count as connected as long as they keep advertising temperatures and as
+/// disconnected once none arrived for the stale timeout of `client`.
+async fn observe(
+    central: &platform::Adapter,
+    client: &Client,
+    indices: &mut HashMap<platform::PeripheralId, usize>,
+    recorder: Option<&Arc<Recorder>>,
+) -> Result<(), MeaterError> {
+    tracing::info!("listening for MEATER advertisements");
+
+    let sender = &client.sender;
+    let mut events = central.events().await?;
+    central.start_scan(service_filter()).await?;
+
+    let mut peripherals = Peripherals::new(central, client.device, &client.names);
+    let mut decoders: HashMap<platform::PeripheralId, Decoder> = HashMap::new();
+    let mut seen: Option<Instant> = None;
+    let mut check = tokio::time::interval(client.stale_after / 2);
+
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                let CentralEvent::ServiceDataAdvertisement { id, service_data } = event else {
+                    continue;
+                };
+
+                let Some((_, layout)) = peripherals.meater(&id).await? else {
+                    continue;
+                };
+
+                // Older firmware only advertises the service without any data.
+                let Some(value) = service_data.get(&layout.service) else {
+                    continue;
+                };
+
+                let next = indices.len();
+                let probe = *indices.entry(id.clone()).or_insert(next);
+
+                let decoder = decoders.entry(id.clone()).or_insert_with(|| {
+                    tracing::info!(id = ?id, probe, "MEATER advertising temperatures");
+                    client.decoder(layout, probe)
+                });
+
+                if let Some(recorder) = recorder {
+                    recorder.write(probe, layout.temperature, value);
+                }
+
+                if seen.replace(Instant::now()).is_none() {
+                    sender.send(Event::State(State::Connected)).await?;
+                }
+
+                for event in decoder.decode(Instant::now(), layout.temperature, value) {
+                    sender.send(event).await?;
+                }
+            }
+            _ = check.tick() => {
+                if seen.is_some_and(|seen| seen.elapsed() > client.stale_after) {
+                    tracing::info!("MEATER advertisements stopped");
+                    seen = None;
+                    sender.send(Event::State(State::Disconnected)).await?;
+                }
+            }
+        }
+    }
+
+    Err(MeaterError::EventsEnded)
+}
+
+/// Start main event loop handling state changes between discovery, connection and connection loss
+/// of all probes in range.
+///
+/// `indices` maps probes to their index and is kept across calls so probes keep their number when
+/// monitoring is restarted.
+async fn monitor(
+    central: &platform::Adapter,
+    client: &Client,
+    indices: &mut HashMap<platform::PeripheralId, usize>,
+    recorder: Option<&Arc<Recorder>>,
+) -> Result<(), MeaterError> {
+    tracing::info!("looking for MEATER devices");
+
+    let sender = &client.sender;
+    let backoff = client.backoff;
+    let stale_after = client.stale_after;
+    let device = client.device;
+
+    let events = central.events().await?;
+
+    let mut filter = service_filter();
+    central.start_scan(filter.clone()).await?;
+
+    // The adapter may still know the requested or last connected probe, so treat it as discovered
+    // right away instead of waiting for its next advertisement.
+    let known = match device.or_else(|| client.read_last_device()) {
+        Some(address) => central
+            .peripherals()
+            .await?
+            .into_iter()
+            .filter(|peripheral| peripheral.address() == address)
+            .map(|peripheral| CentralEvent::DeviceDiscovered(peripheral.id()))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut events = futures::stream::iter(known).chain(events);
+
+    let mut peripherals = Peripherals::new(central, device, &client.names);
+    let mut probes: HashMap<platform::PeripheralId, Probe> = HashMap::new();
+    let mut connected = HashSet::new();
+    let reporter = Reporter::new(sender.clone());
+
+    // Some adapters stop reporting advertisements after a while, so scanning is restarted as long
+    // as no probe is found.
+    let mut rescan = tokio::time::interval(client.scan_timeout.max(Duration::from_secs(1)));
+    rescan.reset();
+    let mut searching = Some(Instant::now());
+
+    loop {
+        let event = tokio::select! {
+            event = events.next() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = rescan.tick() => {
+                if probes.values().any(Probe::is_running) {
+                    continue;
+                }
+
+                let elapsed = searching.get_or_insert_with(Instant::now).elapsed();
+                tracing::info!("no MEATER found for {}s, scanning again", elapsed.as_secs());
+
+                if !filter.services.is_empty() && elapsed >= client.unfiltered_after {
+                    tracing::info!("scanning for all devices instead of MEATER services");
+                    filter = ScanFilter::default();
+                }
+
+                central.stop_scan().await?;
+                central.start_scan(filter.clone()).await?;
+                sender.send(Event::State(State::Searching { elapsed })).await?;
+                continue;
+            }
+        };
+
+        match event {
+            CentralEvent::DeviceDiscovered(id) | CentralEvent::DeviceUpdated(id) => {
+                if probes.get(&id).is_some_and(Probe::is_running) {
+                    continue;
+                }
+
+                if let Some((meater, layout)) = peripherals.meater(&id).await? {
+                    let next = indices.len();
+                    let index = *indices.entry(id.clone()).or_insert(next);
+                    let probe = probes
+                        .entry(id.clone())
+                        .or_insert(Probe { index, task: None });
+
+                    tracing::info!(id = ?id, probe = probe.index, "MEATER discovered");
+                    reporter.state(probe.index, State::Connecting).await?;
+                    searching = None;
+
+                    let index = probe.index;
+                    let decoder = client.decoder(layout, index);
+                    let recorder = recorder.cloned();
+                    let reporter = reporter.clone();
+
+                    probe.task.replace(tokio::spawn(async move {
+                        let result = run_probe(
+                            meater,
+                            decoder,
+                            index,
+                            backoff,
+                            stale_after,
+                            recorder,
+                            reporter.clone(),
+                        )
+                        .await;
+
+                        reporter.stop(index);
+
+                        if let Err(err) = result {
+                            let report = err.report(Some(index));
+
+                            // Stale connections are shown as such already.
+                            if !matches!(err, MeaterError::Stale(_)) {
+                                let _ = reporter.sender.send(report).await;
+                            }
+                        }
+                    }));
+                }
+            }
+            CentralEvent::DeviceConnected(id) => {
+                if let Some(probe) = probes.get(&id) {
+                    tracing::info!(id = ?id, probe = probe.index, "MEATER connected");
+                    client.write_last_device(peripherals.address(&id).await?);
+                    connected.insert(id);
+                }
+            }
+            CentralEvent::DeviceDisconnected(id) => {
+                if let Some(probe) = probes.get_mut(&id) {
+                    tracing::info!(id = ?id, probe = probe.index, "MEATER disconnected");
+                    probe.stop();
+                    reporter.stop(probe.index);
+                    connected.remove(&id);
+
+                    if connected.is_empty() {
+                        sender.send(Event::State(State::Disconnected)).await?;
+                        searching = Some(Instant::now());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err(MeaterError::EventsEnded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_state_only_while_no_other_probe_streams() {
+        let (sender, _receiver) = mpsc::channel(1);
+        let reporter = Reporter::new(sender);
+
+        assert!(reporter.update(0, State::Connecting));
+        assert!(reporter.update(0, State::Connected));
+        assert!(!reporter.update(1, State::Connecting));
+        assert!(!reporter.update(1, State::Retrying { attempt: 1 }));
+        assert!(reporter.update(1, State::Connected));
+        assert!(!reporter.update(0, State::Stale));
+
+        reporter.stop(1);
+        assert!(reporter.update(0, State::Connecting));
+    }
+}
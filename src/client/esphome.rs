@@ -0,0 +1,884 @@
+//! Transport connecting to probes through an ESPHome Bluetooth proxy instead of a local adapter.
+//!
+//! The proxy is talked to with the plaintext ESPHome native API over TCP, so encryption has to be
+//! disabled in its `api` section. Every message is a frame of a zero byte, the length of the
+//! payload and the message type as varints, followed by the payload encoded as protocol buffer. The
+//! proxy reports raw advertisements, connects to probes on request and forwards their GATT
+//! notifications, which are then decoded like those received by a local adapter.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use btleplug::api::BDAddr;
+use futures::StreamExt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+use super::{Client, Event, State};
+use crate::decoder::Decoder;
+use crate::error::MeaterError;
+use crate::protocol::{self, Layout};
+use crate::recording::Recorder;
+
+/// Port of the native API if the address of the proxy has none.
+const DEFAULT_PORT: u16 = 6053;
+
+/// Version of the native API spoken, the proxy sends full 128-bit UUIDs to clients of this one.
+const API_VERSION: (u64, u64) = (1, 9);
+
+/// Largest payload accepted, messages sent by the proxy are far smaller.
+const MAX_PAYLOAD: u64 = 64 * 1024;
+
+/// Client Characteristic Configuration descriptor enabling notifications.
+const CCCD_UUID: Uuid = uuid::uuid!("00002902-0000-1000-8000-00805f9b34fb");
+
+/// Types of the messages exchanged with the proxy.
+mod message {
+    pub const HELLO_REQUEST: u64 = 1;
+    pub const HELLO_RESPONSE: u64 = 2;
+    pub const CONNECT_REQUEST: u64 = 3;
+    pub const CONNECT_RESPONSE: u64 = 4;
+    pub const DISCONNECT_REQUEST: u64 = 5;
+    pub const DISCONNECT_RESPONSE: u64 = 6;
+    pub const PING_REQUEST: u64 = 7;
+    pub const PING_RESPONSE: u64 = 8;
+    pub const SUBSCRIBE_ADVERTISEMENTS_REQUEST: u64 = 66;
+    pub const DEVICE_REQUEST: u64 = 68;
+    pub const DEVICE_CONNECTION_RESPONSE: u64 = 69;
+    pub const GATT_GET_SERVICES_REQUEST: u64 = 70;
+    pub const GATT_GET_SERVICES_RESPONSE: u64 = 71;
+    pub const GATT_GET_SERVICES_DONE_RESPONSE: u64 = 72;
+    pub const GATT_WRITE_DESCRIPTOR_REQUEST: u64 = 77;
+    pub const GATT_NOTIFY_REQUEST: u64 = 78;
+    pub const GATT_NOTIFY_DATA_RESPONSE: u64 = 79;
+    pub const GATT_ERROR_RESPONSE: u64 = 82;
+    pub const RAW_ADVERTISEMENTS_RESPONSE: u64 = 93;
+}
+
+/// Subscribe to advertisements as they were received instead of parsed by the proxy.
+const RAW_ADVERTISEMENTS: u64 = 1;
+
+/// Request to connect without using services cached by the proxy.
+const CONNECT_WITHOUT_CACHE: u64 = 5;
+
+/// Request to disconnect.
+const DISCONNECT: u64 = 1;
+
+/// Address and settings of the proxy.
+#[derive(Clone, Debug)]
+pub(crate) struct Proxy {
+    pub(crate) address: String,
+    pub(crate) password: Option<String>,
+}
+
+/// Protocol buffer message being encoded.
+#[derive(Default)]
+struct Message(Vec<u8>);
+
+/// Append `value` as varint to `buf`.
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    buf.push(value as u8);
+}
+
+impl Message {
+    fn varint(mut self, field: u64, value: u64) -> Self {
+        put_varint(&mut self.0, field << 3);
+        put_varint(&mut self.0, value);
+        self
+    }
+
+    fn bytes(mut self, field: u64, value: &[u8]) -> Self {
+        put_varint(&mut self.0, field << 3 | 2);
+        put_varint(&mut self.0, value.len() as u64);
+        self.0.extend_from_slice(value);
+        self
+    }
+}
+
+/// Value of a decoded field, fixed size values are not used by any message read.
+#[derive(Clone, Copy)]
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+impl<'a> Value<'a> {
+    fn varint(self) -> u64 {
+        match self {
+            Self::Varint(value) => value,
+            _ => 0,
+        }
+    }
+
+    fn bytes(self) -> &'a [u8] {
+        match self {
+            Self::Bytes(value) => value,
+            _ => &[],
+        }
+    }
+
+    /// Values of a repeated varint field, which may be packed.
+    fn varints(self) -> Vec<u64> {
+        match self {
+            Self::Varint(value) => vec![value],
+            Self::Bytes(mut bytes) => std::iter::from_fn(|| read_varint(&mut bytes)).collect(),
+            Self::Fixed => Vec::new(),
+        }
+    }
+}
+
+/// Take a varint from the front of `bytes`.
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Decode all fields of the message `payload` as pairs of field number and value.
+fn parse(mut payload: &[u8]) -> io::Result<Vec<(u64, Value<'_>)>> {
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, "malformed message");
+    let mut fields = Vec::new();
+
+    while !payload.is_empty() {
+        let key = read_varint(&mut payload).ok_or_else(malformed)?;
+
+        let value = match key & 7 {
+            0 => Value::Varint(read_varint(&mut payload).ok_or_else(malformed)?),
+            1 | 5 => {
+                let size = if key & 7 == 1 { 8 } else { 4 };
+                payload = payload.get(size..).ok_or_else(malformed)?;
+                Value::Fixed
+            }
+            2 => {
+                let len = read_varint(&mut payload).ok_or_else(malformed)? as usize;
+                let value = payload.get(..len).ok_or_else(malformed)?;
+                payload = &payload[len..];
+                Value::Bytes(value)
+            }
+            _ => return Err(malformed()),
+        };
+
+        fields.push((key >> 3, value));
+    }
+
+    Ok(fields)
+}
+
+/// Return the first value of `field` in `fields`.
+fn field<'a>(fields: &[(u64, Value<'a>)], field: u64) -> Option<Value<'a>> {
+    fields
+        .iter()
+        .find(|(number, _)| *number == field)
+        .map(|(_, value)| *value)
+}
+
+/// Return all values of the repeated `field` in `fields`.
+fn repeated<'a>(fields: &'a [(u64, Value<'a>)], field: u64) -> impl Iterator<Item = Value<'a>> {
+    fields
+        .iter()
+        .filter(move |(number, _)| *number == field)
+        .map(|(_, value)| *value)
+}
+
+/// Read the next frame from `reader` and return its message type and payload.
+async fn read_frame(reader: &mut (impl AsyncRead + Unpin)) -> io::Result<(u64, Vec<u8>)> {
+    async fn varint(reader: &mut (impl AsyncRead + Unpin)) -> io::Result<u64> {
+        let mut value = 0;
+
+        for shift in (0..64).step_by(7) {
+            let byte = reader.read_u8().await?;
+            value |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "malformed varint",
+        ))
+    }
+
+    if reader.read_u8().await? != 0 {
+        // Encrypted frames start with 1.
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected frame, is encryption of the API enabled?",
+        ));
+    }
+
+    let len = varint(reader).await?;
+
+    if len > MAX_PAYLOAD {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes too large"),
+        ));
+    }
+
+    let kind = varint(reader).await?;
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload).await?;
+    Ok((kind, payload))
+}
+
+/// Connection to the proxy.
+struct Connection {
+    writer: tokio::net::tcp::OwnedWriteHalf,
+}
+
+impl Connection {
+    async fn send(&mut self, kind: u64, message: Message) -> io::Result<()> {
+        let mut frame = vec![0];
+        put_varint(&mut frame, message.0.len() as u64);
+        put_varint(&mut frame, kind);
+        frame.extend_from_slice(&message.0);
+        self.writer.write_all(&frame).await
+    }
+}
+
+/// Return the address of a device reported by the proxy.
+fn bdaddr(address: u64) -> BDAddr {
+    let bytes = address.to_be_bytes();
+    BDAddr::from([bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]])
+}
+
+/// Name and services a device advertised so far, spread over advertisements and scan responses.
+#[derive(Default)]
+struct Advertised {
+    name: Option<String>,
+    services: Vec<Uuid>,
+}
+
+impl Advertised {
+    /// Add the name and services of the raw advertising `data`.
+    fn update(&mut self, mut data: &[u8]) {
+        while let Some((&len, rest)) = data.split_first() {
+            let Some(structure) = rest.get(..usize::from(len)) else {
+                break;
+            };
+
+            data = &rest[usize::from(len)..];
+
+            let Some((&kind, value)) = structure.split_first() else {
+                continue;
+            };
+
+            match kind {
+                // Shortened and complete local name.
+                0x08 | 0x09 => self.name = Some(String::from_utf8_lossy(value).into_owned()),
+                // Incomplete and complete lists of 128-bit services, in little-endian.
+                0x06 | 0x07 => {
+                    for service in value.chunks_exact(16) {
+                        let mut bytes: [u8; 16] = service.try_into().unwrap_or_default();
+                        bytes.reverse();
+                        let service = Uuid::from_bytes(bytes);
+
+                        if !self.services.contains(&service) {
+                            self.services.push(service);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// A MEATER device seen by the proxy.
+struct Device {
+    index: usize,
+    layout: Layout,
+    decoder: Decoder,
+    address_type: u64,
+    /// Characteristics notifying values and their descriptor enabling notifications by handle.
+    characteristics: HashMap<u64, (Uuid, Option<u64>)>,
+    connected: bool,
+    /// Time a connection was requested, if one is being set up.
+    connecting: Option<Instant>,
+    /// Failed connection attempts in a row and when to try again.
+    attempt: u32,
+    retry_at: Option<Instant>,
+    /// Time the last temperature arrived.
+    received: Instant,
+}
+
+impl Device {
+    /// Return the device at `address` if it is a MEATER probe according to what it `advertised`,
+    /// numbering it by `indices`.
+    fn discover(
+        client: &Client,
+        indices: &mut HashMap<u64, usize>,
+        address: u64,
+        address_type: u64,
+        advertised: &Advertised,
+    ) -> Option<Self> {
+        let name = advertised.name.as_deref();
+        let layout = protocol::layout(name, &advertised.services, &client.names);
+
+        let layout = match client.device {
+            // The name may not be advertised yet, so assume a probe.
+            Some(wanted) => (bdaddr(address) == wanted).then(|| layout.unwrap_or(protocol::PROBE)),
+            None => layout,
+        }?;
+
+        let next = indices.len();
+        let index = *indices.entry(address).or_insert(next);
+
+        tracing::info!(address = %bdaddr(address), probe = index, "MEATER discovered by proxy");
+
+        Some(Self {
+            index,
+            layout,
+            decoder: client.decoder(layout, index),
+            address_type,
+            characteristics: HashMap::new(),
+            connected: false,
+            connecting: None,
+            attempt: 0,
+            retry_at: None,
+            received: Instant::now(),
+        })
+    }
+}
+
+/// Connect to MEATER devices through the ESPHome Bluetooth `proxy` until the connection to it is
+/// lost.
+///
+/// `indices` maps device addresses to their index and is kept across calls so probes keep their
+/// number when the connection to the proxy is set up again.
+pub(crate) async fn monitor(
+    proxy: &Proxy,
+    client: &Client,
+    indices: &mut HashMap<u64, usize>,
+    recorder: Option<&Arc<Recorder>>,
+) -> Result<(), MeaterError> {
+    let address = if proxy.address.contains(':') {
+        proxy.address.clone()
+    } else {
+        format!("{}:{DEFAULT_PORT}", proxy.address)
+    };
+
+    tracing::info!(address, "connecting to ESPHome Bluetooth proxy");
+
+    let stream = TcpStream::connect(&address)
+        .await
+        .map_err(MeaterError::Proxy)?;
+    let (reader, writer) = stream.into_split();
+    let mut connection = Connection { writer };
+
+    let frames = futures::stream::unfold(BufReader::new(reader), |mut reader| async move {
+        let frame = read_frame(&mut reader).await;
+        Some((frame, reader))
+    });
+    let mut frames = Box::pin(frames);
+
+    connection
+        .send(
+            message::HELLO_REQUEST,
+            Message::default()
+                .bytes(1, env!("CARGO_PKG_NAME").as_bytes())
+                .varint(2, API_VERSION.0)
+                .varint(3, API_VERSION.1),
+        )
+        .await
+        .map_err(MeaterError::Proxy)?;
+
+    let password = proxy.password.as_deref().unwrap_or_default();
+
+    connection
+        .send(
+            message::CONNECT_REQUEST,
+            Message::default().bytes(1, password.as_bytes()),
+        )
+        .await
+        .map_err(MeaterError::Proxy)?;
+
+    let mut advertised: HashMap<u64, Advertised> = HashMap::new();
+    let mut devices: HashMap<u64, Device> = HashMap::new();
+    let mut check = tokio::time::interval(Duration::from_secs(1));
+
+    let sender = &client.sender;
+
+    loop {
+        let (kind, payload) = tokio::select! {
+            frame = frames.next() => match frame {
+                Some(frame) => frame.map_err(MeaterError::Proxy)?,
+                None => break,
+            },
+            _ = check.tick() => {
+                let was_connected = devices.values().any(|device| device.connected);
+                let mut streaming = devices.values().filter(|device| device.connected).count();
+
+                for (address, device) in &mut devices {
+                    let timed_out = device
+                        .connecting
+                        .is_some_and(|connecting| connecting.elapsed() > client.stale_after);
+
+                    if device.connected && device.received.elapsed() > client.stale_after {
+                        tracing::warn!(probe = device.index, "no temperature, connecting again");
+                        streaming -= 1;
+
+                        // Other probes still streaming are shown instead.
+                        if streaming == 0 {
+                            sender.send(Event::State(State::Stale)).await?;
+                        }
+                    } else if !timed_out {
+                        continue;
+                    }
+
+                    device.connected = false;
+                    device.connecting = None;
+
+                    let request = Message::default()
+                        .varint(1, *address)
+                        .varint(2, DISCONNECT);
+                    connection
+                        .send(message::DEVICE_REQUEST, request)
+                        .await
+                        .map_err(MeaterError::Proxy)?;
+                }
+
+                if was_connected && !devices.values().any(|device| device.connected) {
+                    sender.send(Event::State(State::Disconnected)).await?;
+                }
+
+                continue;
+            }
+        };
+
+        let fields = fields_of(&payload)?;
+
+        match kind {
+            message::HELLO_RESPONSE => {
+                let name = field(&fields, 4).map(Value::bytes).unwrap_or_default();
+                tracing::info!(name = %String::from_utf8_lossy(name), "connected to proxy");
+            }
+            message::CONNECT_RESPONSE => {
+                if field(&fields, 1).is_some_and(|invalid| invalid.varint() != 0) {
+                    return Err(MeaterError::Proxy(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "invalid password of the proxy",
+                    )));
+                }
+
+                connection
+                    .send(
+                        message::SUBSCRIBE_ADVERTISEMENTS_REQUEST,
+                        Message::default().varint(1, RAW_ADVERTISEMENTS),
+                    )
+                    .await
+                    .map_err(MeaterError::Proxy)?;
+            }
+            message::PING_REQUEST => {
+                connection
+                    .send(message::PING_RESPONSE, Message::default())
+                    .await
+                    .map_err(MeaterError::Proxy)?;
+            }
+            message::DISCONNECT_REQUEST => {
+                // The connection is closed by the proxy right after.
+                let _ = connection
+                    .send(message::DISCONNECT_RESPONSE, Message::default())
+                    .await;
+                break;
+            }
+            message::RAW_ADVERTISEMENTS_RESPONSE => {
+                for advertisement in repeated(&fields, 1) {
+                    let fields = fields_of(advertisement.bytes())?;
+                    let address = field(&fields, 1).map(Value::varint).unwrap_or_default();
+                    let address_type = field(&fields, 3).map(Value::varint).unwrap_or_default();
+                    let data = field(&fields, 4).map(Value::bytes).unwrap_or_default();
+
+                    let seen = advertised.entry(address).or_default();
+                    seen.update(data);
+
+                    let streaming = devices.values().any(|device| device.connected);
+
+                    let device = match devices.entry(address) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => {
+                            match Device::discover(client, indices, address, address_type, seen) {
+                                Some(device) => entry.insert(device),
+                                None => continue,
+                            }
+                        }
+                    };
+
+                    let waiting = device.retry_at.is_some_and(|at| Instant::now() < at);
+
+                    if device.connected || device.connecting.is_some() || waiting {
+                        continue;
+                    }
+
+                    if !streaming {
+                        sender.send(Event::State(State::Connecting)).await?;
+                    }
+
+                    device.connecting = Some(Instant::now());
+                    device.characteristics.clear();
+
+                    let request = Message::default()
+                        .varint(1, address)
+                        .varint(2, CONNECT_WITHOUT_CACHE)
+                        .varint(3, 1)
+                        .varint(4, device.address_type);
+                    connection
+                        .send(message::DEVICE_REQUEST, request)
+                        .await
+                        .map_err(MeaterError::Proxy)?;
+                }
+            }
+            message::DEVICE_CONNECTION_RESPONSE => {
+                let address = field(&fields, 1).map(Value::varint).unwrap_or_default();
+                let connected = field(&fields, 2).is_some_and(|value| value.varint() != 0);
+                let error = field(&fields, 4).map(Value::varint).unwrap_or_default();
+
+                let streaming = devices.values().any(|device| device.connected);
+
+                let Some(device) = devices.get_mut(&address) else {
+                    continue;
+                };
+
+                if connected && error == 0 {
+                    tracing::info!(probe = device.index, "MEATER connected through proxy");
+
+                    let request = Message::default().varint(1, address);
+                    connection
+                        .send(message::GATT_GET_SERVICES_REQUEST, request)
+                        .await
+                        .map_err(MeaterError::Proxy)?;
+                } else if device.connecting.take().is_some() {
+                    device.attempt += 1;
+                    device.retry_at = Some(Instant::now() + client.backoff.delay(device.attempt));
+
+                    tracing::warn!(probe = device.index, error, "proxy unable to connect");
+                    let attempt = device.attempt;
+
+                    if !streaming {
+                        sender
+                            .send(Event::State(State::Retrying { attempt }))
+                            .await?;
+                    }
+                } else if device.connected {
+                    tracing::info!(probe = device.index, "MEATER disconnected from proxy");
+                    device.connected = false;
+
+                    if !devices.values().any(|device| device.connected) {
+                        sender.send(Event::State(State::Disconnected)).await?;
+                    }
+                }
+            }
+            message::GATT_GET_SERVICES_RESPONSE => {
+                let address = field(&fields, 1).map(Value::varint).unwrap_or_default();
+
+                let Some(device) = devices.get_mut(&address) else {
+                    continue;
+                };
+
+                for service in repeated(&fields, 2) {
+                    let service = fields_of(service.bytes())?;
+
+                    for characteristic in repeated(&service, 3) {
+                        let characteristic = fields_of(characteristic.bytes())?;
+                        let uuid = uuid_of(&characteristic);
+                        let handle = field(&characteristic, 2).map(Value::varint);
+
+                        let layout = device.layout;
+
+                        if uuid != Some(layout.temperature) && uuid != Some(layout.battery) {
+                            continue;
+                        }
+
+                        let cccd = repeated(&characteristic, 4).find_map(|descriptor| {
+                            let descriptor = parse(descriptor.bytes()).ok()?;
+
+                            (uuid_of(&descriptor) == Some(CCCD_UUID))
+                                .then(|| field(&descriptor, 2).map(Value::varint))
+                                .flatten()
+                        });
+
+                        if let (Some(uuid), Some(handle)) = (uuid, handle) {
+                            device.characteristics.insert(handle, (uuid, cccd));
+                        }
+                    }
+                }
+            }
+            message::GATT_GET_SERVICES_DONE_RESPONSE => {
+                let address = field(&fields, 1).map(Value::varint).unwrap_or_default();
+
+                let Some(device) = devices.get_mut(&address) else {
+                    continue;
+                };
+
+                if device.characteristics.is_empty() {
+                    tracing::warn!(probe = device.index, "no characteristics to subscribe to");
+                    continue;
+                }
+
+                for (handle, (_, cccd)) in &device.characteristics {
+                    let request = Message::default()
+                        .varint(1, address)
+                        .varint(2, *handle)
+                        .varint(3, 1);
+                    connection
+                        .send(message::GATT_NOTIFY_REQUEST, request)
+                        .await
+                        .map_err(MeaterError::Proxy)?;
+
+                    // Connections without cache leave enabling notifications to the client.
+                    if let Some(cccd) = cccd {
+                        let request = Message::default()
+                            .varint(1, address)
+                            .varint(2, *cccd)
+                            .bytes(3, &[0x01, 0x00]);
+                        connection
+                            .send(message::GATT_WRITE_DESCRIPTOR_REQUEST, request)
+                            .await
+                            .map_err(MeaterError::Proxy)?;
+                    }
+                }
+
+                device.connected = true;
+                device.connecting = None;
+                device.attempt = 0;
+                device.retry_at = None;
+                device.received = Instant::now();
+
+                client.write_last_device(bdaddr(address));
+                sender.send(Event::State(State::Connected)).await?;
+            }
+            message::GATT_NOTIFY_DATA_RESPONSE => {
+                let address = field(&fields, 1).map(Value::varint).unwrap_or_default();
+                let handle = field(&fields, 2).map(Value::varint).unwrap_or_default();
+                let value = field(&fields, 3).map(Value::bytes).unwrap_or_default();
+
+                let Some(device) = devices.get_mut(&address) else {
+                    continue;
+                };
+
+                let Some(&(uuid, _)) = device.characteristics.get(&handle) else {
+                    continue;
+                };
+
+                tracing::info!(probe = device.index, value = ?value, "received notification value");
+
+                if let Some(recorder) = recorder {
+                    recorder.write(device.index, uuid, value);
+                }
+
+                for event in device.decoder.decode(Instant::now(), uuid, value) {
+                    if matches!(event, Event::Temperature { .. }) {
+                        device.received = Instant::now();
+                    }
+
+                    sender.send(event).await?;
+                }
+            }
+            message::GATT_ERROR_RESPONSE => {
+                let handle = field(&fields, 2).map(Value::varint).unwrap_or_default();
+                let error = field(&fields, 3).map(Value::varint).unwrap_or_default();
+                tracing::warn!(handle, error, "GATT request through proxy failed");
+            }
+            _ => {}
+        }
+    }
+
+    Err(MeaterError::Proxy(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "proxy closed the connection",
+    )))
+}
+
+/// Decode the fields of a nested message.
+fn fields_of(payload: &[u8]) -> Result<Vec<(u64, Value<'_>)>, MeaterError> {
+    parse(payload).map_err(MeaterError::Proxy)
+}
+
+/// Return the UUID of a service, characteristic or descriptor, sent as pair of 64-bit halves.
+fn uuid_of(fields: &[(u64, Value<'_>)]) -> Option<Uuid> {
+    let halves: Vec<u64> = repeated(fields, 1).flat_map(Value::varints).collect();
+
+    match halves[..] {
+        [high, low] => Some(Uuid::from_u64_pair(high, low)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROBE_NAME: &[u8] = b"MEATER";
+
+    fn frame(kind: u64, message: Message) -> Vec<u8> {
+        let mut frame = vec![0];
+        put_varint(&mut frame, message.0.len() as u64);
+        put_varint(&mut frame, kind);
+        frame.extend_from_slice(&message.0);
+        frame
+    }
+
+    #[test]
+    fn read_varints() {
+        let mut bytes: &[u8] = &[0x01, 0xac, 0x02, 0xff];
+        assert_eq!(read_varint(&mut bytes), Some(1));
+        assert_eq!(read_varint(&mut bytes), Some(300));
+        // Truncated in the middle of a varint.
+        assert_eq!(read_varint(&mut bytes), None);
+        assert_eq!(read_varint(&mut bytes), None);
+
+        let mut buf = Vec::new();
+        put_varint(&mut buf, u64::MAX);
+        assert_eq!(read_varint(&mut buf.as_slice()), Some(u64::MAX));
+
+        // More bytes than any 64-bit value needs.
+        assert_eq!(read_varint(&mut [0x80; 11].as_slice()), None);
+    }
+
+    #[test]
+    fn parse_messages() {
+        let nested = Message::default().varint(1, 42);
+        let message = Message::default()
+            .varint(1, 7)
+            .bytes(2, &nested.0)
+            .bytes(2, PROBE_NAME);
+
+        let fields = parse(&message.0).unwrap();
+        assert_eq!(field(&fields, 1).map(Value::varint), Some(7));
+        assert_eq!(field(&fields, 3).map(Value::varint), None);
+
+        let values: Vec<_> = repeated(&fields, 2).map(Value::bytes).collect();
+        assert_eq!(values, [nested.0.as_slice(), PROBE_NAME]);
+
+        let nested = parse(values[0]).unwrap();
+        assert_eq!(field(&nested, 1).map(Value::varint), Some(42));
+
+        // Fixed size values are skipped.
+        let fields = parse(&[0x09, 0, 0, 0, 0, 0, 0, 0, 0, 0x15, 0, 0, 0, 0]).unwrap();
+        assert_eq!(fields.len(), 2);
+    }
+
+    #[test]
+    fn parse_malformed_messages() {
+        // Truncated key, varint, bytes and fixed size values.
+        assert!(parse(&[0x80]).is_err());
+        assert!(parse(&[0x08, 0x80]).is_err());
+        assert!(parse(&[0x12, 0x05, 0x01]).is_err());
+        assert!(parse(&[0x09, 0x00]).is_err());
+        // Groups are not supported.
+        assert!(parse(&[0x0b]).is_err());
+    }
+
+    #[test]
+    fn decode_uuids() {
+        let uuid = protocol::PROBE.temperature;
+        let (high, low) = uuid.as_u64_pair();
+
+        let unpacked = Message::default().varint(1, high).varint(1, low);
+        assert_eq!(uuid_of(&parse(&unpacked.0).unwrap()), Some(uuid));
+
+        let mut halves = Vec::new();
+        put_varint(&mut halves, high);
+        put_varint(&mut halves, low);
+        let packed = Message::default().bytes(1, &halves);
+        assert_eq!(uuid_of(&parse(&packed.0).unwrap()), Some(uuid));
+
+        let short = Message::default().varint(1, high);
+        assert_eq!(uuid_of(&parse(&short.0).unwrap()), None);
+    }
+
+    #[test]
+    fn decode_advertisements() {
+        let service = protocol::PROBE.service;
+        let mut services = service.as_bytes().to_vec();
+        services.reverse();
+
+        let mut data = vec![PROBE_NAME.len() as u8 + 1, 0x09];
+        data.extend_from_slice(PROBE_NAME);
+        data.extend_from_slice(&[17, 0x07]);
+        data.extend_from_slice(&services);
+        // Flags, ignored.
+        data.extend_from_slice(&[2, 0x01, 0x06]);
+
+        let mut advertised = Advertised::default();
+        advertised.update(&data);
+        // Scan responses may repeat services.
+        advertised.update(&data[PROBE_NAME.len() + 2..]);
+
+        assert_eq!(advertised.name.as_deref(), Some("MEATER"));
+        assert_eq!(advertised.services, [service]);
+
+        // Truncated structures are ignored.
+        let mut advertised = Advertised::default();
+        advertised.update(&[0x10, 0x09, b'M']);
+        assert_eq!(advertised.name, None);
+    }
+
+    #[tokio::test]
+    async fn read_frames() {
+        let mut bytes = frame(
+            message::HELLO_RESPONSE,
+            Message::default().bytes(4, PROBE_NAME),
+        );
+        bytes.extend(frame(message::PING_REQUEST, Message::default()));
+
+        let mut reader = bytes.as_slice();
+        let (kind, payload) = read_frame(&mut reader).await.unwrap();
+        assert_eq!(kind, message::HELLO_RESPONSE);
+
+        let fields = parse(&payload).unwrap();
+        assert_eq!(field(&fields, 4).map(Value::bytes), Some(PROBE_NAME));
+
+        let (kind, payload) = read_frame(&mut reader).await.unwrap();
+        assert_eq!((kind, payload.len()), (message::PING_REQUEST, 0));
+
+        let err = read_frame(&mut reader).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn read_malformed_frames() {
+        // Encrypted frame.
+        let err = read_frame(&mut [0x01, 0x00, 0x01].as_slice())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Length far beyond any message, nothing may be allocated for it.
+        let mut bytes = vec![0];
+        put_varint(&mut bytes, u64::MAX);
+        put_varint(&mut bytes, message::PING_REQUEST);
+        let err = read_frame(&mut bytes.as_slice()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Truncated length and payload.
+        let err = read_frame(&mut [0x00, 0x80].as_slice()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = read_frame(&mut [0x00, 0x04, 0x08, 0x00].as_slice())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,255 @@
+//! Client for Combustion Inc. Predictive Thermometers.
+//!
+//! The probes broadcast all readings in their manufacturer specific advertising data, so they are
+//! read without connecting. Each advertisement carries the raw values of eight thermistors from the
+//! tip towards the handle and which of them the probe picked as core, surface and ambient virtual
+//! sensors. The core is reported as tip temperature, all sensors with an additional
+//! [`Event::Sensors`].
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use btleplug::api::{Central, CentralEvent, Manager, ScanFilter};
+use btleplug::platform;
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use tokio::sync::mpsc;
+
+use crate::client::{Adapter, Backoff, BatteryState, Event, State};
+use crate::error;
+use crate::source::ProbeSource;
+
+/// Bluetooth company identifier of Combustion Inc.
+pub const VENDOR_ID: u16 = 0x09c7;
+
+/// Product type of the Predictive Thermometer.
+const PRODUCT_PROBE: u8 = 1;
+
+/// Number of thermistors of a probe.
+pub const SENSORS: usize = 8;
+
+/// Time without advertisements after which all probes count as gone.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Readings broadcast by a probe.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Advertisement {
+    pub serial: u32,
+    /// Temperatures of the thermistors in °C from the tip towards the handle.
+    pub sensors: [f32; SENSORS],
+    /// Index of the sensor in the core of the meat.
+    pub core: usize,
+    /// Index of the sensor at the surface of the meat.
+    pub surface: usize,
+    /// Index of the sensor measuring the ambient temperature.
+    pub ambient: usize,
+    pub battery_low: bool,
+}
+
+impl Advertisement {
+    /// Parse the manufacturer specific data of [`VENDOR_ID`] without the vendor itself.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 20 || data[0] != PRODUCT_PROBE {
+            return None;
+        }
+
+        let serial = u32::from_le_bytes(data[1..5].try_into().ok()?);
+
+        // Thirteen bits per thermistor packed little-endian into thirteen bytes.
+        let bits = data[5..18]
+            .iter()
+            .rev()
+            .fold(0u128, |bits, byte| bits << 8 | u128::from(*byte));
+
+        let sensors = std::array::from_fn(|index| {
+            let raw = (bits >> (13 * index)) & 0x1fff;
+            raw as f32 * 0.05 - 20.0
+        });
+
+        // Battery flag followed by the virtual core (T1 to T6), surface (T4 to T7) and ambient
+        // (T5 to T8) sensors.
+        let status = data[19];
+
+        Some(Self {
+            serial,
+            sensors,
+            core: usize::from((status >> 1) & 0b111).min(5),
+            surface: 3 + usize::from((status >> 4) & 0b11),
+            ambient: 4 + usize::from((status >> 6) & 0b11),
+            battery_low: status & 1 == 1,
+        })
+    }
+}
+
+/// Listens to the advertisements of all Combustion probes in range and emits [`Event`]s for them.
+pub struct Combustion {
+    sender: mpsc::Sender<Event>,
+    adapter: Adapter,
+    backoff: Backoff,
+}
+
+impl Combustion {
+    pub fn new() -> (Self, mpsc::Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel(16);
+
+        let combustion = Self {
+            sender,
+            adapter: Adapter::default(),
+            backoff: Backoff::default(),
+        };
+
+        (combustion, receiver)
+    }
+
+    /// Scan with `adapter` instead of the first one.
+    pub fn adapter(mut self, adapter: Adapter) -> Self {
+        self.adapter = adapter;
+        self
+    }
+
+    /// Wait according to `backoff` before scanning again after Bluetooth failed.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Scan for probes and stream their events until the receiver is dropped.
+    pub async fn run(self) -> anyhow::Result<()> {
+        // Probes keep their number across restarts of the scan.
+        let mut indices = HashMap::new();
+        let mut attempt = 0;
+
+        loop {
+            self.sender.send(Event::State(State::Disconnected)).await?;
+
+            let started = Instant::now();
+
+            if let Err(err) = self.scan(&mut indices).await {
+                let message = format!("bluetooth failed: {err}");
+                let event = error::report(None, error::OTHER_FAILED, message);
+                let _ = self.sender.send(event).await;
+            }
+
+            if self.sender.is_closed() {
+                return Ok(());
+            }
+
+            if started.elapsed() > self.backoff.max {
+                attempt = 0;
+            }
+
+            attempt += 1;
+            let delay = self.backoff.delay(attempt);
+            tracing::info!("restarting bluetooth in {delay:?}");
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Listen to advertisements until the adapter goes away.
+    async fn scan(&self, indices: &mut HashMap<u32, usize>) -> anyhow::Result<()> {
+        let manager = platform::Manager::new().await?;
+        let central = self.adapter.select(manager.adapters().await?).await?;
+        let mut events = central.events().await?;
+
+        tracing::info!("looking for Combustion probes");
+        central.start_scan(ScanFilter::default()).await?;
+        self.sender.send(Event::State(State::Connecting)).await?;
+
+        let mut batteries = HashMap::new();
+        let mut seen: Option<Instant> = None;
+        let mut check = tokio::time::interval(TIMEOUT / 2);
+
+        loop {
+            tokio::select! {
+                event = events.next() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+
+                    let CentralEvent::ManufacturerDataAdvertisement {
+                        manufacturer_data, ..
+                    } = event
+                    else {
+                        continue;
+                    };
+
+                    let Some(advertisement) = manufacturer_data
+                        .get(&VENDOR_ID)
+                        .and_then(|data| Advertisement::parse(data))
+                    else {
+                        continue;
+                    };
+
+                    if seen.replace(Instant::now()).is_none() {
+                        self.sender.send(Event::State(State::Connected)).await?;
+                    }
+
+                    let next = indices.len();
+                    let probe = *indices.entry(advertisement.serial).or_insert_with(|| {
+                        tracing::info!(serial = advertisement.serial, "Combustion probe found");
+                        next
+                    });
+
+                    let battery_low = advertisement.battery_low;
+
+                    if batteries.insert(probe, battery_low) != Some(battery_low) {
+                        let (percent, state) = if battery_low {
+                            (10, BatteryState::Low)
+                        } else {
+                            (100, BatteryState::Normal)
+                        };
+
+                        self.sender.send(Event::Battery { probe, percent }).await?;
+                        self.sender.send(Event::BatteryState { probe, state }).await?;
+                    }
+
+                    self.send(probe, &advertisement).await?;
+                }
+                _ = check.tick() => {
+                    if seen.is_some_and(|seen| seen.elapsed() > TIMEOUT) {
+                        tracing::info!("Combustion probes out of range");
+                        seen = None;
+                        batteries.clear();
+                        self.sender.send(Event::State(State::Disconnected)).await?;
+                    }
+                }
+            }
+        }
+
+        Err(anyhow!("bluetooth event stream ended"))
+    }
+
+    /// Send the temperatures of `advertisement` as those of `probe`.
+    async fn send(&self, probe: usize, advertisement: &Advertisement) -> anyhow::Result<()> {
+        let sensors = &advertisement.sensors;
+
+        self.sender
+            .send(Event::Temperature {
+                probe,
+                tip: sensors[advertisement.core],
+                ambient: sensors[advertisement.ambient],
+            })
+            .await?;
+
+        self.sender
+            .send(Event::Sensors {
+                probe,
+                temperatures: sensors.to_vec(),
+                surface: Some(sensors[advertisement.surface]),
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl ProbeSource for Combustion {
+    fn name(&self) -> &'static str {
+        "Combustion"
+    }
+
+    fn run(self: Box<Self>) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(Combustion::run(*self))
+    }
+}
@@ -0,0 +1,551 @@
+//! Configuration file merged with command line arguments.
+//!
+//! Settings are read from [`DEFAULT_PATH`] or the file given with `--config`. All settings are
+//! optional and command line arguments take precedence over the file.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::{Deserialize, Deserializer};
+
+use crate::alerts;
+use crate::buffer;
+use crate::burn_in;
+use crate::cli::Args;
+use crate::display;
+#[cfg(feature = "influxdb")]
+use crate::influxdb;
+use crate::input;
+use crate::led;
+use crate::log_file::Rotation;
+#[cfg(feature = "mqtt")]
+use crate::mqtt;
+use crate::night;
+#[cfg(feature = "notify")]
+use crate::notify;
+#[cfg(feature = "peripheral")]
+use crate::peripheral;
+use crate::presets::Preset;
+use crate::probes;
+use crate::screensaver;
+use crate::smoothing;
+use crate::stall;
+#[cfg(feature = "statsd")]
+use crate::statsd;
+#[cfg(feature = "otel")]
+use crate::telemetry;
+use crate::ui;
+use crate::units::Unit;
+#[cfg(feature = "web")]
+use crate::web;
+
+/// Location of the configuration file if not given on the command line.
+pub const DEFAULT_PATH: &str = "/etc/meater/config.toml";
+
+/// Complete configuration.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub display: display::Options,
+    pub burn_in: burn_in::Options,
+    pub screensaver: screensaver::Options,
+    pub night: night::Options,
+    pub smoothing: smoothing::Options,
+    pub stall: stall::Options,
+    pub input: input::Options,
+    pub led: led::Options,
+    pub ui: ui::Options,
+    pub probe: probes::Options,
+    pub bluetooth: Bluetooth,
+    pub units: Units,
+    pub alerts: Alerts,
+    pub logging: Logging,
+    pub buffer: buffer::Options,
+    #[cfg(feature = "mqtt")]
+    pub mqtt: mqtt::Options,
+    #[cfg(feature = "influxdb")]
+    pub influxdb: influxdb::Options,
+    #[cfg(feature = "statsd")]
+    pub statsd: statsd::Options,
+    #[cfg(feature = "otel")]
+    pub telemetry: telemetry::Options,
+    #[cfg(feature = "notify")]
+    pub notify: notify::Options,
+    #[cfg(feature = "web")]
+    pub web: web::Options,
+    #[cfg(feature = "peripheral")]
+    pub peripheral: peripheral::Options,
+}
+
+/// Bluetooth settings.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Bluetooth {
+    /// Index or name of the Bluetooth adapter to scan with.
+    #[serde(deserialize_with = "deserialize_adapter")]
+    pub adapter: meater::Adapter,
+    /// Address of the only probe to connect to.
+    #[serde(deserialize_with = "deserialize_address")]
+    pub device: Option<meater::BDAddr>,
+    /// Names probes are recognized by besides their service, none to only go by the service.
+    #[serde(deserialize_with = "deserialize_names")]
+    pub names: Vec<meater::protocol::NamePattern>,
+    /// File the address of the last connected probe is kept in for faster reconnects.
+    pub last_device: Option<PathBuf>,
+    /// Only read the temperatures probes advertise instead of connecting to them.
+    pub passive: bool,
+    /// Address of an ESPHome Bluetooth proxy to connect to probes through instead of the adapter.
+    #[cfg(feature = "esphome")]
+    pub esphome_proxy: Option<String>,
+    /// Password of the native API of the proxy.
+    #[cfg(feature = "esphome")]
+    pub esphome_password: Option<String>,
+    /// File the raw notifications of all probes are recorded to.
+    pub record: Option<PathBuf>,
+    /// Seconds to wait after the first failed attempt to connect a probe.
+    pub reconnect_initial: f32,
+    /// Maximum seconds to wait between attempts to connect a probe.
+    pub reconnect_max: f32,
+    /// Fraction the delay between attempts is randomly varied by.
+    pub reconnect_jitter: f32,
+    /// Seconds without temperatures after which a connected probe is connected again.
+    pub stale_seconds: f32,
+    /// Seconds after which scanning is restarted as long as no probe is found.
+    pub scan_seconds: f32,
+    /// Seconds without a probe found after which all devices are scanned for instead of those
+    /// advertising MEATER services.
+    pub unfiltered_seconds: f32,
+}
+
+impl Default for Bluetooth {
+    fn default() -> Self {
+        let backoff = meater::Backoff::default();
+
+        Self {
+            adapter: meater::Adapter::default(),
+            device: None,
+            names: vec![meater::protocol::NamePattern::Exact(
+                meater::protocol::NAME.to_string(),
+            )],
+            last_device: None,
+            passive: false,
+            #[cfg(feature = "esphome")]
+            esphome_proxy: None,
+            #[cfg(feature = "esphome")]
+            esphome_password: None,
+            record: None,
+            reconnect_initial: backoff.initial.as_secs_f32(),
+            reconnect_max: backoff.max.as_secs_f32(),
+            reconnect_jitter: backoff.jitter,
+            stale_seconds: 30.0,
+            scan_seconds: 30.0,
+            unfiltered_seconds: 60.0,
+        }
+    }
+}
+
+impl Bluetooth {
+    /// Delays between connection attempts.
+    pub fn backoff(&self) -> meater::Backoff {
+        meater::Backoff {
+            initial: Duration::from_secs_f32(self.reconnect_initial.max(0.0)),
+            max: Duration::from_secs_f32(self.reconnect_max.max(0.0)),
+            jitter: self.reconnect_jitter,
+        }
+    }
+
+    /// Time without temperatures after which a connected probe is connected again.
+    pub fn stale_after(&self) -> Duration {
+        Duration::from_secs_f32(self.stale_seconds.max(1.0))
+    }
+
+    /// Time after which scanning is restarted as long as no probe is found.
+    pub fn scan_timeout(&self) -> Duration {
+        Duration::from_secs_f32(self.scan_seconds.max(1.0))
+    }
+
+    /// Time without a probe found after which all devices are scanned for.
+    pub fn unfiltered_after(&self) -> Duration {
+        Duration::from_secs_f32(self.unfiltered_seconds.max(0.0))
+    }
+}
+
+/// Unit settings.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Units {
+    /// Unit temperatures are shown and target temperatures are given in.
+    pub temperature: Unit,
+}
+
+/// Buzzer alarm and alert settings.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Alerts {
+    /// Target tip temperatures, the n-th applies to the n-th probe and the last to all others.
+    pub targets: Vec<f32>,
+    /// Degrees the tip has to fall below the target before the buzzer stops again.
+    pub hysteresis: f32,
+    /// GPIO pin (BCM numbering) the piezo buzzer is connected to.
+    pub buzzer_pin: u8,
+    /// Preset setting the target temperature for all probes instead of `targets`.
+    pub preset: Option<Preset>,
+    /// GPIO pin (BCM numbering) of a push button cycling through the presets.
+    pub preset_pin: Option<u8>,
+    /// Rules raising alerts.
+    pub rules: Vec<alerts::Rule>,
+    /// Lowest ambient temperature before the alarm sounds and an alert is raised.
+    pub ambient_min: Option<f32>,
+    /// Highest ambient temperature before the alarm sounds and an alert is raised.
+    pub ambient_max: Option<f32>,
+}
+
+impl Default for Alerts {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            hysteresis: 1.0,
+            buzzer_pin: 18,
+            preset: None,
+            preset_pin: None,
+            rules: Vec::new(),
+            ambient_min: None,
+            ambient_max: None,
+        }
+    }
+}
+
+/// Logging and recording settings.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Logging {
+    /// Maximum level of log messages.
+    #[serde(deserialize_with = "deserialize_level")]
+    pub level: tracing::Level,
+    /// Also write log messages as JSON lines to this file.
+    pub file: Option<PathBuf>,
+    /// Size in MiB after which the log file is rotated.
+    pub file_max_mb: u64,
+    /// Period after which the log file is rotated regardless of its size.
+    pub file_rotation: Rotation,
+    /// Number of rotated log files kept.
+    pub file_keep: usize,
+    /// Log events to CSV files named after this path, a new one for each cook session.
+    pub csv: Option<PathBuf>,
+    /// Keep statistics of all cooks in this file across restarts.
+    pub state: Option<PathBuf>,
+    /// Log to the systemd journal with all fields of the messages instead of to stderr.
+    #[cfg(feature = "journald")]
+    pub journald: bool,
+    /// Store temperatures and cook sessions in this SQLite database.
+    #[cfg(feature = "sqlite")]
+    pub history: Option<PathBuf>,
+}
+
+impl Default for Logging {
+    fn default() -> Self {
+        Self {
+            level: tracing::Level::INFO,
+            file: None,
+            file_max_mb: 10,
+            file_rotation: Rotation::default(),
+            file_keep: 5,
+            csv: None,
+            state: None,
+            #[cfg(feature = "journald")]
+            journald: false,
+            #[cfg(feature = "sqlite")]
+            history: None,
+        }
+    }
+}
+
+fn deserialize_level<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<tracing::Level, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_adapter<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<meater::Adapter, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Adapter {
+        Index(usize),
+        Name(String),
+    }
+
+    Ok(match Adapter::deserialize(deserializer)? {
+        Adapter::Index(index) => meater::Adapter::Index(index),
+        Adapter::Name(name) => meater::Adapter::Name(name),
+    })
+}
+
+fn deserialize_address<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<meater::BDAddr>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|address| address.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+fn deserialize_names<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<meater::protocol::NamePattern>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| pattern.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
+impl Config {
+    /// Load the configuration from `path` or [`DEFAULT_PATH`] if not given.
+    ///
+    /// A missing file at the default location is not an error and yields the default
+    /// configuration.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path, true),
+            None => (Path::new(DEFAULT_PATH), false),
+        };
+
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if !required && err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("unable to read {}", path.display()))
+            }
+        };
+
+        toml::from_str(&content).with_context(|| format!("unable to parse {}", path.display()))
+    }
+
+    /// Override settings with those given on the command line.
+    pub fn merge(mut self, args: Args) -> Self {
+        if let Some(kind) = args.display {
+            self.display.kind = kind;
+        }
+
+        if args.no_display {
+            self.display.kind = display::Kind::None;
+        }
+
+        if let Some(bus) = args.i2c_bus {
+            self.display.i2c_bus = bus;
+        }
+
+        #[cfg(feature = "host")]
+        if args.screenshots.is_some() {
+            self.display.screenshots = args.screenshots;
+        }
+
+        if let Some(shift) = args.burn_in_shift {
+            self.burn_in.shift = shift;
+        }
+
+        if let Some(minutes) = args.burn_in_invert {
+            self.burn_in.invert_minutes = Some(minutes);
+        }
+
+        if args.next_pin.is_some() {
+            self.input.next_pin = args.next_pin;
+        }
+
+        if args.previous_pin.is_some() {
+            self.input.previous_pin = args.previous_pin;
+        }
+
+        if args.sleep_after.is_some() {
+            self.screensaver.timeout_minutes = args.sleep_after;
+        }
+
+        if args.wake_pin.is_some() {
+            self.screensaver.wake_pin = args.wake_pin;
+        }
+
+        if args.led.is_some() {
+            self.led.output = args.led;
+        }
+
+        if let Some(percent) = args.low_battery {
+            self.probe.low_battery = percent;
+        }
+
+        if let Some(kind) = args.probe {
+            self.probe.kind = kind;
+        }
+
+        if let Some(adapter) = args.adapter {
+            self.bluetooth.adapter = adapter;
+        }
+
+        if args.device.is_some() {
+            self.bluetooth.device = args.device;
+        }
+
+        if !args.names.is_empty() {
+            self.bluetooth.names = args.names;
+        }
+
+        if args.last_device.is_some() {
+            self.bluetooth.last_device = args.last_device;
+        }
+
+        #[cfg(feature = "esphome")]
+        if args.esphome_proxy.is_some() {
+            self.bluetooth.esphome_proxy = args.esphome_proxy;
+        }
+
+        if args.passive {
+            self.bluetooth.passive = true;
+        }
+
+        if args.record.is_some() {
+            self.bluetooth.record = args.record;
+        }
+
+        if args.replay.is_some() {
+            self.probe.kind = probes::Kind::Replay;
+            self.probe.replay = args.replay;
+        }
+
+        if let Some(seconds) = args.reconnect_max {
+            self.bluetooth.reconnect_max = seconds;
+        }
+
+        if let Some(filter) = args.smoothing {
+            self.smoothing.filter = filter;
+        }
+
+        if let Some(unit) = args.units {
+            self.units.temperature = unit;
+        }
+
+        if !args.targets.is_empty() {
+            self.alerts.targets = args.targets;
+        }
+
+        if let Some(hysteresis) = args.alarm_hysteresis {
+            self.alerts.hysteresis = hysteresis;
+        }
+
+        if let Some(pin) = args.buzzer_pin {
+            self.alerts.buzzer_pin = pin;
+        }
+
+        if args.preset.is_some() {
+            self.alerts.preset = args.preset;
+        }
+
+        if args.preset_pin.is_some() {
+            self.alerts.preset_pin = args.preset_pin;
+        }
+
+        if args.ambient_min.is_some() {
+            self.alerts.ambient_min = args.ambient_min;
+        }
+
+        if args.ambient_max.is_some() {
+            self.alerts.ambient_max = args.ambient_max;
+        }
+
+        if let Some(level) = args.log_level {
+            self.logging.level = level;
+        }
+
+        if args.log_file.is_some() {
+            self.logging.file = args.log_file;
+        }
+
+        if args.log_csv.is_some() {
+            self.logging.csv = args.log_csv;
+        }
+
+        if args.state.is_some() {
+            self.logging.state = args.state;
+        }
+
+        #[cfg(feature = "journald")]
+        if args.journald {
+            self.logging.journald = true;
+        }
+
+        #[cfg(feature = "sqlite")]
+        if args.history.is_some() {
+            self.logging.history = args.history;
+        }
+
+        #[cfg(feature = "mqtt")]
+        {
+            if args.mqtt_host.is_some() {
+                self.mqtt.host = args.mqtt_host;
+            }
+
+            if let Some(port) = args.mqtt_port {
+                self.mqtt.port = port;
+            }
+
+            if let Some(prefix) = args.mqtt_prefix {
+                self.mqtt.prefix = prefix;
+            }
+
+            if args.homeassistant {
+                self.mqtt.homeassistant = true;
+            }
+
+            if let Some(prefix) = args.homeassistant_prefix {
+                self.mqtt.homeassistant_prefix = prefix;
+            }
+        }
+
+        #[cfg(feature = "influxdb")]
+        {
+            if args.influxdb_url.is_some() {
+                self.influxdb.url = args.influxdb_url;
+            }
+
+            if let Some(bucket) = args.influxdb_bucket {
+                self.influxdb.bucket = bucket;
+            }
+        }
+
+        #[cfg(feature = "statsd")]
+        if args.statsd_host.is_some() {
+            self.statsd.host = args.statsd_host;
+        }
+
+        #[cfg(feature = "otel")]
+        if args.otlp_endpoint.is_some() {
+            self.telemetry.endpoint = args.otlp_endpoint;
+        }
+
+        #[cfg(feature = "peripheral")]
+        if args.peripheral {
+            self.peripheral.enabled = true;
+        }
+
+        #[cfg(feature = "notify")]
+        {
+            if let Some(url) = args.ntfy {
+                self.notify.sinks.push(notify::Sink::Ntfy { url });
+            }
+
+            if let Some(url) = args.webhook {
+                self.notify.sinks.push(notify::Sink::Webhook { url });
+            }
+        }
+
+        #[cfg(feature = "web")]
+        if args.web_listen.is_some() {
+            self.web.listen = args.web_listen;
+        }
+
+        self
+    }
+}
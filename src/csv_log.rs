@@ -0,0 +1,171 @@
+//! Logging of events into CSV files, one per cook session.
+//!
+//! A session starts when a probe connects and ends when the last probe disconnects. Each session
+//! is written to `<stem>-<start time>.<extension>` next to the configured path.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::Local;
+use tokio::sync::mpsc;
+
+const HEADER: &str = "timestamp,probe,state,tip,ambient,battery";
+
+/// Latest values of a probe, repeated in each row so every row is self-contained.
+#[derive(Default, Clone, Copy)]
+struct Values {
+    tip: Option<f32>,
+    ambient: Option<f32>,
+    battery: Option<u16>,
+}
+
+/// Writes rows into the file of the current session.
+struct SessionLog {
+    path: PathBuf,
+    file: Option<BufWriter<File>>,
+    state: meater::State,
+    values: BTreeMap<usize, Values>,
+}
+
+impl SessionLog {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: None,
+            state: meater::State::Disconnected,
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Return the file name for a session starting now.
+    fn session_path(&self) -> PathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "meater".to_string());
+
+        let extension = self
+            .path
+            .extension()
+            .map(|extension| extension.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "csv".to_string());
+
+        let name = format!(
+            "{stem}-{}.{extension}",
+            Local::now().format("%Y%m%d-%H%M%S")
+        );
+
+        self.path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(name)
+    }
+
+    fn start_session(&mut self) -> anyhow::Result<()> {
+        let path = self.session_path();
+        tracing::info!(path = ?path, "starting CSV session log");
+
+        let file =
+            File::create(&path).with_context(|| format!("unable to create {}", path.display()))?;
+        let mut file = BufWriter::new(file);
+        writeln!(file, "{HEADER}")?;
+
+        self.file = Some(file);
+        self.values.clear();
+        Ok(())
+    }
+
+    fn write_row(&mut self, probe: Option<usize>) -> anyhow::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+
+        let values = probe
+            .and_then(|probe| self.values.get(&probe).copied())
+            .unwrap_or_default();
+
+        writeln!(
+            file,
+            "{},{},{},{},{},{}",
+            Local::now().to_rfc3339(),
+            probe
+                .map(|probe| (probe + 1).to_string())
+                .unwrap_or_default(),
+            self.state,
+            values
+                .tip
+                .map(|tip| format!("{tip:.1}"))
+                .unwrap_or_default(),
+            values
+                .ambient
+                .map(|ambient| format!("{ambient:.1}"))
+                .unwrap_or_default(),
+            values
+                .battery
+                .map(|battery| battery.to_string())
+                .unwrap_or_default(),
+        )?;
+
+        // Flush each row so nothing is lost when the Pi is switched off mid-cook.
+        file.flush()?;
+        Ok(())
+    }
+
+    fn handle(&mut self, event: meater::Event) -> anyhow::Result<()> {
+        match event {
+            meater::Event::State(state) => {
+                self.state = state;
+
+                if state == meater::State::Connected && self.file.is_none() {
+                    self.start_session()?;
+                }
+
+                self.write_row(None)?;
+
+                if state == meater::State::Disconnected {
+                    self.file = None;
+                }
+            }
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => {
+                let values = self.values.entry(probe).or_default();
+                values.tip = Some(tip);
+                values.ambient = Some(ambient);
+                self.write_row(Some(probe))?;
+            }
+            meater::Event::Battery { probe, percent } => {
+                self.values.entry(probe).or_default().battery = Some(percent);
+                self.write_row(Some(probe))?;
+            }
+            meater::Event::BatteryState { .. }
+            | meater::Event::Rssi { .. }
+            | meater::Event::Sensors { .. }
+            | meater::Event::ProbeRemoved { .. }
+            | meater::Event::ProbeInserted { .. }
+            | meater::Event::DecodeError { .. }
+            | meater::Event::DeviceInfo { .. }
+            | meater::Event::Error { .. }
+            | meater::Event::SessionEnded => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Log all events received on `receiver` to CSV files based on `path`.
+pub async fn run(path: PathBuf, mut receiver: mpsc::Receiver<meater::Event>) -> anyhow::Result<()> {
+    let mut log = SessionLog::new(path);
+
+    while let Some(event) = receiver.recv().await {
+        log.handle(event)?;
+    }
+
+    Ok(())
+}
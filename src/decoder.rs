@@ -0,0 +1,112 @@
+//! Events decoded from the raw notifications of a probe.
+
+use std::time::Instant;
+
+use uuid::Uuid;
+
+use crate::battery;
+use crate::client::{Calibration, Event};
+use crate::error::MeaterError;
+use crate::protocol::{self, Layout};
+use crate::removal;
+
+/// Decodes the notifications of a single probe.
+#[derive(Clone)]
+pub(crate) struct Decoder {
+    layout: Layout,
+    probe: usize,
+    calibration: Calibration,
+    removal: removal::Detector,
+    /// Battery level last sent, notifications repeating it are not passed on.
+    battery: Option<u16>,
+    battery_state: battery::Detector,
+}
+
+impl Decoder {
+    pub(crate) fn new(
+        layout: Layout,
+        probe: usize,
+        calibration: Calibration,
+        low_battery: u16,
+    ) -> Self {
+        Self {
+            layout,
+            probe,
+            calibration,
+            removal: removal::Detector::default(),
+            battery: None,
+            battery_state: battery::Detector::new(low_battery),
+        }
+    }
+
+    /// Decode the notification `value` of characteristic `uuid` received at `at`.
+    pub(crate) fn decode(&mut self, at: Instant, uuid: Uuid, value: &[u8]) -> Vec<Event> {
+        let probe = self.probe;
+
+        if uuid == self.layout.temperature {
+            let Some((tip, ambient)) = self.layout.decode(value) else {
+                return self.error(uuid, value);
+            };
+
+            let tip = tip + self.calibration.tip;
+            let ambient = ambient + self.calibration.ambient;
+            tracing::debug!(probe, tip, ambient, "decoded temperature");
+
+            let mut events = vec![Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            }];
+
+            match self.removal.update(at, tip, ambient) {
+                Some(removal::Change::Removed) => {
+                    tracing::info!(probe, "probe pulled out of the meat");
+                    events.push(Event::ProbeRemoved { probe });
+                }
+                Some(removal::Change::Inserted) => {
+                    tracing::info!(probe, "probe inserted into the meat");
+                    events.push(Event::ProbeInserted { probe });
+                }
+                None => {}
+            }
+
+            events
+        } else if uuid == self.layout.battery {
+            let Some(percent) = protocol::decode_battery(value) else {
+                return self.error(uuid, value);
+            };
+
+            tracing::debug!(probe, percent, "decoded battery level");
+
+            if self.battery.replace(percent) == Some(percent) {
+                return Vec::new();
+            }
+
+            let mut events = vec![Event::Battery { probe, percent }];
+
+            if let Some(state) = self.battery_state.update(percent) {
+                tracing::info!(probe, percent, %state, "battery state changed");
+                events.push(Event::BatteryState { probe, state });
+            }
+
+            events
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn error(&self, uuid: Uuid, value: &[u8]) -> Vec<Event> {
+        let err = MeaterError::Decode {
+            uuid,
+            value: value.to_vec(),
+        };
+
+        vec![
+            Event::DecodeError {
+                probe: self.probe,
+                value: value.to_vec(),
+            },
+            err.report(Some(self.probe)),
+        ]
+    }
+}
@@ -0,0 +1,739 @@
+//! Display backends the UI is rendered on.
+//!
+//! All drawing goes into a [`Canvas`] owned by the backend, which transfers it to the actual
+//! display on [`DisplayBackend::flush`]. New displays therefore only have to implement
+//! initialization and the transfer.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, OriginDimensions, Point, Size};
+use embedded_graphics::image::Image;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::{Polyline, Primitive, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::Text;
+use embedded_graphics::transform::Transform;
+use embedded_graphics::{Drawable, Pixel};
+use serde::Deserialize;
+
+use crate::font::{self, Font};
+use crate::units::Unit;
+
+#[cfg(feature = "device")]
+mod epaper;
+mod headless;
+#[cfg(feature = "device")]
+mod max7219;
+mod recovering;
+#[cfg(feature = "device")]
+mod sh1106;
+#[cfg(feature = "host")]
+mod simulator;
+#[cfg(feature = "device")]
+mod ssd1306;
+#[cfg(feature = "device")]
+mod st7789;
+#[cfg(feature = "terminal")]
+mod terminal;
+
+#[cfg(feature = "device")]
+pub use epaper::EPaper;
+pub use headless::Headless;
+#[cfg(feature = "device")]
+pub use max7219::Max7219;
+pub use recovering::Recovering;
+#[cfg(feature = "device")]
+pub use sh1106::Sh1106;
+#[cfg(feature = "host")]
+pub use simulator::Simulator;
+#[cfg(feature = "device")]
+pub use ssd1306::Ssd1306;
+#[cfg(feature = "device")]
+pub use st7789::St7789;
+#[cfg(feature = "terminal")]
+pub use terminal::Terminal;
+
+/// Display selectable at runtime.
+#[derive(Clone, Copy, Debug, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// SH1106 OLED connected via I2C or SPI.
+    #[cfg(feature = "device")]
+    Sh1106,
+    /// SSD1306 OLED connected via I2C.
+    #[cfg(feature = "device")]
+    Ssd1306,
+    /// 240x240 ST7789 color TFT connected via SPI.
+    #[cfg(feature = "device")]
+    St7789,
+    /// Waveshare 2.13" e-paper HAT (V2) connected via SPI.
+    #[cfg(feature = "device")]
+    Epd2in13,
+    /// Chained MAX7219 8x8 LED matrices connected via SPI.
+    #[cfg(feature = "device")]
+    Max7219,
+    /// Window emulating the OLED on the host.
+    #[cfg(feature = "host")]
+    Simulator,
+    /// Terminal the program runs in.
+    #[cfg(feature = "terminal")]
+    Terminal,
+    /// No display at all.
+    None,
+}
+
+impl Default for Kind {
+    fn default() -> Self {
+        #[cfg(feature = "device")]
+        return Self::Sh1106;
+
+        #[cfg(all(feature = "host", not(feature = "device")))]
+        return Self::Simulator;
+
+        #[cfg(not(any(feature = "host", feature = "device")))]
+        return Self::None;
+    }
+}
+
+/// Bus the display is connected to.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Bus {
+    #[default]
+    I2c,
+    /// Only supported by the SH1106.
+    Spi,
+}
+
+/// Orientation the display is mounted in.
+///
+/// The layout is drawn for landscape, so on a display mounted upright the frame is turned by a
+/// quarter and shown at half its size.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "u16")]
+pub enum Rotation {
+    #[default]
+    Rotate0,
+    /// Upright, the content turned a quarter clockwise.
+    Rotate90,
+    /// Upside down.
+    Rotate180,
+    /// Upright, the content turned a quarter counterclockwise.
+    Rotate270,
+}
+
+impl TryFrom<u16> for Rotation {
+    type Error = String;
+
+    fn try_from(degrees: u16) -> Result<Self, Self::Error> {
+        match degrees {
+            0 => Ok(Self::Rotate0),
+            90 => Ok(Self::Rotate90),
+            180 => Ok(Self::Rotate180),
+            270 => Ok(Self::Rotate270),
+            _ => Err(format!(
+                "rotation must be 0, 90, 180 or 270 degrees, not {degrees}"
+            )),
+        }
+    }
+}
+
+/// Display settings.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Display to render on.
+    pub kind: Kind,
+    /// Orientation the display is mounted in.
+    pub rotation: Rotation,
+    /// Bus the display is connected to.
+    pub bus: Bus,
+    /// I2C bus the display is connected to.
+    pub i2c_bus: u8,
+    /// SPI bus the display is connected to.
+    pub spi_bus: u8,
+    /// Hardware chip select line of the SPI bus the display is connected to.
+    pub spi_chip_select: u8,
+    /// GPIO pin (BCM numbering) of the data/command line of a display connected via SPI.
+    pub dc_pin: u8,
+    /// GPIO pin (BCM numbering) of the reset line of a display connected via SPI.
+    pub rst_pin: Option<u8>,
+    /// GPIO pin (BCM numbering) of the busy line of an e-paper display.
+    pub busy_pin: u8,
+    /// Number of chained LED matrices.
+    pub modules: usize,
+    /// Brightness of LED matrices from 0 to 15.
+    pub brightness: u8,
+    /// Contrast from 0 to 255, the default of the display if not set.
+    pub contrast: Option<u8>,
+    /// Maximum number of frames per second drawn on the display.
+    pub max_rate: f32,
+    /// Directory with BMP icons replacing the embedded ones of the same name.
+    pub assets: Option<PathBuf>,
+    /// Factor the simulator window is scaled by.
+    #[cfg(feature = "host")]
+    pub scale: u32,
+    /// Directory the simulator saves each frame to.
+    #[cfg(feature = "host")]
+    pub screenshots: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            kind: Kind::default(),
+            rotation: Rotation::default(),
+            bus: Bus::default(),
+            i2c_bus: 1,
+            spi_bus: 0,
+            spi_chip_select: 0,
+            dc_pin: 24,
+            rst_pin: None,
+            busy_pin: 24,
+            modules: 4,
+            brightness: 4,
+            contrast: None,
+            max_rate: 4.0,
+            assets: None,
+            #[cfg(feature = "host")]
+            scale: 4,
+            #[cfg(feature = "host")]
+            screenshots: None,
+        }
+    }
+}
+
+impl Options {
+    /// Minimum time between two frames.
+    pub fn render_interval(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.max_rate.max(0.1))
+    }
+}
+
+/// Create and initialize the backend for the display configured in `options`.
+///
+/// If the display cannot be set up, the error is logged and a headless backend is returned, so
+/// the remaining functionality keeps working without a display. Errors after that are recovered
+/// from by initializing the display again.
+pub fn open(options: &Options) -> Box<dyn DisplayBackend> {
+    let mut display = match create(options) {
+        Ok(display) => display,
+        Err(err) => {
+            tracing::error!("unable to open display, continuing without: {err:#}");
+            return Box::new(Headless::default());
+        }
+    };
+
+    if let Err(err) = display.init() {
+        tracing::error!("unable to initialize display, continuing without: {err:#}");
+        return Box::new(Headless::default());
+    }
+
+    Box::new(Recovering::new(display))
+}
+
+fn create(options: &Options) -> anyhow::Result<Box<dyn DisplayBackend>> {
+    Ok(match options.kind {
+        #[cfg(feature = "device")]
+        Kind::Sh1106 => match options.bus {
+            Bus::I2c => Box::new(Sh1106::new(options.i2c_bus)?),
+            Bus::Spi => {
+                let (spi, dc, rst) = spi(options, sh1106::SPI_CLOCK)?;
+                Box::new(Sh1106::spi(spi, dc, rst))
+            }
+        },
+        #[cfg(feature = "device")]
+        Kind::Ssd1306 => match options.bus {
+            Bus::I2c => Box::new(Ssd1306::new(options.i2c_bus)?),
+            Bus::Spi => return Err(anyhow::anyhow!("SSD1306 is only supported via I2C")),
+        },
+        #[cfg(feature = "device")]
+        Kind::St7789 => {
+            let (spi, dc, rst) = spi(options, st7789::SPI_CLOCK)?;
+            Box::new(St7789::new(spi, dc, rst)?)
+        }
+        #[cfg(feature = "device")]
+        Kind::Epd2in13 => {
+            use anyhow::Context;
+
+            let (spi, dc, rst) = spi(options, epaper::SPI_CLOCK)?;
+            let rst = rst.context("e-paper displays need the RST pin")?;
+
+            let busy = rppal::gpio::Gpio::new()
+                .and_then(|gpio| gpio.get(options.busy_pin))
+                .context("unable to get BUSY pin")?
+                .into_input();
+
+            Box::new(EPaper::new(spi, dc, rst, busy)?)
+        }
+        #[cfg(feature = "device")]
+        Kind::Max7219 => {
+            // The matrices need neither a data/command nor a reset line.
+            let (spi, _, _) = spi(options, max7219::SPI_CLOCK)?;
+            Box::new(Max7219::new(
+                spi,
+                options.modules.max(1),
+                options.brightness,
+            )?)
+        }
+        #[cfg(feature = "host")]
+        Kind::Simulator => Box::new(Simulator::new(options.scale, options.screenshots.clone())),
+        #[cfg(feature = "terminal")]
+        Kind::Terminal => Box::new(Terminal::new()),
+        Kind::None => Box::new(Headless::default()),
+    })
+}
+
+/// Open the SPI bus of `options` at `clock` Hz along with the data/command and reset pins.
+#[cfg(feature = "device")]
+fn spi(
+    options: &Options,
+    clock: u32,
+) -> anyhow::Result<(
+    rppal::spi::Spi,
+    rppal::gpio::OutputPin,
+    Option<rppal::gpio::OutputPin>,
+)> {
+    use anyhow::{anyhow, Context};
+    use rppal::spi;
+
+    let bus = match options.spi_bus {
+        0 => spi::Bus::Spi0,
+        1 => spi::Bus::Spi1,
+        2 => spi::Bus::Spi2,
+        bus => return Err(anyhow!("unsupported SPI bus {bus}")),
+    };
+
+    let chip_select = match options.spi_chip_select {
+        0 => spi::SlaveSelect::Ss0,
+        1 => spi::SlaveSelect::Ss1,
+        2 => spi::SlaveSelect::Ss2,
+        chip_select => return Err(anyhow!("unsupported SPI chip select {chip_select}")),
+    };
+
+    let spi =
+        spi::Spi::new(bus, chip_select, clock, spi::Mode::Mode0).context("unable to create Spi")?;
+
+    let gpio = rppal::gpio::Gpio::new().context("unable to access GPIO")?;
+    let dc = gpio
+        .get(options.dc_pin)
+        .context("unable to get DC pin")?
+        .into_output();
+
+    let rst = options
+        .rst_pin
+        .map(|pin| gpio.get(pin).map(|pin| pin.into_output_high()))
+        .transpose()
+        .context("unable to get RST pin")?;
+
+    Ok((spi, dc, rst))
+}
+
+/// How close a temperature is to its target, color displays draw it in a matching color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Heat {
+    Cold,
+    Warming,
+    Close,
+    Done,
+}
+
+impl Heat {
+    /// Heat of a temperature at `progress` between 0 and 1 from its start to the target.
+    pub fn new(progress: f32) -> Self {
+        if progress >= 1.0 {
+            Self::Done
+        } else if progress >= 0.9 {
+            Self::Close
+        } else if progress >= 0.5 {
+            Self::Warming
+        } else {
+            Self::Cold
+        }
+    }
+}
+
+/// Width of the canvas in pixels.
+pub const WIDTH: u32 = 128;
+
+/// Height of the canvas in pixels.
+pub const HEIGHT: u32 = 64;
+
+/// Monochrome frame buffer the UI is drawn into.
+///
+/// The frame last transferred to the display is kept, so backends on a slow bus only transfer the
+/// pixels that changed since.
+pub struct Canvas {
+    pixels: [bool; (WIDTH * HEIGHT) as usize],
+    #[cfg(any(feature = "device", feature = "host"))]
+    flushed: [bool; (WIDTH * HEIGHT) as usize],
+    /// Everything has to be transferred, e.g. because the display was cleared.
+    #[cfg(any(feature = "device", feature = "host"))]
+    invalid: bool,
+    /// Areas showing a temperature and how close it is to its target.
+    highlights: Vec<(Rectangle, Heat)>,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self {
+            pixels: [false; (WIDTH * HEIGHT) as usize],
+            #[cfg(any(feature = "device", feature = "host"))]
+            flushed: [false; (WIDTH * HEIGHT) as usize],
+            #[cfg(any(feature = "device", feature = "host"))]
+            invalid: true,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+impl Canvas {
+    /// Return `true` if the pixel at `point` is on.
+    #[cfg(any(feature = "device", feature = "terminal"))]
+    pub fn is_on(&self, point: Point) -> bool {
+        index(point).is_some_and(|index| self.pixels[index])
+    }
+
+    /// Move the content by `offset`, dropping whatever ends up outside of the canvas.
+    pub fn translate(&mut self, offset: Point) {
+        let source = std::mem::replace(&mut self.pixels, [false; (WIDTH * HEIGHT) as usize]);
+
+        for (index, on) in source.into_iter().enumerate() {
+            if let Some(index) = self::index(point(index) + offset).filter(|_| on) {
+                self.pixels[index] = true;
+            }
+        }
+
+        for (area, _) in &mut self.highlights {
+            *area = area.translate(offset);
+        }
+    }
+
+    /// Mark `area` as showing a temperature at `heat` until the canvas is cleared.
+    pub fn highlight(&mut self, area: Rectangle, heat: Heat) {
+        self.highlights.push((area, heat));
+    }
+
+    /// Areas marked with [`Canvas::highlight`] since the canvas was cleared.
+    #[cfg(feature = "device")]
+    pub fn highlights(&self) -> &[(Rectangle, Heat)] {
+        &self.highlights
+    }
+
+    /// Turn the content upside down.
+    pub fn rotate_half_turn(&mut self) {
+        self.pixels.reverse();
+
+        for (area, _) in &mut self.highlights {
+            let bottom_right = area.top_left + area.size;
+            area.top_left = Point::new(WIDTH as i32, HEIGHT as i32) - bottom_right;
+        }
+    }
+
+    /// Turn the content a quarter, `clockwise` or not, scaled down to half its size so it fits
+    /// the height of the canvas, centered.
+    pub fn rotate_quarter_turn(&mut self, clockwise: bool) {
+        let source = std::mem::replace(&mut self.pixels, [false; (WIDTH * HEIGHT) as usize]);
+
+        for (index, on) in source.into_iter().enumerate() {
+            if let Some(index) = self::index(quarter_turn(point(index), clockwise)).filter(|_| on) {
+                self.pixels[index] = true;
+            }
+        }
+
+        for (area, _) in &mut self.highlights {
+            if let Some(bottom_right) = area.bottom_right() {
+                *area = Rectangle::with_corners(
+                    quarter_turn(area.top_left, clockwise),
+                    quarter_turn(bottom_right, clockwise),
+                );
+            }
+        }
+    }
+
+    /// Swap on and off pixels.
+    pub fn invert(&mut self) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = !*pixel;
+        }
+    }
+
+    /// Iterate over the pixels that changed since the last [`Canvas::mark_flushed`], or all of
+    /// them at first and after [`Canvas::invalidate`].
+    #[cfg(any(feature = "device", feature = "host"))]
+    pub fn changes(&self) -> impl Iterator<Item = Pixel<BinaryColor>> + '_ {
+        self.pixels
+            .iter()
+            .zip(self.flushed.iter())
+            .enumerate()
+            .filter(|(_, (on, flushed))| self.invalid || on != flushed)
+            .map(|(index, (on, _))| Pixel(point(index), BinaryColor::from(*on)))
+    }
+
+    /// Return `true` if any pixel has to be transferred.
+    #[cfg(feature = "device")]
+    pub fn is_dirty(&self) -> bool {
+        self.invalid || self.pixels != self.flushed
+    }
+
+    /// Remember the current pixels as transferred to the display.
+    #[cfg(any(feature = "device", feature = "host"))]
+    pub fn mark_flushed(&mut self) {
+        self.flushed = self.pixels;
+        self.invalid = false;
+    }
+
+    /// Transfer all pixels with the next flush, e.g. after the display was cleared.
+    #[cfg(feature = "device")]
+    pub fn invalidate(&mut self) {
+        self.invalid = true;
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some(index) = index(point) {
+                self.pixels[index] = color.is_on();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.pixels = [color.is_on(); (WIDTH * HEIGHT) as usize];
+        self.highlights.clear();
+        Ok(())
+    }
+}
+
+/// Map `index` into the pixel buffer to its point on the canvas.
+fn point(index: usize) -> Point {
+    Point::new(index as i32 % WIDTH as i32, index as i32 / WIDTH as i32)
+}
+
+/// Map `point` of the canvas to where it ends up when the content is turned a quarter,
+/// `clockwise` or not, at half its size and centered.
+fn quarter_turn(point: Point, clockwise: bool) -> Point {
+    let half = point / 2;
+    let (width, height) = (HEIGHT as i32 / 2, WIDTH as i32 / 2);
+    let offset = Point::new((WIDTH as i32 - width) / 2, (HEIGHT as i32 - height) / 2);
+
+    let turned = if clockwise {
+        Point::new(width - 1 - half.y, half.x)
+    } else {
+        Point::new(half.y, height - 1 - half.x)
+    };
+
+    turned + offset
+}
+
+/// Map `point` to the index into the pixel buffer if it lies within the canvas.
+fn index(point: Point) -> Option<usize> {
+    let x = u32::try_from(point.x).ok().filter(|x| *x < WIDTH)?;
+    let y = u32::try_from(point.y).ok().filter(|y| *y < HEIGHT)?;
+    Some((y * WIDTH + x) as usize)
+}
+
+/// A display the UI can be rendered on.
+pub trait DisplayBackend {
+    /// Initialize the display hardware and blank the screen.
+    fn init(&mut self) -> anyhow::Result<()>;
+
+    /// Canvas that is transferred to the display on [`DisplayBackend::flush`].
+    fn canvas(&mut self) -> &mut Canvas;
+
+    /// Transfer the canvas to the display.
+    fn flush(&mut self) -> anyhow::Result<()>;
+
+    /// Turn the display on or off, backends without power control ignore this.
+    fn set_power(&mut self, _on: bool) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Set the contrast or brightness from 0 to 255, backends without control over it ignore
+    /// this.
+    fn set_contrast(&mut self, _contrast: u8) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Clear the canvas.
+    fn clear(&mut self) -> anyhow::Result<()> {
+        self.canvas().clear(BinaryColor::Off)?;
+        Ok(())
+    }
+
+    /// Draw `icon` with its top left corner at `position`.
+    fn draw_icon(
+        &mut self,
+        icon: &tinybmp::Bmp<BinaryColor>,
+        position: Point,
+    ) -> anyhow::Result<()> {
+        Image::new(icon, position).draw(self.canvas())?;
+        Ok(())
+    }
+
+    /// Draw `text` with its baseline starting at `position`.
+    fn draw_text(
+        &mut self,
+        text: &str,
+        position: Point,
+        style: MonoTextStyle<BinaryColor>,
+    ) -> anyhow::Result<()> {
+        Text::new(text, position, style).draw(self.canvas())?;
+        Ok(())
+    }
+
+    /// Draw a temperature given in degree Celsius in `unit` with its baseline starting at
+    /// `position` and return the area it covers.
+    ///
+    /// Values taking more than three glyphs, like -100 or 1000, get a smaller unit symbol so they
+    /// leave as much room right of them as three glyphs with a regular one.
+    fn draw_temperature(
+        &mut self,
+        celsius: f32,
+        unit: Unit,
+        position: Point,
+        font: Font,
+    ) -> anyhow::Result<Rectangle> {
+        let value = unit.convert(celsius).round();
+
+        // Values just below zero would be shown as -0.
+        let value = if value == 0.0 { 0.0 } else { value };
+        let number = format!("{value:.0}");
+
+        if number.len() <= 3 {
+            let text = format!("{number}{}", unit.symbol());
+            self.draw_text(&text, position, font.style())?;
+            return Ok(Text::new(&text, position, font.style()).bounding_box());
+        }
+
+        self.draw_text(&number, position, font.style())?;
+
+        let x = position.x + font.width(&number);
+        let symbol = font::MEDIUM.style();
+        self.draw_text(unit.symbol(), Point::new(x, position.y), symbol)?;
+
+        let area = Text::new(&number, position, font.style()).bounding_box();
+        let width = font::MEDIUM.width(unit.symbol()) as u32;
+        Ok(Rectangle::new(
+            area.top_left,
+            area.size + Size::new(width, 0),
+        ))
+    }
+
+    /// Mark `area` as showing a temperature at `heat`, monochrome displays ignore it.
+    fn highlight(&mut self, area: Rectangle, heat: Heat) {
+        self.canvas().highlight(area, heat);
+    }
+
+    /// Draw a bar filled to `progress` between 0 and 1 into `area`.
+    fn draw_progress(&mut self, progress: f32, area: Rectangle) -> anyhow::Result<()> {
+        area.into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(self.canvas())?;
+
+        let width = (progress.clamp(0.0, 1.0) * area.size.width as f32).round() as u32;
+
+        Rectangle::new(area.top_left, Size::new(width, area.size.height))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(self.canvas())?;
+
+        Ok(())
+    }
+
+    /// Draw `values` as a line graph into `area`, one pixel per value from the left, scaled so
+    /// the smallest value touches the bottom.
+    fn draw_sparkline(&mut self, values: &[f32], area: Rectangle) -> anyhow::Result<()> {
+        let (min, max) = values
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), value| {
+                (min.min(*value), max.max(*value))
+            });
+
+        // Do not blow up noise of a flat line to the full height.
+        let range = (max - min).max(1.0);
+        let height = area.size.height.saturating_sub(1) as f32;
+        let bottom = area.top_left.y + height as i32;
+
+        let points: Vec<Point> = values
+            .iter()
+            .take(area.size.width as usize)
+            .enumerate()
+            .map(|(x, value)| {
+                let y = ((value - min) / range * height).round() as i32;
+                Point::new(area.top_left.x + x as i32, bottom - y)
+            })
+            .collect();
+
+        Polyline::new(&points)
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(self.canvas())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas(points: &[Point]) -> Canvas {
+        let mut canvas = Canvas::default();
+
+        for point in points {
+            canvas.pixels[index(*point).unwrap()] = true;
+        }
+
+        canvas
+    }
+
+    fn on(canvas: &Canvas) -> Vec<Point> {
+        (0..canvas.pixels.len())
+            .filter(|index| canvas.pixels[*index])
+            .map(point)
+            .collect()
+    }
+
+    #[test]
+    fn rotate_quarter_turn_clockwise() {
+        let mut canvas = canvas(&[Point::new(0, 0), Point::new(127, 63)]);
+        canvas.rotate_quarter_turn(true);
+        assert_eq!(on(&canvas), [Point::new(79, 0), Point::new(48, 63)]);
+    }
+
+    #[test]
+    fn rotate_quarter_turn_counterclockwise() {
+        let mut canvas = canvas(&[Point::new(0, 0), Point::new(127, 0)]);
+        canvas.rotate_quarter_turn(false);
+        assert_eq!(on(&canvas), [Point::new(48, 0), Point::new(48, 63)]);
+    }
+
+    #[test]
+    fn rotate_highlights_with_content() {
+        let mut canvas = Canvas::default();
+        canvas.highlight(
+            Rectangle::new(Point::new(0, 0), Size::new(8, 4)),
+            Heat::Done,
+        );
+        canvas.rotate_quarter_turn(true);
+        assert_eq!(
+            canvas.highlights[0].0,
+            Rectangle::new(Point::new(78, 0), Size::new(2, 4))
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::Context;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::primitives::{PointsIter, Rectangle};
+use epd_waveshare::buffer_len;
+use epd_waveshare::epd2in13_v2::{self, Epd2in13};
+use epd_waveshare::prelude::{Color, RefreshLut, WaveshareDisplay};
+use rppal::gpio::{InputPin, OutputPin};
+use rppal::hal::Delay;
+use rppal::spi::Spi;
+
+use super::{Canvas, DisplayBackend, HEIGHT, WIDTH};
+
+/// Clock of the SPI bus in Hz.
+pub const SPI_CLOCK: u32 = 4_000_000;
+
+/// Quick refreshes after which the whole panel is refreshed to clear ghosting.
+const FULL_REFRESH_EVERY: u32 = 20;
+
+/// Area of the panel in landscape the canvas is scaled into, keeping its aspect ratio.
+const AREA: Rectangle = Rectangle::new(Point::new(3, 0), Size::new(244, 122));
+
+/// Bytes of a frame in the panel's native portrait layout.
+const FRAME_LEN: usize = buffer_len(epd2in13_v2::WIDTH as usize, epd2in13_v2::HEIGHT as usize);
+
+/// Bytes of a single portrait row of the panel.
+const ROW_LEN: usize = (epd2in13_v2::WIDTH as usize).div_ceil(8);
+
+/// Chip select driven by the SPI controller instead of the driver.
+struct NoPin;
+
+impl embedded_hal::digital::v2::OutputPin for NoPin {
+    type Error = core::convert::Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+type Epd = Epd2in13<Spi, NoPin, InputPin, OutputPin, OutputPin, Delay>;
+
+/// Waveshare 2.13" e-paper HAT (V2) connected via SPI.
+///
+/// E-ink keeps its content without power but slowly ghosts with quick refreshes, so frames are
+/// shown with a quick refresh and every few of them with a full one.
+pub struct EPaper {
+    spi: Spi,
+    epd: Epd,
+    delay: Delay,
+    /// Frame buffer of the panel the scaled up canvas is drawn into.
+    frame: [u8; FRAME_LEN],
+    canvas: Canvas,
+    /// Quick refreshes since the last full one.
+    quick: u32,
+}
+
+impl EPaper {
+    /// Connect via `spi` with the data/command pin `dc`, the reset pin `rst` and the `busy` pin.
+    pub fn new(
+        mut spi: Spi,
+        dc: OutputPin,
+        rst: OutputPin,
+        busy: InputPin,
+    ) -> anyhow::Result<Self> {
+        let mut delay = Delay::new();
+        let epd = Epd2in13::new(&mut spi, NoPin, busy, dc, rst, &mut delay)
+            .context("unable to create e-paper display")?;
+
+        Ok(Self {
+            spi,
+            epd,
+            delay,
+            frame: [Color::White.get_byte_value(); FRAME_LEN],
+            canvas: Canvas::default(),
+            quick: FULL_REFRESH_EVERY,
+        })
+    }
+
+    /// Switch the panel to the full or quick `refresh`.
+    fn set_refresh(&mut self, refresh: RefreshLut) -> anyhow::Result<()> {
+        self.epd
+            .set_refresh(&mut self.spi, &mut self.delay, refresh)
+            .context("unable to set refresh mode")
+    }
+
+    /// Set the landscape `point` of the frame, which lies on its side in the portrait panel.
+    fn set_pixel(&mut self, point: Point, color: Color) {
+        let x = epd2in13_v2::WIDTH as usize - 1 - point.y as usize;
+        let y = point.x as usize;
+        let index = y * ROW_LEN + x / 8;
+        let bit = 0x80 >> (x % 8);
+
+        // Set bits are white, cleared ones black.
+        match color {
+            Color::Black => self.frame[index] &= !bit,
+            Color::White => self.frame[index] |= bit,
+        }
+    }
+}
+
+impl DisplayBackend for EPaper {
+    fn init(&mut self) -> anyhow::Result<()> {
+        self.epd
+            .wake_up(&mut self.spi, &mut self.delay)
+            .context("unable to initialize display")?;
+        self.epd
+            .clear_frame(&mut self.spi, &mut self.delay)
+            .context("unable to clear display")?;
+        self.quick = FULL_REFRESH_EVERY;
+        self.canvas.invalidate();
+        self.flush()
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    fn set_power(&mut self, on: bool) -> anyhow::Result<()> {
+        if on {
+            // The panel forgets its mode while asleep.
+            self.quick = FULL_REFRESH_EVERY;
+            self.epd.wake_up(&mut self.spi, &mut self.delay)
+        } else {
+            self.epd.sleep(&mut self.spi, &mut self.delay)
+        }
+        .context("unable to switch display")
+    }
+
+    /// Transfer the whole frame scaled up unless it is unchanged.
+    fn flush(&mut self) -> anyhow::Result<()> {
+        if !self.canvas.is_dirty() {
+            return Ok(());
+        }
+
+        for point in AREA.points() {
+            let offset = point - AREA.top_left;
+            let x = offset.x * WIDTH as i32 / AREA.size.width as i32;
+            let y = offset.y * HEIGHT as i32 / AREA.size.height as i32;
+            let on = self.canvas.is_on(Point::new(x, y));
+            self.set_pixel(point, if on { Color::Black } else { Color::White });
+        }
+
+        let full = self.quick >= FULL_REFRESH_EVERY;
+
+        if full {
+            self.set_refresh(RefreshLut::Full)?;
+        } else if self.quick == 0 {
+            self.set_refresh(RefreshLut::Quick)?;
+        }
+
+        self.epd
+            .update_and_display_frame(&mut self.spi, &self.frame, &mut self.delay)
+            .context("unable to flush display")?;
+
+        if full {
+            // Back to quick refreshes with the next frame.
+            self.quick = 0;
+        } else {
+            self.quick += 1;
+        }
+
+        self.canvas.mark_flushed();
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use super::{Canvas, DisplayBackend};
+
+/// Backend discarding everything, for running without a display.
+#[derive(Default)]
+pub struct Headless {
+    canvas: Canvas,
+}
+
+impl DisplayBackend for Headless {
+    fn init(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
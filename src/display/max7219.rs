@@ -0,0 +1,302 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+use embedded_graphics::mono_font::ascii::FONT_5X7;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::{Drawable, Pixel};
+use max7219::connectors::SpiConnector;
+use max7219::MAX7219;
+use rppal::spi::Spi;
+
+use super::{Canvas, DisplayBackend};
+use crate::font::Font;
+use crate::units::Unit;
+
+/// Clock of the SPI bus in Hz.
+pub const SPI_CLOCK: u32 = 1_000_000;
+
+/// Time between moving scrolling text by one column.
+const SCROLL_INTERVAL: Duration = Duration::from_millis(60);
+
+/// Columns between the end of scrolling text and its start coming around again.
+const SCROLL_GAP: usize = 8;
+
+/// Change of what the matrices show.
+enum Command {
+    Show(String),
+    Power(bool),
+    /// Brightness from 0 to 15.
+    Brightness(u8),
+}
+
+/// Chain of MAX7219 8x8 LED matrices connected via SPI.
+///
+/// The matrices are far too small for the canvas. They show the first temperature drawn in a
+/// frame instead, or if there is none, e.g. while searching for the probe, the text of the frame
+/// scrolling by.
+pub struct Max7219 {
+    canvas: Canvas,
+    /// Temperature drawn since the canvas was cleared.
+    temperature: Option<String>,
+    /// Texts drawn since the canvas was cleared.
+    texts: Vec<String>,
+    /// Text shown as of the last flush.
+    shown: Option<String>,
+    commands: mpsc::Sender<Command>,
+}
+
+impl Max7219 {
+    /// Connect `modules` chained matrices via `spi` and set them to `brightness` from 0 to 15.
+    pub fn new(spi: Spi, modules: usize, brightness: u8) -> anyhow::Result<Self> {
+        let mut matrices = MAX7219::from_spi(modules, spi)
+            .map_err(|err| anyhow!("unable to create LED matrices: {err:?}"))?;
+
+        for module in 0..modules {
+            matrices
+                .set_intensity(module, brightness.min(15))
+                .map_err(|err| anyhow!("unable to set brightness: {err:?}"))?;
+        }
+
+        let (commands, receiver) = mpsc::channel();
+        std::thread::spawn(move || scroll(matrices, modules, receiver));
+
+        Ok(Self {
+            canvas: Canvas::default(),
+            temperature: None,
+            texts: Vec::new(),
+            shown: None,
+            commands,
+        })
+    }
+
+    fn send(&self, command: Command) -> anyhow::Result<()> {
+        self.commands
+            .send(command)
+            .map_err(|_| anyhow!("LED matrix thread is gone"))
+    }
+}
+
+impl DisplayBackend for Max7219 {
+    fn init(&mut self) -> anyhow::Result<()> {
+        self.shown = None;
+        self.send(Command::Power(true))
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    fn set_power(&mut self, on: bool) -> anyhow::Result<()> {
+        self.send(Command::Power(on))
+    }
+
+    fn set_contrast(&mut self, contrast: u8) -> anyhow::Result<()> {
+        self.send(Command::Brightness(contrast / 16))
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        let text = self
+            .temperature
+            .clone()
+            .unwrap_or_else(|| self.texts.join(" "));
+
+        if self.shown.as_ref() == Some(&text) {
+            return Ok(());
+        }
+
+        self.shown = Some(text.clone());
+        self.send(Command::Show(text))
+    }
+
+    fn clear(&mut self) -> anyhow::Result<()> {
+        self.temperature = None;
+        self.texts.clear();
+        Ok(())
+    }
+
+    fn draw_icon(
+        &mut self,
+        _icon: &tinybmp::Bmp<BinaryColor>,
+        _position: Point,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn draw_text(
+        &mut self,
+        text: &str,
+        _position: Point,
+        _style: MonoTextStyle<BinaryColor>,
+    ) -> anyhow::Result<()> {
+        // The font has no degree sign.
+        self.texts.push(text.replace('°', ""));
+        Ok(())
+    }
+
+    fn draw_temperature(
+        &mut self,
+        celsius: f32,
+        unit: Unit,
+        _position: Point,
+        _font: Font,
+    ) -> anyhow::Result<Rectangle> {
+        if self.temperature.is_none() {
+            let value = unit.convert(celsius).round();
+            let value = if value == 0.0 { 0.0 } else { value };
+            let symbol = unit.symbol().replace('°', "");
+            self.temperature = Some(format!("{value:.0}{symbol}"));
+        }
+
+        Ok(Rectangle::zero())
+    }
+
+    fn draw_progress(&mut self, _progress: f32, _area: Rectangle) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn draw_sparkline(&mut self, _values: &[f32], _area: Rectangle) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Columns of lit pixels, bit 0 at the top.
+#[derive(Default)]
+struct Columns(Vec<u8>);
+
+impl Columns {
+    /// Render `text` in the small font.
+    fn new(text: &str) -> Self {
+        let mut columns = Self::default();
+        let style = MonoTextStyle::new(&FONT_5X7, BinaryColor::On);
+        let width = text.chars().count() * (FONT_5X7.character_size.width + 1) as usize;
+        columns.0.resize(width, 0);
+
+        // Drawing into a vector cannot fail.
+        let _ = Text::with_baseline(text, Point::zero(), style, Baseline::Top).draw(&mut columns);
+
+        columns
+    }
+}
+
+impl OriginDimensions for Columns {
+    fn size(&self) -> Size {
+        Size::new(self.0.len() as u32, 8)
+    }
+}
+
+impl DrawTarget for Columns {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+
+            if let Some(column) = self.0.get_mut(x).filter(|_| y < 8 && color.is_on()) {
+                *column |= 1 << y;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Show the text received on `commands` on the chain of `modules` matrices, scrolling it if it
+/// does not fit, until the backend is dropped.
+fn scroll(
+    mut matrices: MAX7219<SpiConnector<Spi>>,
+    modules: usize,
+    commands: mpsc::Receiver<Command>,
+) {
+    let width = modules * 8;
+    let mut columns = Columns::default();
+    let mut offset = 0;
+    let mut changed = false;
+
+    loop {
+        match commands.recv_timeout(SCROLL_INTERVAL) {
+            Ok(Command::Show(text)) => {
+                columns = Columns::new(&text);
+                offset = 0;
+                changed = true;
+            }
+            Ok(Command::Power(on)) => {
+                let result = if on {
+                    matrices.power_on()
+                } else {
+                    matrices.power_off()
+                };
+
+                if let Err(err) = result {
+                    tracing::warn!("unable to switch LED matrices: {err:?}");
+                }
+
+                continue;
+            }
+            Ok(Command::Brightness(brightness)) => {
+                for module in 0..modules {
+                    if let Err(err) = matrices.set_intensity(module, brightness) {
+                        tracing::warn!("unable to set brightness of LED matrices: {err:?}");
+                    }
+                }
+
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+
+        let length = columns.0.len();
+
+        // Text that fits only has to be written once.
+        if !changed && length <= width {
+            continue;
+        }
+
+        changed = false;
+
+        // Text that fits is centered, longer text scrolls by.
+        let column = |index: usize| -> u8 {
+            if length <= width {
+                let padding = (width - length) / 2;
+                index
+                    .checked_sub(padding)
+                    .and_then(|index| columns.0.get(index))
+                    .copied()
+                    .unwrap_or_default()
+            } else {
+                let index = (offset + index) % (length + SCROLL_GAP);
+                columns.0.get(index).copied().unwrap_or_default()
+            }
+        };
+
+        for module in 0..modules {
+            let mut rows = [0u8; 8];
+
+            for (row, bits) in rows.iter_mut().enumerate() {
+                for x in 0..8 {
+                    if column(module * 8 + x) & (1 << row) != 0 {
+                        *bits |= 0x80 >> x;
+                    }
+                }
+            }
+
+            if let Err(err) = matrices.write_raw(module, &rows) {
+                tracing::warn!("unable to update LED matrices: {err:?}");
+            }
+        }
+
+        offset = (offset + 1) % (length + SCROLL_GAP);
+    }
+}
@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+use embedded_graphics::geometry::Point;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::Rectangle;
+
+use super::{Canvas, DisplayBackend, Heat};
+use crate::font::Font;
+use crate::units::Unit;
+
+/// Failed operations in a row after which the display counts as gone, e.g. unplugged.
+const MAX_FAILURES: u32 = 5;
+
+/// Delay between attempts to initialize a display that is gone.
+const REINIT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Backend initializing the wrapped display again after errors, e.g. a NACK on a noisy I2C bus.
+///
+/// If errors persist, the display is assumed to be unplugged and initialized periodically until
+/// it is back, so the UI recovers in place without a restart.
+pub struct Recovering {
+    display: Box<dyn DisplayBackend>,
+    /// Operations that failed in a row.
+    failures: u32,
+    /// Time of the last attempt to initialize the display while it is gone.
+    attempted: Option<Instant>,
+    /// Contrast set last, restored after initializing the display again.
+    contrast: Option<u8>,
+}
+
+impl Recovering {
+    pub fn new(display: Box<dyn DisplayBackend>) -> Self {
+        Self {
+            display,
+            failures: 0,
+            attempted: None,
+            contrast: None,
+        }
+    }
+
+    /// Run `operation`, initializing the display again and retrying once if it fails.
+    ///
+    /// While the display is gone, `operation` only runs after initializing it succeeded again.
+    fn retry(&mut self, operation: impl Fn(&mut dyn DisplayBackend) -> anyhow::Result<()>) {
+        if self.failures >= MAX_FAILURES {
+            if self
+                .attempted
+                .is_some_and(|attempted| attempted.elapsed() < REINIT_INTERVAL)
+            {
+                return;
+            }
+
+            self.attempted = Some(Instant::now());
+
+            match self.reinit(&operation) {
+                Ok(()) => tracing::info!("display is back"),
+                Err(err) => tracing::debug!("display still gone: {err:#}"),
+            }
+
+            return;
+        }
+
+        let Err(err) = operation(self.display.as_mut()) else {
+            self.failures = 0;
+            return;
+        };
+
+        self.failures += 1;
+
+        if self.failures >= MAX_FAILURES {
+            tracing::error!(
+                "display failed {} times in a row: {err:#}, initializing it every {:?}",
+                self.failures,
+                REINIT_INTERVAL
+            );
+            self.attempted = Some(Instant::now());
+            return;
+        }
+
+        tracing::warn!("display failed: {err:#}, initializing it again");
+
+        if let Err(err) = self.reinit(&operation) {
+            tracing::warn!("unable to recover display: {err:#}");
+        }
+    }
+
+    /// Initialize and clear the display and restore its contrast, then redraw the last frame still
+    /// held by the canvas by running `operation`.
+    fn reinit(
+        &mut self,
+        operation: &impl Fn(&mut dyn DisplayBackend) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.display.init()?;
+
+        if let Some(contrast) = self.contrast {
+            self.display.set_contrast(contrast)?;
+        }
+
+        self.display.flush()?;
+        operation(self.display.as_mut())?;
+        self.failures = 0;
+        Ok(())
+    }
+}
+
+impl DisplayBackend for Recovering {
+    fn init(&mut self) -> anyhow::Result<()> {
+        self.display.init()
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        self.display.canvas()
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        self.retry(|display| display.flush());
+        Ok(())
+    }
+
+    fn set_power(&mut self, on: bool) -> anyhow::Result<()> {
+        self.retry(|display| display.set_power(on));
+        Ok(())
+    }
+
+    fn set_contrast(&mut self, contrast: u8) -> anyhow::Result<()> {
+        self.contrast = Some(contrast);
+        self.retry(|display| display.set_contrast(contrast));
+        Ok(())
+    }
+
+    // Drawing is left to the wrapped display, which may not draw into the canvas at all.
+
+    fn clear(&mut self) -> anyhow::Result<()> {
+        self.display.clear()
+    }
+
+    fn draw_icon(
+        &mut self,
+        icon: &tinybmp::Bmp<BinaryColor>,
+        position: Point,
+    ) -> anyhow::Result<()> {
+        self.display.draw_icon(icon, position)
+    }
+
+    fn draw_text(
+        &mut self,
+        text: &str,
+        position: Point,
+        style: MonoTextStyle<BinaryColor>,
+    ) -> anyhow::Result<()> {
+        self.display.draw_text(text, position, style)
+    }
+
+    fn draw_temperature(
+        &mut self,
+        celsius: f32,
+        unit: Unit,
+        position: Point,
+        font: Font,
+    ) -> anyhow::Result<Rectangle> {
+        self.display.draw_temperature(celsius, unit, position, font)
+    }
+
+    fn highlight(&mut self, area: Rectangle, heat: Heat) {
+        self.display.highlight(area, heat);
+    }
+
+    fn draw_progress(&mut self, progress: f32, area: Rectangle) -> anyhow::Result<()> {
+        self.display.draw_progress(progress, area)
+    }
+
+    fn draw_sparkline(&mut self, values: &[f32], area: Rectangle) -> anyhow::Result<()> {
+        self.display.draw_sparkline(values, area)
+    }
+}
@@ -0,0 +1,106 @@
+use std::convert::Infallible;
+use std::fmt::Debug;
+
+use anyhow::{anyhow, Context};
+use embedded_graphics::draw_target::DrawTarget;
+use rppal::gpio::OutputPin;
+use rppal::spi::Spi;
+use sh1106::builder::NoOutputPin;
+use sh1106::interface::{DisplayInterface, I2cInterface, SpiInterface};
+use sh1106::mode::GraphicsMode;
+
+use super::{Canvas, DisplayBackend};
+
+/// Clock of the SPI bus in Hz, well below the 10 MHz the SH1106 supports.
+pub const SPI_CLOCK: u32 = 8_000_000;
+
+/// SH1106 OLED connected via I2C or SPI.
+pub struct Sh1106<DI: DisplayInterface> {
+    display: GraphicsMode<DI>,
+    /// Pin resetting the display before initialization, only wired up with SPI.
+    reset: Option<OutputPin>,
+    canvas: Canvas,
+}
+
+impl Sh1106<I2cInterface<rppal::i2c::I2c>> {
+    pub fn new(bus: u8) -> anyhow::Result<Self> {
+        let interface = rppal::i2c::I2c::with_bus(bus).context("unable to create I2c")?;
+
+        let display = sh1106::Builder::new()
+            .with_size(sh1106::displaysize::DisplaySize::Display128x64)
+            .connect_i2c(interface)
+            .into();
+
+        Ok(Self {
+            display,
+            reset: None,
+            canvas: Canvas::default(),
+        })
+    }
+}
+
+/// Chip select is driven by the SPI controller, the unused pin fails like the GPIO pins.
+type NoChipSelect = NoOutputPin<Infallible>;
+
+impl Sh1106<SpiInterface<Spi, OutputPin, NoChipSelect>> {
+    /// Connect via `spi` with the data/command pin `dc` and the optional reset pin `rst`.
+    pub fn spi(spi: Spi, dc: OutputPin, rst: Option<OutputPin>) -> Self {
+        let display = sh1106::Builder::new()
+            .with_size(sh1106::displaysize::DisplaySize::Display128x64)
+            .connect_spi(spi, dc, NoChipSelect::new())
+            .into();
+
+        Self {
+            display,
+            reset: rst,
+            canvas: Canvas::default(),
+        }
+    }
+}
+
+impl<DI> DisplayBackend for Sh1106<DI>
+where
+    DI: DisplayInterface,
+    DI::Error: Debug,
+{
+    fn init(&mut self) -> anyhow::Result<()> {
+        if let Some(reset) = self.reset.as_mut() {
+            self.display
+                .reset(reset, &mut rppal::hal::Delay::new())
+                .map_err(|err| anyhow!("unable to reset display: {err:?}"))?;
+        }
+
+        self.display
+            .init()
+            .map_err(|err| anyhow!("unable to initialize display: {err:?}"))?;
+        self.display.clear();
+        self.canvas.invalidate();
+        self.flush()
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    // The driver offers no command to switch the panel off, so it stays on blanked while asleep.
+
+    fn set_contrast(&mut self, contrast: u8) -> anyhow::Result<()> {
+        self.display
+            .set_contrast(contrast)
+            .map_err(|err| anyhow!("unable to set contrast: {err:?}"))
+    }
+
+    /// Transfer the frame unless it is unchanged, the driver always sends the whole frame.
+    fn flush(&mut self) -> anyhow::Result<()> {
+        if !self.canvas.is_dirty() {
+            return Ok(());
+        }
+
+        self.display.draw_iter(self.canvas.changes())?;
+        self.display
+            .flush()
+            .map_err(|err| anyhow!("unable to flush display: {err:?}"))?;
+        self.canvas.mark_flushed();
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Size;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics_simulator::{
+    BinaryColorTheme, OutputSettings, OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent,
+    Window,
+};
+
+use super::{Canvas, DisplayBackend, HEIGHT, WIDTH};
+
+/// Window on the host emulating the OLED pixel by pixel.
+///
+/// If a screenshot directory is given, every flushed frame is also saved there as a numbered PNG
+/// so rendering changes can be compared against previous runs.
+pub struct Simulator {
+    display: SimulatorDisplay<BinaryColor>,
+    settings: OutputSettings,
+    window: Window,
+    canvas: Canvas,
+    screenshots: Option<PathBuf>,
+    frame: usize,
+}
+
+impl Simulator {
+    pub fn new(scale: u32, screenshots: Option<PathBuf>) -> Self {
+        let settings = OutputSettingsBuilder::new()
+            .theme(BinaryColorTheme::OledWhite)
+            .scale(scale)
+            .build();
+
+        Self {
+            display: SimulatorDisplay::new(Size::new(WIDTH, HEIGHT)),
+            window: Window::new("MEATER emulated display", &settings),
+            settings,
+            canvas: Canvas::default(),
+            screenshots,
+            frame: 0,
+        }
+    }
+}
+
+impl DisplayBackend for Simulator {
+    fn init(&mut self) -> anyhow::Result<()> {
+        if let Some(path) = &self.screenshots {
+            std::fs::create_dir_all(path)
+                .with_context(|| format!("unable to create {}", path.display()))?;
+        }
+
+        self.flush()
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        self.display.draw_iter(self.canvas.changes())?;
+        self.canvas.mark_flushed();
+        self.window.update(&self.display);
+
+        if self
+            .window
+            .events()
+            .any(|event| matches!(event, SimulatorEvent::Quit))
+        {
+            return Err(anyhow!("simulator window closed"));
+        }
+
+        if let Some(path) = &self.screenshots {
+            let path = path.join(format!("frame-{:04}.png", self.frame));
+            self.display
+                .to_rgb_output_image(&self.settings)
+                .save_png(&path)
+                .with_context(|| format!("unable to save {}", path.display()))?;
+        }
+
+        self.frame += 1;
+        Ok(())
+    }
+}
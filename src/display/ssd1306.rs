@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Context};
+use embedded_graphics::draw_target::DrawTarget;
+use ssd1306::mode::BufferedGraphicsMode;
+use ssd1306::prelude::*;
+use ssd1306::I2CDisplayInterface;
+
+use super::{Canvas, DisplayBackend};
+
+type Driver = ssd1306::Ssd1306<
+    I2CInterface<rppal::i2c::I2c>,
+    DisplaySize128x64,
+    BufferedGraphicsMode<DisplaySize128x64>,
+>;
+
+/// SSD1306 OLED connected via I2C.
+pub struct Ssd1306 {
+    display: Driver,
+    canvas: Canvas,
+}
+
+impl Ssd1306 {
+    pub fn new(bus: u8) -> anyhow::Result<Self> {
+        let interface = rppal::i2c::I2c::with_bus(bus).context("unable to create I2c")?;
+
+        let display = ssd1306::Ssd1306::new(
+            I2CDisplayInterface::new(interface),
+            DisplaySize128x64,
+            DisplayRotation::Rotate0,
+        )
+        .into_buffered_graphics_mode();
+
+        Ok(Self {
+            display,
+            canvas: Canvas::default(),
+        })
+    }
+}
+
+impl DisplayBackend for Ssd1306 {
+    fn init(&mut self) -> anyhow::Result<()> {
+        self.display
+            .init()
+            .map_err(|err| anyhow!("unable to initialize display: {err:?}"))?;
+        self.canvas.invalidate();
+        self.flush()
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    fn set_power(&mut self, on: bool) -> anyhow::Result<()> {
+        self.display
+            .set_display_on(on)
+            .map_err(|err| anyhow!("unable to switch display: {err:?}"))
+    }
+
+    fn set_contrast(&mut self, contrast: u8) -> anyhow::Result<()> {
+        self.display
+            .set_brightness(Brightness::custom(0x2, contrast))
+            .map_err(|err| anyhow!("unable to set contrast: {err:?}"))
+    }
+
+    /// Transfer the pixels that changed, the driver only sends the area they span.
+    fn flush(&mut self) -> anyhow::Result<()> {
+        if !self.canvas.is_dirty() {
+            return Ok(());
+        }
+
+        self.display
+            .draw_iter(self.canvas.changes())
+            .map_err(|err| anyhow!("unable to draw: {err:?}"))?;
+        self.display
+            .flush()
+            .map_err(|err| anyhow!("unable to flush display: {err:?}"))?;
+        self.canvas.mark_flushed();
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::anyhow;
+use display_interface_spi::SPIInterfaceNoCS;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+use embedded_graphics::primitives::{PointsIter, Rectangle};
+use mipidsi::dcs::{Dcs, EnterSleepMode, SetDisplayOff};
+use mipidsi::models::ST7789;
+use mipidsi::options::ColorInversion;
+use rppal::gpio::OutputPin;
+use rppal::hal::Delay;
+use rppal::spi::Spi;
+
+use super::{Canvas, DisplayBackend, Heat, HEIGHT, WIDTH};
+
+/// Clock of the SPI bus in Hz, a full frame takes about 30 ms.
+pub const SPI_CLOCK: u32 = 32_000_000;
+
+/// Edge length of the square display in pixels.
+const SIZE: u32 = 240;
+
+/// Area the canvas is scaled into, keeping its aspect ratio in the vertical center.
+const AREA: Rectangle = Rectangle::new(
+    Point::new(0, ((SIZE - SIZE * HEIGHT / WIDTH) / 2) as i32),
+    Size::new(SIZE, SIZE * HEIGHT / WIDTH),
+);
+
+/// Orange between green and red.
+const ORANGE: Rgb565 = Rgb565::new(31, 40, 0);
+
+type Interface = SPIInterfaceNoCS<Spi, OutputPin>;
+
+type Display = mipidsi::Display<Interface, ST7789, OutputPin>;
+
+/// 240x240 ST7789 color TFT connected via SPI.
+///
+/// The monochrome canvas is scaled up to the width of the display. Lit pixels are drawn white,
+/// temperatures marked with [`DisplayBackend::highlight`] in blue, green, orange or red the closer
+/// they get to their target.
+pub struct St7789 {
+    /// Gone while asleep or if initializing it again failed halfway.
+    display: Option<Display>,
+    /// Interface and reset pin of the display while asleep, the driver is set up again on waking.
+    asleep: Option<(Interface, Option<OutputPin>)>,
+    canvas: Canvas,
+    /// Highlights as of the last transfer.
+    flushed: Vec<(Rectangle, Heat)>,
+}
+
+/// Initialize the display behind `interface`, resetting it first with `rst` if wired up.
+fn init(interface: Interface, rst: Option<OutputPin>) -> anyhow::Result<Display> {
+    mipidsi::Builder::st7789(interface)
+        .with_display_size(SIZE as u16, SIZE as u16)
+        .with_invert_colors(ColorInversion::Inverted)
+        .init(&mut Delay::new(), rst)
+        .map_err(|err| anyhow!("unable to initialize display: {err:?}"))
+}
+
+/// Color of a lit pixel within an area marked with `heat`.
+fn color(heat: Heat) -> Rgb565 {
+    match heat {
+        Heat::Cold => Rgb565::BLUE,
+        Heat::Warming => Rgb565::GREEN,
+        Heat::Close => ORANGE,
+        Heat::Done => Rgb565::RED,
+    }
+}
+
+impl St7789 {
+    /// Connect via `spi` with the data/command pin `dc` and the optional reset pin `rst`.
+    pub fn new(spi: Spi, dc: OutputPin, rst: Option<OutputPin>) -> anyhow::Result<Self> {
+        let display = init(SPIInterfaceNoCS::new(spi, dc), rst)?;
+
+        Ok(Self {
+            display: Some(display),
+            asleep: None,
+            canvas: Canvas::default(),
+            flushed: Vec::new(),
+        })
+    }
+
+    /// Take the interface and reset pin from the display, awake or asleep.
+    fn release(&mut self) -> anyhow::Result<(Interface, Option<OutputPin>)> {
+        if let Some(display) = self.display.take() {
+            let (interface, _, rst) = display.release();
+            return Ok((interface, rst));
+        }
+
+        self.asleep
+            .take()
+            .ok_or_else(|| anyhow!("display was lost while initializing it"))
+    }
+
+    fn display(&mut self) -> anyhow::Result<&mut Display> {
+        self.display
+            .as_mut()
+            .ok_or_else(|| anyhow!("display was lost while initializing it"))
+    }
+
+    /// Color of the canvas pixel at `point`.
+    fn pixel(&self, point: Point) -> Rgb565 {
+        if !self.canvas.is_on(point) {
+            return Rgb565::BLACK;
+        }
+
+        self.canvas
+            .highlights()
+            .iter()
+            .rev()
+            .find(|(area, _)| area.contains(point))
+            .map_or(Rgb565::WHITE, |(_, heat)| color(*heat))
+    }
+}
+
+impl DisplayBackend for St7789 {
+    fn init(&mut self) -> anyhow::Result<()> {
+        let (interface, rst) = self.release()?;
+        let mut display = init(interface, rst)?;
+
+        display
+            .clear(Rgb565::BLACK)
+            .map_err(|err| anyhow!("unable to clear display: {err:?}"))?;
+
+        self.display = Some(display);
+        self.canvas.invalidate();
+        self.flush()
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    /// Put the display to sleep, the driver has no command to wake it up again, so it is
+    /// initialized again instead, which leaves sleep mode and turns the display on.
+    fn set_power(&mut self, on: bool) -> anyhow::Result<()> {
+        if on {
+            return match self.asleep {
+                Some(_) => self.init(),
+                None => Ok(()),
+            };
+        }
+
+        if self.display.is_none() {
+            return Ok(());
+        }
+
+        let (interface, rst) = self.release()?;
+        let mut dcs = Dcs::write_only(interface);
+
+        let result = dcs
+            .write_command(SetDisplayOff)
+            .and_then(|()| dcs.write_command(EnterSleepMode));
+
+        self.asleep = Some((dcs.release(), rst));
+        result.map_err(|err| anyhow!("unable to switch display off: {err:?}"))
+    }
+
+    /// Transfer the whole frame scaled up unless it is unchanged.
+    fn flush(&mut self) -> anyhow::Result<()> {
+        if !self.canvas.is_dirty() && self.canvas.highlights() == self.flushed {
+            return Ok(());
+        }
+
+        let colors: Vec<Rgb565> = AREA
+            .points()
+            .map(|point| {
+                let point = point - AREA.top_left;
+                let x = point.x * WIDTH as i32 / AREA.size.width as i32;
+                let y = point.y * HEIGHT as i32 / AREA.size.height as i32;
+                self.pixel(Point::new(x, y))
+            })
+            .collect();
+
+        self.display()?
+            .fill_contiguous(&AREA, colors)
+            .map_err(|err| anyhow!("unable to flush display: {err:?}"))?;
+
+        self.flushed = self.canvas.highlights().to_vec();
+        self.canvas.mark_flushed();
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::{Stdout, Write};
+
+use crossterm::{cursor, terminal, QueueableCommand};
+use embedded_graphics::geometry::Point;
+
+use super::{Canvas, DisplayBackend, HEIGHT, WIDTH};
+
+/// Terminal showing the canvas with block characters, two pixels per character cell.
+///
+/// Log messages are written to stderr, redirect them to keep the output intact.
+pub struct Terminal {
+    stdout: Stdout,
+    canvas: Canvas,
+}
+
+impl Terminal {
+    pub fn new() -> Self {
+        Self {
+            stdout: std::io::stdout(),
+            canvas: Canvas::default(),
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.stdout.queue(cursor::Show);
+        let _ = self.stdout.queue(terminal::LeaveAlternateScreen);
+        let _ = self.stdout.flush();
+    }
+}
+
+impl DisplayBackend for Terminal {
+    fn init(&mut self) -> anyhow::Result<()> {
+        self.stdout.queue(terminal::EnterAlternateScreen)?;
+        self.stdout.queue(cursor::Hide)?;
+        self.flush()
+    }
+
+    fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        self.stdout.queue(cursor::MoveTo(0, 0))?;
+
+        let border = "─".repeat(WIDTH as usize);
+        write!(self.stdout, "┌{border}┐\r\n")?;
+
+        for y in (0..HEIGHT as i32).step_by(2) {
+            let line: String = (0..WIDTH as i32)
+                .map(|x| {
+                    let top = self.canvas.is_on(Point::new(x, y));
+                    let bottom = self.canvas.is_on(Point::new(x, y + 1));
+
+                    match (top, bottom) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    }
+                })
+                .collect();
+
+            write!(self.stdout, "│{line}│\r\n")?;
+        }
+
+        write!(self.stdout, "└{border}┘\r\n")?;
+        self.stdout.flush()?;
+        Ok(())
+    }
+}
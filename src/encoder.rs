@@ -0,0 +1,60 @@
+//! KY-040 style rotary encoder.
+//!
+//! The clock pin triggers an interrupt on each detent, the level of the data pin at that moment
+//! tells the direction.
+
+use tokio::sync::mpsc;
+
+#[cfg(feature = "device")]
+use anyhow::Context;
+
+use crate::input::Event;
+
+/// Minimum time between two detents, shorter pulses are contact bounce.
+#[cfg(feature = "device")]
+const DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(5);
+
+/// Rotary encoder sending a [`Event::Rotate`] for each detent while it is alive.
+pub struct Encoder {
+    #[cfg(feature = "device")]
+    _clk: rppal::gpio::InputPin,
+}
+
+impl Encoder {
+    #[cfg(feature = "device")]
+    pub fn new(clk: u8, dt: u8, sender: mpsc::Sender<Event>) -> anyhow::Result<Self> {
+        let gpio = rppal::gpio::Gpio::new().context("unable to access GPIO")?;
+
+        let get = |pin: u8| {
+            gpio.get(pin)
+                .map(|pin| pin.into_input_pullup())
+                .with_context(|| format!("unable to get GPIO pin {pin}"))
+        };
+
+        let mut clk = get(clk)?;
+        let dt = get(dt)?;
+        let mut last = std::time::Instant::now();
+
+        clk.set_async_interrupt(rppal::gpio::Trigger::FallingEdge, move |_| {
+            if last.elapsed() < DEBOUNCE {
+                return;
+            }
+
+            last = std::time::Instant::now();
+            let steps = if dt.is_high() { 1 } else { -1 };
+
+            if sender.try_send(Event::Rotate(steps)).is_err() {
+                tracing::warn!("input is lagging behind, dropping rotation");
+            }
+        })
+        .context("unable to watch encoder")?;
+
+        Ok(Self { _clk: clk })
+    }
+
+    #[cfg(not(feature = "device"))]
+    pub fn new(clk: u8, dt: u8, _sender: mpsc::Sender<Event>) -> anyhow::Result<Self> {
+        tracing::info!(clk, dt, "no GPIO available, encoder is ignored");
+        Ok(Self {})
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use tokio::sync::mpsc::error::SendError;
+use uuid::Uuid;
+
+use crate::client::{Adapter, Event};
+
+/// Failure of the MEATER [`Client`](crate::Client).
+#[derive(Debug, thiserror::Error)]
+pub enum MeaterError {
+    /// No Bluetooth adapter matches the configured one.
+    #[error("bluetooth adapter {0} not found")]
+    AdapterNotFound(Adapter),
+    /// The connection to a probe could not be set up.
+    #[error("unable to connect: {0}")]
+    ConnectFailed(#[source] btleplug::Error),
+    /// Notifications of a probe could not be subscribed to.
+    #[error("unable to subscribe: {0}")]
+    SubscribeFailed(#[source] btleplug::Error),
+    /// The notification listener of a probe failed this many times in a row.
+    #[error("listener failed {0} times in a row")]
+    ListenerFailed(u32),
+    /// A connected probe sent no temperature for this long.
+    #[error("no temperature for {0:?}")]
+    Stale(std::time::Duration),
+    /// The notification `value` of characteristic `uuid` makes no sense.
+    #[error("unable to decode {value:?} of {uuid}")]
+    Decode { uuid: Uuid, value: Vec<u8> },
+    /// Any other failure of the Bluetooth stack.
+    #[error("bluetooth failed: {0}")]
+    Bluetooth(#[from] btleplug::Error),
+    /// The adapter stopped reporting events, e.g. because bluetoothd went away.
+    #[error("bluetooth event stream ended")]
+    EventsEnded,
+    /// The connection to the ESPHome Bluetooth proxy failed or it sent something unexpected.
+    #[error("bluetooth proxy failed: {0}")]
+    Proxy(#[source] std::io::Error),
+    /// The recording could not be created.
+    #[error("unable to create recording {}: {source}", path.display())]
+    Record {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The receiver of the events was dropped.
+    #[error("event receiver closed")]
+    Closed(#[from] SendError<Event>),
+}
+
+/// Code of failures of the thermometers other than the MEATER.
+pub(crate) const OTHER_FAILED: u16 = 80;
+
+impl MeaterError {
+    /// Short number telling the kind of error apart on the display.
+    pub fn code(&self) -> u16 {
+        match self {
+            Self::AdapterNotFound(_) => 10,
+            Self::Bluetooth(_) => 11,
+            Self::EventsEnded => 12,
+            Self::Proxy(_) => 13,
+            Self::ConnectFailed(_) => 20,
+            Self::SubscribeFailed(_) => 21,
+            Self::ListenerFailed(_) => 30,
+            Self::Stale(_) => 31,
+            Self::Decode { .. } => 40,
+            Self::Record { .. } => 50,
+            Self::Closed(_) => 60,
+        }
+    }
+
+    /// Log the error and return the event announcing it, see [`report`].
+    pub(crate) fn report(&self, probe: Option<usize>) -> Event {
+        report(probe, self.code(), self)
+    }
+}
+
+/// Log the failure `err` with `code` and a random id and return the [`Event::Error`] announcing
+/// both, so the error shown on the display can be found in the logs.
+pub(crate) fn report(probe: Option<usize>, code: u16, err: impl std::fmt::Display) -> Event {
+    let id = rand::random();
+    tracing::error!(probe, code, id = format_args!("{id:04x}"), "{err}");
+    Event::Error { probe, code, id }
+}
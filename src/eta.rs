@@ -0,0 +1,167 @@
+//! Estimation of the time until a probe reaches its target temperature.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Time span of tip temperatures the rate of rise is estimated from.
+const WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Minimum time span of samples before an estimate is given.
+const MIN_SPAN: Duration = Duration::from_secs(2 * 60);
+
+/// Time span of tip temperatures the current rate of change is computed from, short enough to
+/// follow opened vents within minutes.
+const RATE_WINDOW: Duration = Duration::from_secs(3 * 60);
+
+/// Minimum time span of samples before the current rate of change is given.
+const MIN_RATE_SPAN: Duration = Duration::from_secs(60);
+
+/// Slowest rate of rise in °C per second that still yields an estimate.
+const MIN_RATE: f64 = 0.1 / 60.0;
+
+/// Estimates the rate of rise of the tip temperature by linear regression over a sliding window.
+#[derive(Default)]
+pub struct Estimator {
+    samples: VecDeque<(Instant, f32)>,
+}
+
+impl Estimator {
+    /// Add the tip temperature `tip` measured at `at`.
+    pub fn push(&mut self, at: Instant, tip: f32) {
+        self.samples.push_back((at, tip));
+
+        while let Some((first, _)) = self.samples.front() {
+            if at.duration_since(*first) <= WINDOW {
+                break;
+            }
+
+            self.samples.pop_front();
+        }
+    }
+
+    /// Rate of rise in °C per second over the last `window` if its samples span at least
+    /// `min_span`.
+    fn rate(&self, window: Duration, min_span: Duration) -> Option<f64> {
+        let (last, _) = *self.samples.back()?;
+        let samples: Vec<_> = self
+            .samples
+            .iter()
+            .filter(|(at, _)| last.duration_since(*at) <= window)
+            .collect();
+
+        let (first, _) = *samples[0];
+
+        if last.duration_since(first) < min_span {
+            return None;
+        }
+
+        let n = samples.len() as f64;
+        let points = samples
+            .iter()
+            .map(|(at, tip)| (at.duration_since(first).as_secs_f64(), f64::from(*tip)));
+
+        let (sum_t, sum_y, sum_tt, sum_ty) = points.fold(
+            (0.0, 0.0, 0.0, 0.0),
+            |(sum_t, sum_y, sum_tt, sum_ty), (t, y)| {
+                (sum_t + t, sum_y + y, sum_tt + t * t, sum_ty + t * y)
+            },
+        );
+
+        let denominator = n * sum_tt - sum_t * sum_t;
+
+        (denominator > 0.0).then(|| (n * sum_ty - sum_t * sum_y) / denominator)
+    }
+
+    /// Time until the tip reaches `target` if it is below and rising steadily enough.
+    pub fn eta(&self, target: f32) -> Option<Duration> {
+        let (_, tip) = *self.samples.back()?;
+        let rate = self
+            .rate(WINDOW, MIN_SPAN)
+            .filter(|rate| *rate >= MIN_RATE)?;
+
+        if tip >= target {
+            return None;
+        }
+
+        Duration::try_from_secs_f64(f64::from(target - tip) / rate).ok()
+    }
+
+    /// Current rate of change in °C per minute, following changes faster than the estimate.
+    pub fn per_minute(&self) -> Option<f32> {
+        self.rate(RATE_WINDOW, MIN_RATE_SPAN)
+            .map(|rate| (rate * 60.0) as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Estimator with a sample every ten seconds for `minutes` after `begin`, the tip changing by
+    /// `rate` °C per minute from `start`.
+    fn estimator(begin: Instant, minutes: u64, start: f32, rate: f32) -> Estimator {
+        let mut estimator = Estimator::default();
+
+        for step in 0..=minutes * 6 {
+            let tip = start + rate * step as f32 / 6.0;
+            estimator.push(begin + Duration::from_secs(step * 10), tip);
+        }
+
+        estimator
+    }
+
+    #[test]
+    fn estimate_steady_rise() {
+        let estimator = estimator(Instant::now(), 5, 20.0, 1.0);
+        let eta = estimator.eta(35.0).unwrap();
+        assert!((eta.as_secs_f32() - 600.0).abs() < 1.0, "{eta:?}");
+        assert!((estimator.per_minute().unwrap() - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn no_estimate_without_enough_samples() {
+        assert_eq!(Estimator::default().eta(60.0), None);
+        assert_eq!(Estimator::default().per_minute(), None);
+
+        // Rising quickly, but not for long enough.
+        let estimator = estimator(Instant::now(), 1, 20.0, 5.0);
+        assert_eq!(estimator.eta(60.0), None);
+        assert!(estimator.per_minute().is_some());
+    }
+
+    #[test]
+    fn no_estimate_on_flat_slope() {
+        let estimator = estimator(Instant::now(), 5, 50.0, 0.0);
+        assert_eq!(estimator.eta(60.0), None);
+        assert_eq!(estimator.per_minute(), Some(0.0));
+    }
+
+    #[test]
+    fn no_estimate_while_falling() {
+        let estimator = estimator(Instant::now(), 5, 50.0, -0.5);
+        assert_eq!(estimator.eta(60.0), None);
+        assert!((estimator.per_minute().unwrap() + 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn no_estimate_once_target_reached() {
+        let estimator = estimator(Instant::now(), 5, 50.0, 1.0);
+        assert_eq!(estimator.eta(55.0), None);
+        assert_eq!(estimator.eta(50.0), None);
+        assert!(estimator.eta(60.0).is_some());
+    }
+
+    #[test]
+    fn follow_recent_changes_faster_than_estimate() {
+        let begin = Instant::now();
+        let mut estimator = estimator(begin, 5, 20.0, 2.0);
+
+        // Rising at 2 °C per minute for five minutes, then flat after opening the vents.
+        for step in 31..=60 {
+            estimator.push(begin + Duration::from_secs(step * 10), 30.0);
+        }
+
+        assert_eq!(estimator.per_minute(), Some(0.0));
+        assert!(estimator.eta(60.0).is_some());
+    }
+}
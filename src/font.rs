@@ -0,0 +1,50 @@
+//! Fonts text is set in on the display and measuring that text.
+//!
+//! Glyph sizes are taken from the descriptor of each font, so layouts can right-align or center
+//! text without knowing them.
+
+use embedded_graphics::mono_font::{MonoFont, MonoTextStyle};
+use embedded_graphics::pixelcolor::BinaryColor;
+use profont::{PROFONT_12_POINT, PROFONT_24_POINT, PROFONT_9_POINT};
+
+use crate::display::WIDTH;
+
+/// Monospaced font of the display.
+#[derive(Clone, Copy)]
+pub struct Font(&'static MonoFont<'static>);
+
+/// Status text like the preset or the number of connection attempts.
+pub const SMALL: Font = Font(&PROFONT_9_POINT);
+
+/// Labels and secondary values like the ambient temperature.
+pub const MEDIUM: Font = Font(&PROFONT_12_POINT);
+
+/// Main temperature of a page.
+pub const LARGE: Font = Font(&PROFONT_24_POINT);
+
+impl Font {
+    /// Style drawing lit pixels in this font.
+    pub fn style(self) -> MonoTextStyle<'static, BinaryColor> {
+        MonoTextStyle::new(self.0, BinaryColor::On)
+    }
+
+    /// Horizontal distance in pixels from one glyph to the next.
+    pub fn advance(self) -> i32 {
+        (self.0.character_size.width + self.0.character_spacing) as i32
+    }
+
+    /// Width of `text` in pixels.
+    pub fn width(self, text: &str) -> i32 {
+        self.advance() * text.chars().count() as i32
+    }
+
+    /// Left edge of `text` centered horizontally on the display.
+    pub fn centered(self, text: &str) -> i32 {
+        (WIDTH as i32 - self.width(text)) / 2
+    }
+
+    /// Left edge of `text` ending at `right`.
+    pub fn right_aligned(self, text: &str, right: i32) -> i32 {
+        right - self.width(text)
+    }
+}
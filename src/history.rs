@@ -0,0 +1,205 @@
+//! Persistent cook history in a SQLite database.
+//!
+//! Sessions are started when a probe connects and ended when the last probe disconnects. If a
+//! probe connects again shortly after the previous session ended, for example after a restart of
+//! this program or a brief connection loss, the previous session is resumed instead.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
+use tokio::sync::mpsc;
+
+/// Seconds within which a session is resumed rather than a new one started.
+const RESUME_WINDOW: i64 = 10 * 60;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS samples (
+    session_id INTEGER NOT NULL REFERENCES sessions(id),
+    timestamp INTEGER NOT NULL,
+    probe INTEGER NOT NULL,
+    tip REAL NOT NULL,
+    ambient REAL NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS transitions (
+    session_id INTEGER REFERENCES sessions(id),
+    timestamp INTEGER NOT NULL,
+    state TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS devices (
+    session_id INTEGER REFERENCES sessions(id),
+    timestamp INTEGER NOT NULL,
+    probe INTEGER NOT NULL,
+    firmware TEXT,
+    serial TEXT
+);
+
+CREATE INDEX IF NOT EXISTS samples_session ON samples(session_id, timestamp);
+";
+
+/// Current time in seconds since the UNIX epoch.
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Database connection and the currently running session.
+struct History {
+    connection: Connection,
+    session: Option<i64>,
+}
+
+impl History {
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        let connection =
+            Connection::open(path).with_context(|| format!("unable to open {}", path.display()))?;
+
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            connection,
+            session: None,
+        })
+    }
+
+    /// Resume the most recent session if it was active recently or start a new one.
+    fn start_session(&mut self) -> anyhow::Result<i64> {
+        let now = now();
+
+        let latest: Option<(i64, Option<i64>, Option<i64>)> = self
+            .connection
+            .query_row(
+                "SELECT id, ended_at, (SELECT MAX(timestamp) FROM samples WHERE session_id = id)
+                 FROM sessions ORDER BY id DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+
+        if let Some((id, ended_at, last_sample)) = latest {
+            let last_activity = ended_at.max(last_sample).unwrap_or_default();
+
+            if now - last_activity < RESUME_WINDOW {
+                tracing::info!(session = id, "resuming cook session");
+                self.connection.execute(
+                    "UPDATE sessions SET ended_at = NULL WHERE id = ?1",
+                    params![id],
+                )?;
+                return Ok(id);
+            }
+
+            if ended_at.is_none() {
+                // The program was stopped without the session being ended properly.
+                self.connection.execute(
+                    "UPDATE sessions SET ended_at = ?1 WHERE id = ?2",
+                    params![last_activity, id],
+                )?;
+            }
+        }
+
+        self.connection.execute(
+            "INSERT INTO sessions (started_at) VALUES (?1)",
+            params![now],
+        )?;
+
+        let id = self.connection.last_insert_rowid();
+        tracing::info!(session = id, "starting cook session");
+        Ok(id)
+    }
+
+    fn end_session(&mut self) -> anyhow::Result<()> {
+        if let Some(id) = self.session.take() {
+            tracing::info!(session = id, "ending cook session");
+            self.connection.execute(
+                "UPDATE sessions SET ended_at = ?1 WHERE id = ?2",
+                params![now(), id],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn handle(&mut self, event: meater::Event) -> anyhow::Result<()> {
+        match event {
+            meater::Event::State(state) => {
+                if state == meater::State::Connected && self.session.is_none() {
+                    self.session = Some(self.start_session()?);
+                }
+
+                self.connection.execute(
+                    "INSERT INTO transitions (session_id, timestamp, state) VALUES (?1, ?2, ?3)",
+                    params![self.session, now(), state.to_string()],
+                )?;
+
+                if state == meater::State::Disconnected {
+                    self.end_session()?;
+                }
+            }
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => {
+                if let Some(session) = self.session {
+                    self.connection.execute(
+                        "INSERT INTO samples (session_id, timestamp, probe, tip, ambient)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![session, now(), probe, tip, ambient],
+                    )?;
+                }
+            }
+            meater::Event::Battery { .. }
+            | meater::Event::BatteryState { .. }
+            | meater::Event::Rssi { .. }
+            | meater::Event::Sensors { .. }
+            | meater::Event::ProbeRemoved { .. }
+            | meater::Event::ProbeInserted { .. }
+            | meater::Event::DecodeError { .. }
+            | meater::Event::Error { .. } => {}
+            meater::Event::SessionEnded => {
+                // The probes stay connected, so their samples go to a new session from now on.
+                if self.session.is_some() {
+                    self.end_session()?;
+                    self.session = Some(self.start_session()?);
+                }
+            }
+            meater::Event::DeviceInfo {
+                probe,
+                firmware,
+                serial,
+            } => {
+                self.connection.execute(
+                    "INSERT INTO devices (session_id, timestamp, probe, firmware, serial)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![self.session, now(), probe, firmware, serial],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Store all events received on `receiver` in the database at `path`.
+///
+/// This blocks on database access and must run on a blocking thread.
+pub fn run(path: &Path, mut receiver: mpsc::Receiver<meater::Event>) -> anyhow::Result<()> {
+    let mut history = History::open(path)?;
+
+    while let Some(event) = receiver.blocking_recv() {
+        history.handle(event)?;
+    }
+
+    history.end_session()
+}
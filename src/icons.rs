@@ -0,0 +1,157 @@
+//! Icons shown on the display.
+//!
+//! The icons are embedded into the binary. Each one can be replaced by a BMP of the same name in
+//! a directory of assets to theme the display without recompiling.
+
+use std::path::Path;
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use tinybmp::Bmp;
+
+const NOT_FOUND: &[u8] = include_bytes!("assets/not-found.bmp");
+const CONNECTING: &[u8] = include_bytes!("assets/connecting.bmp");
+const CONNECTED: &[u8] = include_bytes!("assets/connected.bmp");
+const SPINNER: [&[u8]; 4] = [
+    include_bytes!("assets/spinner-0.bmp"),
+    include_bytes!("assets/spinner-1.bmp"),
+    include_bytes!("assets/spinner-2.bmp"),
+    include_bytes!("assets/spinner-3.bmp"),
+];
+const REMOVED: &[u8] = include_bytes!("assets/removed.bmp");
+const STALE: &[u8] = include_bytes!("assets/stale.bmp");
+const ERROR: &[u8] = include_bytes!("assets/error.bmp");
+const BATTERY_25: &[u8] = include_bytes!("assets/battery-25.bmp");
+const BATTERY_50: &[u8] = include_bytes!("assets/battery-50.bmp");
+const BATTERY_75: &[u8] = include_bytes!("assets/battery-75.bmp");
+const BATTERY_100: &[u8] = include_bytes!("assets/battery-100.bmp");
+const BATTERY_LOW: &[u8] = include_bytes!("assets/battery-low.bmp");
+const BATTERY_CHARGING: &[u8] = include_bytes!("assets/battery-charging.bmp");
+const SIGNAL: [&[u8]; 5] = [
+    include_bytes!("assets/signal-0.bmp"),
+    include_bytes!("assets/signal-1.bmp"),
+    include_bytes!("assets/signal-2.bmp"),
+    include_bytes!("assets/signal-3.bmp"),
+    include_bytes!("assets/signal-4.bmp"),
+];
+
+/// Parsed icon bitmaps.
+pub struct Icons {
+    pub not_found: Bmp<'static, BinaryColor>,
+    pub connecting: Bmp<'static, BinaryColor>,
+    pub connected: Bmp<'static, BinaryColor>,
+    /// Frames of the spinner animated while connecting.
+    pub spinner: [Bmp<'static, BinaryColor>; 4],
+    /// Probe pulled out of the meat.
+    pub removed: Bmp<'static, BinaryColor>,
+    /// Probe connected but no longer sending temperatures.
+    pub stale: Bmp<'static, BinaryColor>,
+    /// Shown with the code of the last error until a probe works again.
+    pub error: Bmp<'static, BinaryColor>,
+    pub battery_25: Bmp<'static, BinaryColor>,
+    pub battery_50: Bmp<'static, BinaryColor>,
+    pub battery_75: Bmp<'static, BinaryColor>,
+    pub battery_100: Bmp<'static, BinaryColor>,
+    pub battery_low: Bmp<'static, BinaryColor>,
+    /// Probe back in its charger.
+    pub battery_charging: Bmp<'static, BinaryColor>,
+    /// Signal strength with zero to four bars.
+    pub signal: [Bmp<'static, BinaryColor>; 5],
+}
+
+impl Icons {
+    /// Parse the embedded icons, replaced by the ones of the same name in `directory` if given.
+    pub fn new(directory: Option<&Path>) -> anyhow::Result<Self> {
+        let loader = Loader { directory };
+
+        Ok(Self {
+            not_found: loader.icon("not-found", NOT_FOUND)?,
+            connecting: loader.icon("connecting", CONNECTING)?,
+            connected: loader.icon("connected", CONNECTED)?,
+            spinner: [
+                loader.icon("spinner-0", SPINNER[0])?,
+                loader.icon("spinner-1", SPINNER[1])?,
+                loader.icon("spinner-2", SPINNER[2])?,
+                loader.icon("spinner-3", SPINNER[3])?,
+            ],
+            removed: loader.icon("removed", REMOVED)?,
+            stale: loader.icon("stale", STALE)?,
+            error: loader.icon("error", ERROR)?,
+            battery_25: loader.icon("battery-25", BATTERY_25)?,
+            battery_50: loader.icon("battery-50", BATTERY_50)?,
+            battery_75: loader.icon("battery-75", BATTERY_75)?,
+            battery_100: loader.icon("battery-100", BATTERY_100)?,
+            battery_low: loader.icon("battery-low", BATTERY_LOW)?,
+            battery_charging: loader.icon("battery-charging", BATTERY_CHARGING)?,
+            signal: [
+                loader.icon("signal-0", SIGNAL[0])?,
+                loader.icon("signal-1", SIGNAL[1])?,
+                loader.icon("signal-2", SIGNAL[2])?,
+                loader.icon("signal-3", SIGNAL[3])?,
+                loader.icon("signal-4", SIGNAL[4])?,
+            ],
+        })
+    }
+
+    /// Return the battery icon matching `percent`.
+    pub fn battery(&self, percent: u16) -> &Bmp<'static, BinaryColor> {
+        match percent {
+            ..=25 => &self.battery_25,
+            26..=50 => &self.battery_50,
+            51..=75 => &self.battery_75,
+            _ => &self.battery_100,
+        }
+    }
+
+    /// Return the signal strength icon matching `rssi` in dBm.
+    pub fn signal(&self, rssi: i16) -> &Bmp<'static, BinaryColor> {
+        let bars = match rssi {
+            -60.. => 4,
+            -70..=-61 => 3,
+            -80..=-71 => 2,
+            -90..=-81 => 1,
+            _ => 0,
+        };
+
+        &self.signal[bars]
+    }
+}
+
+/// Picks icons from a directory of assets or the embedded ones.
+struct Loader<'a> {
+    directory: Option<&'a Path>,
+}
+
+impl Loader<'_> {
+    /// Return the icon `name` from the directory if it has a usable one, `default` otherwise.
+    fn icon(
+        &self,
+        name: &str,
+        default: &'static [u8],
+    ) -> anyhow::Result<Bmp<'static, BinaryColor>> {
+        if let Some(path) = self
+            .directory
+            .map(|directory| directory.join(format!("{name}.bmp")))
+        {
+            match std::fs::read(&path) {
+                Ok(data) => {
+                    // Icons live as long as the program, so the few bytes are never freed.
+                    match parse(Box::leak(data.into_boxed_slice())) {
+                        Ok(icon) => {
+                            tracing::debug!("using icon {}", path.display());
+                            return Ok(icon);
+                        }
+                        Err(err) => tracing::warn!("ignoring {}: {err}", path.display()),
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => tracing::warn!("ignoring {}: {err}", path.display()),
+            }
+        }
+
+        parse(default)
+    }
+}
+
+fn parse(data: &'static [u8]) -> anyhow::Result<Bmp<'static, BinaryColor>> {
+    Bmp::from_slice(data).map_err(|err| anyhow::anyhow!("unable to parse icon: {err:?}"))
+}
@@ -0,0 +1,223 @@
+//! Export of samples to InfluxDB via the v2 HTTP API.
+//!
+//! Events are turned into points in line protocol, e.g. `meater,probe=1 tip=54.2,ambient=120.3
+//! 1700000000`, with probes numbered from one like on the display. Points are collected and
+//! written in batches to keep the overhead of long cooks low. Points of failed writes are kept
+//! and sent along with the next batch, up to a limit.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+/// Time after which a write is given up.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Batches of points kept while the server cannot be reached before the oldest are dropped.
+const MAX_PENDING_BATCHES: usize = 10;
+
+/// Connection settings of the InfluxDB sink.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Base URL of the InfluxDB server, e.g. `http://localhost:8086`, exporting is disabled if not
+    /// set.
+    pub url: Option<String>,
+    /// Organization the bucket belongs to.
+    pub org: String,
+    /// Bucket the points are written to.
+    pub bucket: String,
+    /// API token with write access to the bucket.
+    pub token: Option<String>,
+    /// Measurement all points are written as.
+    pub measurement: String,
+    /// Points collected before they are written.
+    pub batch_size: usize,
+    /// Maximum seconds points are held back before they are written.
+    pub flush_seconds: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            url: None,
+            org: "meater".to_string(),
+            bucket: "meater".to_string(),
+            token: None,
+            measurement: "meater".to_string(),
+            batch_size: 50,
+            flush_seconds: 10.0,
+        }
+    }
+}
+
+/// Return `value` as quoted string field value.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Return the points in line protocol for `event` at `timestamp` in seconds since the UNIX epoch.
+fn lines(measurement: &str, event: &meater::Event, timestamp: u64) -> Vec<String> {
+    let point = |probe: Option<usize>, fields: String| match probe {
+        Some(probe) => format!("{measurement},probe={} {fields} {timestamp}", probe + 1),
+        None => format!("{measurement} {fields} {timestamp}"),
+    };
+
+    match event {
+        meater::Event::State(state) => {
+            vec![point(None, format!("state={}", quote(&state.to_string())))]
+        }
+        meater::Event::Temperature {
+            probe,
+            tip,
+            ambient,
+        } => vec![point(Some(*probe), format!("tip={tip},ambient={ambient}"))],
+        meater::Event::Battery { probe, percent } => {
+            vec![point(Some(*probe), format!("battery={percent}i"))]
+        }
+        meater::Event::BatteryState { probe, state } => {
+            let field = format!("battery_state={}", quote(&state.to_string()));
+            vec![point(Some(*probe), field)]
+        }
+        meater::Event::Rssi { probe, rssi } => vec![point(Some(*probe), format!("rssi={rssi}i"))],
+        meater::Event::Sensors {
+            probe,
+            temperatures,
+            surface,
+        } => {
+            let fields: Vec<String> = temperatures
+                .iter()
+                .enumerate()
+                .map(|(sensor, temperature)| format!("sensor{}={temperature}", sensor + 1))
+                .chain(surface.map(|surface| format!("surface={surface}")))
+                .collect();
+
+            if fields.is_empty() {
+                Vec::new()
+            } else {
+                vec![point(Some(*probe), fields.join(","))]
+            }
+        }
+        meater::Event::ProbeRemoved { probe } => vec![point(Some(*probe), "removed=true".into())],
+        meater::Event::ProbeInserted { probe } => {
+            vec![point(Some(*probe), "removed=false".into())]
+        }
+        meater::Event::DeviceInfo { .. }
+        | meater::Event::DecodeError { .. }
+        | meater::Event::Error { .. }
+        | meater::Event::SessionEnded => Vec::new(),
+    }
+}
+
+/// Current time in seconds since the UNIX epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Writes batches of points to the server.
+struct Writer {
+    client: reqwest::Client,
+    url: String,
+    options: Options,
+    /// Points not yet written, oldest first.
+    pending: Vec<String>,
+    /// Whether the last write failed, retries then wait for the interval.
+    failing: bool,
+}
+
+impl Writer {
+    /// Write all pending points, keeping them for the next attempt if that fails.
+    async fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        match self.write().await {
+            Ok(()) => {
+                self.pending.clear();
+                self.failing = false;
+            }
+            Err(err) => {
+                self.failing = true;
+                tracing::warn!(
+                    "unable to write {} points to InfluxDB: {err}",
+                    self.pending.len()
+                );
+
+                let limit = self.options.batch_size.max(1) * MAX_PENDING_BATCHES;
+
+                if self.pending.len() > limit {
+                    let dropped = self.pending.len() - limit;
+                    self.pending.drain(..dropped);
+                    tracing::warn!("dropped {dropped} points InfluxDB did not accept");
+                }
+            }
+        }
+    }
+
+    async fn write(&self) -> anyhow::Result<()> {
+        let mut request = self
+            .client
+            .post(format!("{}/api/v2/write", self.url.trim_end_matches('/')))
+            .query(&[
+                ("org", self.options.org.as_str()),
+                ("bucket", self.options.bucket.as_str()),
+                ("precision", "s"),
+            ])
+            .body(self.pending.join("\n"));
+
+        if let Some(token) = &self.options.token {
+            request = request.header("Authorization", format!("Token {token}"));
+        }
+
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Write all events received on `receiver` to the InfluxDB server at `url`.
+pub async fn run(
+    url: String,
+    options: Options,
+    mut receiver: mpsc::Receiver<meater::Event>,
+) -> anyhow::Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+
+    let mut interval =
+        tokio::time::interval(Duration::from_secs_f32(options.flush_seconds.max(1.0)));
+
+    let mut writer = Writer {
+        client,
+        url,
+        options,
+        pending: Vec::new(),
+        failing: false,
+    };
+
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                let measurement = &writer.options.measurement;
+                writer.pending.extend(lines(measurement, &event, now()));
+
+                if !writer.failing && writer.pending.len() >= writer.options.batch_size {
+                    writer.flush().await;
+                }
+            }
+            _ = interval.tick() => writer.flush().await,
+        }
+    }
+
+    // Write what is left once the client stopped.
+    writer.flush().await;
+    Ok(())
+}
@@ -0,0 +1,249 @@
+//! Client for Inkbird IBT-2X and IBT-4XS grill thermometers.
+//!
+//! The thermometer advertises itself as `iBBQ` and, once logged into and asked for real-time data,
+//! notifies the temperatures of all its channels as little-endian values in 0.1 °C. Each channel is
+//! reported as a probe numbered like on the thermometer, starting from zero. There is no ambient
+//! sensor, instead a probe clipped to the grill can be reported as the ambient temperature of all
+//! others.
+
+use std::time::Instant;
+
+use anyhow::anyhow;
+use btleplug::api::{Central, CentralEvent, Manager, Peripheral, ScanFilter, WriteType};
+use btleplug::platform;
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use tokio::sync::mpsc;
+use uuid::{uuid, Uuid};
+
+use crate::client::{Adapter, Backoff, Event, State};
+use crate::error;
+use crate::source::ProbeSource;
+
+/// Name the thermometers advertise.
+pub const NAME: &str = "iBBQ";
+
+/// Service advertised by the thermometers.
+pub const SERVICE_UUID: Uuid = uuid!("0000fff0-0000-1000-8000-00805f9b34fb");
+
+/// Characteristic the login is written to.
+pub const LOGIN_UUID: Uuid = uuid!("0000fff2-0000-1000-8000-00805f9b34fb");
+
+/// Characteristic notifying the temperatures of all channels.
+pub const TEMPERATURE_UUID: Uuid = uuid!("0000fff4-0000-1000-8000-00805f9b34fb");
+
+/// Characteristic commands are written to.
+pub const COMMAND_UUID: Uuid = uuid!("0000fff5-0000-1000-8000-00805f9b34fb");
+
+/// Fixed login the thermometers expect before sending anything.
+const LOGIN: [u8; 15] = [
+    0x21, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0xb8, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Command enabling the notification of temperatures.
+const ENABLE_REAL_TIME_DATA: [u8; 6] = [0x0b, 0x01, 0x00, 0x00, 0x00, 0x00];
+
+/// Raw value of channels without a probe plugged in.
+const UNPLUGGED: u16 = 0xfff6;
+
+/// Decode a temperature notification into the temperature of each channel in °C, `None` if no
+/// probe is plugged in.
+pub fn decode(value: &[u8]) -> Vec<Option<f32>> {
+    value
+        .chunks_exact(2)
+        .map(|bytes| {
+            let raw = u16::from_le_bytes([bytes[0], bytes[1]]);
+            (raw != UNPLUGGED).then(|| f32::from(raw as i16) / 10.0)
+        })
+        .collect()
+}
+
+/// Connects to the first Inkbird thermometer found and emits [`Event`]s for its probes.
+pub struct Inkbird {
+    sender: mpsc::Sender<Event>,
+    adapter: Adapter,
+    backoff: Backoff,
+    ambient_probe: Option<usize>,
+}
+
+impl Inkbird {
+    pub fn new() -> (Self, mpsc::Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel(16);
+
+        let inkbird = Self {
+            sender,
+            adapter: Adapter::default(),
+            backoff: Backoff::default(),
+            ambient_probe: None,
+        };
+
+        (inkbird, receiver)
+    }
+
+    /// Scan with `adapter` instead of the first one.
+    pub fn adapter(mut self, adapter: Adapter) -> Self {
+        self.adapter = adapter;
+        self
+    }
+
+    /// Wait according to `backoff` between failed attempts to connect the thermometer.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Report the channel `probe`, numbered from zero, as ambient temperature of all others
+    /// instead of as a probe.
+    pub fn ambient_probe(mut self, probe: usize) -> Self {
+        self.ambient_probe = Some(probe);
+        self
+    }
+
+    /// Connect to the thermometer and stream its events until the receiver is dropped,
+    /// reconnecting after a delay whenever the connection is lost.
+    pub async fn run(self) -> anyhow::Result<()> {
+        let mut attempt = 0;
+
+        loop {
+            self.sender.send(Event::State(State::Disconnected)).await?;
+
+            let started = Instant::now();
+
+            if let Err(err) = self.connect().await {
+                let message = format!("Inkbird failed: {err}");
+                let event = error::report(None, error::OTHER_FAILED, message);
+                let _ = self.sender.send(event).await;
+            }
+
+            if self.sender.is_closed() {
+                return Ok(());
+            }
+
+            // Only back off further if things keep failing right away.
+            if started.elapsed() > self.backoff.max {
+                attempt = 0;
+            }
+
+            attempt += 1;
+            let delay = self.backoff.delay(attempt);
+            tracing::info!("reconnecting Inkbird in {delay:?}");
+            self.sender
+                .send(Event::State(State::Retrying { attempt }))
+                .await?;
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Connect to the thermometer and send out its temperatures until the connection is lost.
+    async fn connect(&self) -> anyhow::Result<()> {
+        let manager = platform::Manager::new().await?;
+        let central = self.adapter.select(manager.adapters().await?).await?;
+        let inkbird = find(&central).await?;
+
+        self.sender.send(Event::State(State::Connecting)).await?;
+        tracing::info!("connecting Inkbird");
+        inkbird.connect().await?;
+        inkbird.discover_services().await?;
+
+        let characteristics = inkbird.characteristics();
+
+        let characteristic = |uuid: Uuid| {
+            characteristics
+                .iter()
+                .find(|characteristic| characteristic.uuid == uuid)
+                .ok_or_else(|| anyhow!("characteristic {uuid} not found"))
+        };
+
+        inkbird
+            .write(characteristic(LOGIN_UUID)?, &LOGIN, WriteType::WithResponse)
+            .await?;
+
+        inkbird.subscribe(characteristic(TEMPERATURE_UUID)?).await?;
+
+        inkbird
+            .write(
+                characteristic(COMMAND_UUID)?,
+                &ENABLE_REAL_TIME_DATA,
+                WriteType::WithResponse,
+            )
+            .await?;
+
+        let mut notifications = inkbird.notifications().await?;
+        self.sender.send(Event::State(State::Connected)).await?;
+
+        while let Some(notification) = notifications.next().await {
+            if notification.uuid == TEMPERATURE_UUID {
+                self.send_temperatures(&decode(&notification.value)).await?;
+            }
+        }
+
+        Err(anyhow!("Inkbird disconnected"))
+    }
+
+    /// Send a temperature event for each plugged in probe of `temperatures`.
+    async fn send_temperatures(&self, temperatures: &[Option<f32>]) -> anyhow::Result<()> {
+        let ambient = self
+            .ambient_probe
+            .and_then(|probe| *temperatures.get(probe)?);
+
+        for (probe, tip) in temperatures.iter().enumerate() {
+            let Some(tip) = *tip else {
+                continue;
+            };
+
+            if Some(probe) == self.ambient_probe {
+                continue;
+            }
+
+            self.sender
+                .send(Event::Temperature {
+                    probe,
+                    tip,
+                    ambient: ambient.unwrap_or(tip),
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ProbeSource for Inkbird {
+    fn name(&self) -> &'static str {
+        "Inkbird"
+    }
+
+    fn run(self: Box<Self>) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(Inkbird::run(*self))
+    }
+}
+
+/// Scan until an Inkbird thermometer is found.
+async fn find(central: &platform::Adapter) -> anyhow::Result<platform::Peripheral> {
+    tracing::info!("looking for Inkbird thermometers");
+
+    let mut events = central.events().await?;
+
+    central
+        .start_scan(ScanFilter {
+            services: vec![SERVICE_UUID],
+        })
+        .await?;
+
+    while let Some(event) = events.next().await {
+        let (CentralEvent::DeviceDiscovered(id) | CentralEvent::DeviceUpdated(id)) = event else {
+            continue;
+        };
+
+        let peripheral = central.peripheral(&id).await?;
+        let props = peripheral.properties().await?;
+
+        if props.and_then(|props| props.local_name).as_deref() == Some(NAME) {
+            tracing::info!(id = ?id, "Inkbird discovered");
+            central.stop_scan().await?;
+            return Ok(peripheral);
+        }
+    }
+
+    Err(anyhow!("bluetooth event stream ended"))
+}
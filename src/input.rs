@@ -0,0 +1,115 @@
+//! Push buttons and rotary encoder the user controls the display with.
+//!
+//! Buttons are polled in a task of their own and debounced, each press is sent as an [`Event`]
+//! to the UI. Turns of the encoder are reported by interrupt. Other frontends such as the web
+//! dashboard send their input on the same channel.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::button::Button;
+use crate::encoder::Encoder;
+
+/// Interval in which buttons are polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Input settings.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// GPIO pin (BCM numbering) of a push button showing the next page.
+    pub next_pin: Option<u8>,
+    /// GPIO pin (BCM numbering) of a push button showing the previous page.
+    pub previous_pin: Option<u8>,
+    /// GPIO pin (BCM numbering) of a push button resetting the lowest and highest temperatures.
+    pub reset_pin: Option<u8>,
+    /// GPIO pin (BCM numbering) of a push button restarting the cook timer.
+    pub timer_pin: Option<u8>,
+    /// GPIO pin (BCM numbering) of the clock output of a rotary encoder adjusting the target.
+    pub encoder_clk_pin: Option<u8>,
+    /// GPIO pin (BCM numbering) of the data output of the rotary encoder.
+    pub encoder_dt_pin: Option<u8>,
+    /// GPIO pin (BCM numbering) of the push button of the rotary encoder confirming the target.
+    pub encoder_sw_pin: Option<u8>,
+}
+
+/// Function of a button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    /// Show the next page.
+    Next,
+    /// Show the previous page.
+    Previous,
+    /// Cycle through the doneness presets.
+    Preset,
+    /// Only wake up the display.
+    Wake,
+    /// Confirm the target temperature dialed in with the encoder.
+    Confirm,
+    /// Reset the lowest and highest temperatures.
+    Reset,
+    /// Restart the cook timer.
+    Timer,
+}
+
+/// Input from the user.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A button was pressed.
+    Button(Key),
+    /// The encoder was turned by this many detents, positive clockwise.
+    #[cfg_attr(not(feature = "device"), allow(dead_code))]
+    Rotate(i32),
+    /// Target temperatures in °C were set remotely.
+    #[cfg_attr(not(feature = "web"), allow(dead_code))]
+    Targets(Vec<f32>),
+}
+
+/// Start polling `buttons` and watching the encoder with clock and data pins `encoder`, and
+/// send their events to `sender`.
+///
+/// Without any input no task is started.
+pub fn spawn(
+    buttons: Vec<(Key, Button)>,
+    encoder: Option<(u8, u8)>,
+    sender: mpsc::Sender<Event>,
+) -> anyhow::Result<()> {
+    let encoder = encoder
+        .map(|(clk, dt)| Encoder::new(clk, dt, sender.clone()))
+        .transpose()?;
+
+    if !buttons.is_empty() || encoder.is_some() {
+        tokio::spawn(poll(buttons, encoder, sender));
+    }
+
+    Ok(())
+}
+
+/// Poll `buttons` and keep `encoder` alive until the receiver is dropped.
+async fn poll(
+    mut buttons: Vec<(Key, Button)>,
+    _encoder: Option<Encoder>,
+    sender: mpsc::Sender<Event>,
+) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        for (key, button) in &mut buttons {
+            if button.was_pressed() {
+                tracing::debug!(key = ?key, "button pressed");
+
+                if sender.send(Event::Button(*key)).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        if sender.is_closed() {
+            return;
+        }
+    }
+}
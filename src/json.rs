@@ -0,0 +1,92 @@
+//! Events as JSON, shared by the WebSocket of the web dashboard and `--output json`.
+
+use chrono::{Local, SecondsFormat};
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+/// Format of the events printed on stdout.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum Output {
+    /// One JSON object per line with the `timestamp` of the event and its `type`.
+    Json,
+}
+
+/// Return `event` as JSON object tagged by its `type`.
+pub fn event(event: &meater::Event) -> serde_json::Value {
+    match *event {
+        meater::Event::State(state) => match state {
+            meater::State::Searching { elapsed } => {
+                json!({ "type": "state", "state": state.to_string(), "elapsed": elapsed.as_secs() })
+            }
+            meater::State::Retrying { attempt } => {
+                json!({ "type": "state", "state": state.to_string(), "attempt": attempt })
+            }
+            _ => json!({ "type": "state", "state": state.to_string() }),
+        },
+        meater::Event::Temperature {
+            probe,
+            tip,
+            ambient,
+        } => json!({ "type": "temperature", "probe": probe, "tip": tip, "ambient": ambient }),
+        meater::Event::Battery { probe, percent } => {
+            json!({ "type": "battery", "probe": probe, "percent": percent })
+        }
+        meater::Event::BatteryState { probe, state } => {
+            json!({ "type": "battery-state", "probe": probe, "state": state.to_string() })
+        }
+        meater::Event::Rssi { probe, rssi } => {
+            json!({ "type": "rssi", "probe": probe, "rssi": rssi })
+        }
+        meater::Event::Sensors {
+            probe,
+            ref temperatures,
+            surface,
+        } => json!({
+            "type": "sensors",
+            "probe": probe,
+            "temperatures": temperatures,
+            "surface": surface,
+        }),
+        meater::Event::ProbeRemoved { probe } => json!({ "type": "probe-removed", "probe": probe }),
+        meater::Event::ProbeInserted { probe } => {
+            json!({ "type": "probe-inserted", "probe": probe })
+        }
+        meater::Event::DecodeError { probe, ref value } => {
+            json!({ "type": "decode-error", "probe": probe, "value": value })
+        }
+        meater::Event::DeviceInfo {
+            probe,
+            ref firmware,
+            ref serial,
+        } => json!({
+            "type": "device-info",
+            "probe": probe,
+            "firmware": firmware,
+            "serial": serial,
+        }),
+        meater::Event::Error { probe, code, id } => {
+            json!({ "type": "error", "probe": probe, "code": code, "id": format!("{id:04x}") })
+        }
+        meater::Event::SessionEnded => json!({ "type": "session-ended" }),
+    }
+}
+
+/// Print events received on `receiver` as JSON lines on stdout until the client stops.
+pub async fn run(mut receiver: mpsc::Receiver<meater::Event>) -> anyhow::Result<()> {
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(received) = receiver.recv().await {
+        let mut object = event(&received);
+        object["timestamp"] = Local::now()
+            .to_rfc3339_opts(SecondsFormat::Millis, false)
+            .into();
+
+        let line = format!("{object}\n");
+        stdout.write_all(line.as_bytes()).await?;
+        // Flush every line so consumers of a pipe see the event right away.
+        stdout.flush().await?;
+    }
+
+    Ok(())
+}
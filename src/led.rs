@@ -0,0 +1,228 @@
+//! Status LED showing the connection state, for enclosures hiding the display.
+//!
+//! The LED blinks while looking for or connecting to a probe, is lit while one is connected and off
+//! once all are disconnected. It is either connected to a GPIO pin or one of the LEDs of the board
+//! under `/sys/class/leds`, e.g. the ACT LED of the Pi.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::{Deserialize, Deserializer};
+use tokio::sync::mpsc;
+
+/// Directory the LEDs of the board are found in.
+const LEDS: &str = "/sys/class/leds";
+
+/// Time the LED is on and off in turn while blinking.
+const BLINK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// LED to drive.
+#[derive(Clone, Debug)]
+pub enum Output {
+    /// LED between a GPIO pin (BCM numbering) and ground.
+    Pin(u8),
+    /// LED of the board by its name, e.g. `ACT`.
+    Board(String),
+}
+
+impl FromStr for Output {
+    type Err = std::convert::Infallible;
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        Ok(match output.parse() {
+            Ok(pin) => Self::Pin(pin),
+            Err(_) => Self::Board(output.to_string()),
+        })
+    }
+}
+
+/// Status LED settings.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// LED to drive, disabled if not set.
+    #[serde(deserialize_with = "deserialize_output")]
+    pub output: Option<Output>,
+}
+
+fn deserialize_output<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Output>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Pin(u8),
+        Name(String),
+    }
+
+    Ok(
+        Option::<Raw>::deserialize(deserializer)?.map(|raw| match raw {
+            Raw::Pin(pin) => Output::Pin(pin),
+            Raw::Name(name) => Output::Board(name),
+        }),
+    )
+}
+
+/// What the LED is doing.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Off,
+    Blink,
+    On,
+}
+
+impl Mode {
+    fn of(state: meater::State) -> Self {
+        match state {
+            meater::State::Disconnected => Self::Off,
+            meater::State::Connected => Self::On,
+            meater::State::Searching { .. }
+            | meater::State::Connecting
+            | meater::State::Retrying { .. }
+            | meater::State::Stale => Self::Blink,
+        }
+    }
+}
+
+/// LED of the board, handed back to the kernel when dropped.
+struct Board {
+    directory: PathBuf,
+    /// Trigger the kernel drove the LED with before, e.g. `mmc0` for SD card activity.
+    trigger: Option<String>,
+}
+
+impl Board {
+    fn new(name: &str) -> anyhow::Result<Self> {
+        let directory = PathBuf::from(LEDS).join(name);
+        let triggers = std::fs::read_to_string(directory.join("trigger"))
+            .with_context(|| format!("unable to read triggers of LED {name}"))?;
+
+        // The active trigger is the one in brackets.
+        let trigger = triggers
+            .split_whitespace()
+            .find_map(|trigger| trigger.strip_prefix('[')?.strip_suffix(']'))
+            .map(str::to_string);
+
+        std::fs::write(directory.join("trigger"), "none")
+            .with_context(|| format!("unable to take over LED {name}"))?;
+
+        Ok(Self { directory, trigger })
+    }
+
+    fn set(&self, on: bool) -> anyhow::Result<()> {
+        std::fs::write(
+            self.directory.join("brightness"),
+            if on { "1" } else { "0" },
+        )
+        .with_context(|| format!("unable to switch LED {}", self.directory.display()))
+    }
+}
+
+impl Drop for Board {
+    fn drop(&mut self) {
+        if let Some(trigger) = &self.trigger {
+            if let Err(err) = std::fs::write(self.directory.join("trigger"), trigger) {
+                tracing::warn!("unable to restore trigger {trigger} of LED: {err}");
+            }
+        }
+    }
+}
+
+/// LED connected to a GPIO pin or of the board.
+enum Led {
+    #[cfg(feature = "device")]
+    Pin(rppal::gpio::OutputPin),
+    #[cfg(not(feature = "device"))]
+    Pin(u8),
+    Board(Board),
+}
+
+impl Led {
+    fn new(output: &Output) -> anyhow::Result<Self> {
+        match output {
+            #[cfg(feature = "device")]
+            Output::Pin(pin) => {
+                let pin = rppal::gpio::Gpio::new()
+                    .context("unable to access GPIO")?
+                    .get(*pin)
+                    .with_context(|| format!("unable to get GPIO pin {pin}"))?
+                    .into_output_low();
+
+                Ok(Self::Pin(pin))
+            }
+            #[cfg(not(feature = "device"))]
+            Output::Pin(pin) => {
+                tracing::info!(pin, "no GPIO available, status LED is only logged");
+                Ok(Self::Pin(*pin))
+            }
+            Output::Board(name) => Ok(Self::Board(Board::new(name)?)),
+        }
+    }
+
+    fn set(&mut self, on: bool) -> anyhow::Result<()> {
+        match self {
+            #[cfg(feature = "device")]
+            Self::Pin(pin) => {
+                if on {
+                    pin.set_high();
+                } else {
+                    pin.set_low();
+                }
+
+                Ok(())
+            }
+            #[cfg(not(feature = "device"))]
+            Self::Pin(pin) => {
+                tracing::trace!(pin, on, "switching status LED");
+                Ok(())
+            }
+            Self::Board(board) => board.set(on),
+        }
+    }
+}
+
+/// Show the connection state of the events received on `receiver` on the LED `output`.
+pub async fn run(
+    output: Output,
+    mut receiver: mpsc::Receiver<meater::Event>,
+) -> anyhow::Result<()> {
+    let mut led = Led::new(&output)?;
+    let mut mode = Mode::Off;
+    let mut on = false;
+    let mut blink = tokio::time::interval(BLINK_INTERVAL);
+
+    led.set(on)?;
+
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                let meater::Event::State(state) = event else {
+                    continue;
+                };
+
+                let next = Mode::of(state);
+
+                if next == mode {
+                    continue;
+                }
+
+                mode = next;
+                on = mode != Mode::Off;
+                blink.reset();
+                led.set(on)?;
+            }
+            _ = blink.tick(), if mode == Mode::Blink => {
+                on = !on;
+                led.set(on)?;
+            }
+        }
+    }
+
+    led.set(false)
+}
@@ -0,0 +1,26 @@
+//! Connect to and read data from a [MEATER](https://www.meater.com) smart thermometer.
+//!
+//! [`Client`] scans for probes and emits [`Event`]s on a channel, while [`protocol`] contains the
+//! raw BLE constants and conversion functions for use without the client. Other thermometers can
+//! produce the same events by implementing [`ProbeSource`], like the [`inkbird`], [`combustion`]
+//! and [`onewire`] clients do. A [`simulated`] probe goes through a whole cook without hardware
+//! and [`replay`] feeds back the notifications recorded with [`Client::record`].
+
+mod battery;
+mod client;
+pub mod combustion;
+mod decoder;
+mod error;
+pub mod inkbird;
+pub mod onewire;
+pub mod protocol;
+mod recording;
+mod removal;
+pub mod replay;
+pub mod simulated;
+mod source;
+
+pub use btleplug::api::BDAddr;
+pub use client::{Adapter, Backoff, BatteryState, Calibration, Client, Event, State};
+pub use error::MeaterError;
+pub use source::ProbeSource;
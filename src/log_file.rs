@@ -0,0 +1,166 @@
+//! Log file rotated by size and time so long unattended cooks cannot fill the SD card.
+//!
+//! Once the file grows beyond its maximum size or a new rotation period begins, it is renamed to
+//! `<file>.1`, older ones move up to `<file>.2` and so on, and only the configured number of old
+//! files is kept.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Period after which the log file is rotated regardless of its size.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    /// Only rotate by size.
+    Never,
+    Hourly,
+    #[default]
+    Daily,
+}
+
+impl Rotation {
+    /// Return the period `time` falls into, a new one means the file is rotated.
+    fn period(self, time: DateTime<Local>) -> String {
+        match self {
+            Self::Never => String::new(),
+            Self::Hourly => time.format("%Y%m%d%H").to_string(),
+            Self::Daily => time.format("%Y%m%d").to_string(),
+        }
+    }
+}
+
+struct Current {
+    file: File,
+    size: u64,
+    period: String,
+}
+
+/// Log file rotated by size and time, shared by all log messages.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    rotation: Rotation,
+    /// Number of rotated files kept.
+    keep: usize,
+    current: Mutex<Current>,
+}
+
+impl RotatingFile {
+    /// Append to the log file at `path`, rotating it once it is larger than `max_size` bytes or
+    /// a new `rotation` period began, keeping `keep` old files.
+    pub fn open(
+        path: &Path,
+        max_size: u64,
+        rotation: Rotation,
+        keep: usize,
+    ) -> anyhow::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("unable to open {}", path.display()))?;
+
+        let metadata = file.metadata()?;
+
+        // A file left from a previous run belongs to the period it was last written in.
+        let modified = metadata
+            .modified()
+            .map(DateTime::<Local>::from)
+            .unwrap_or_else(|_| Local::now());
+
+        Ok(Self {
+            path: path.to_owned(),
+            max_size,
+            rotation,
+            keep,
+            current: Mutex::new(Current {
+                file,
+                size: metadata.len(),
+                period: rotation.period(modified),
+            }),
+        })
+    }
+
+    /// Return the path of the `index`-th rotated file.
+    fn rotated(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    /// Move the current file out of the way and start a new one.
+    fn rotate(&self, current: &mut Current, period: String) -> io::Result<()> {
+        current.file.flush()?;
+
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            // Renaming onto the oldest file drops it.
+            for index in (1..self.keep).rev() {
+                let from = self.rotated(index);
+
+                if from.exists() {
+                    fs::rename(from, self.rotated(index + 1))?;
+                }
+            }
+
+            fs::rename(&self.path, self.rotated(1))?;
+        }
+
+        current.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        current.size = 0;
+        current.period = period;
+        Ok(())
+    }
+
+    /// Write the complete message `buf`, rotating the file before if necessary.
+    fn write(&self, buf: &[u8]) -> io::Result<()> {
+        let mut current = self
+            .current
+            .lock()
+            .map_err(|_| io::Error::other("log file lock poisoned"))?;
+
+        let period = self.rotation.period(Local::now());
+        let full = current.size > 0 && current.size + buf.len() as u64 > self.max_size;
+
+        if full || period != current.period {
+            self.rotate(&mut current, period)?;
+        }
+
+        current.file.write_all(buf)?;
+        current.size += buf.len() as u64;
+        Ok(())
+    }
+}
+
+/// Writer handed out for a single log message.
+pub struct Writer<'a>(&'a RotatingFile);
+
+impl Write for Writer<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFile {
+    type Writer = Writer<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        Writer(self)
+    }
+}
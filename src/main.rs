@@ -1,144 +1,674 @@
-use anyhow::Context;
-#[cfg(feature = "host")]
-use embedded_graphics::draw_target::DrawTarget;
-use embedded_graphics::geometry::Point;
-use embedded_graphics::image::Image;
-use embedded_graphics::mono_font::MonoTextStyle;
-use embedded_graphics::pixelcolor::BinaryColor;
-use embedded_graphics::text::Text;
-use embedded_graphics::Drawable;
-use profont::PROFONT_24_POINT;
-
-mod meater;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use tokio::sync::{mpsc, watch};
+use tokio::time::MissedTickBehavior;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use alarm::{Alarm, Buzzer};
+use burn_in::BurnIn;
+use button::Button;
+use cli::Args;
+use config::Config;
+use display::DisplayBackend;
+use icons::Icons;
+use model::Model;
+use night::Night;
+use presets::Preset;
+use render::Renderer;
+use screensaver::Screensaver;
+use smoothing::Smoother;
+use units::Unit;
+
+mod alarm;
+mod alerts;
+mod buffer;
+mod burn_in;
+mod bus;
+mod button;
+mod calibrate;
+mod carryover;
+mod cli;
+mod config;
+mod csv_log;
+mod display;
+mod encoder;
+mod eta;
+mod font;
+#[cfg(feature = "sqlite")]
+mod history;
+mod icons;
+#[cfg(feature = "influxdb")]
+mod influxdb;
+mod input;
+#[cfg(feature = "json")]
+mod json;
+mod led;
+mod log_file;
+#[cfg(feature = "device")]
+mod max31855;
+mod model;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod night;
+#[cfg(feature = "notify")]
+mod notify;
+#[cfg(feature = "peripheral")]
+mod peripheral;
+mod presets;
+mod probes;
+mod render;
+mod screensaver;
+mod session;
+mod smoothing;
+mod stall;
+mod stats;
+#[cfg(feature = "statsd")]
+mod statsd;
+#[cfg(feature = "otel")]
+mod telemetry;
+mod ui;
+mod units;
+mod watchdog;
+#[cfg(feature = "web")]
+mod web;
+
+/// Target in °C the encoder starts from if there is none yet.
+const DEFAULT_TARGET: f32 = 57.0;
+
+/// Time after which an unconfirmed adjustment of the target is discarded.
+const ADJUSTMENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Everything fed by the events of the client shown to the user: display and alarm.
+struct Ui {
+    display: Box<dyn DisplayBackend>,
+    renderer: Renderer,
+    model: Model,
+    screensaver: Screensaver,
+    night: Night,
+    alarm: Option<Alarm>,
+    /// Publishes target changes to tasks outside of the UI.
+    targets: watch::Sender<Vec<f32>>,
+    /// Publishes the start of the cook timer to tasks outside of the UI.
+    timer: watch::Sender<Option<Instant>>,
+    unit: Unit,
+    /// Time of the last turn of the encoder while adjusting the target.
+    adjusted: Option<Instant>,
+    awake: bool,
+    /// Minimum time between two frames drawn on the display.
+    render_interval: Duration,
+}
+
+impl Ui {
+    /// Update the display with the events received from the client, the user and animation
+    /// frames.
+    ///
+    /// Changes arriving faster than the render interval are coalesced into a single frame.
+    async fn run(
+        mut self,
+        mut receiver: mpsc::Receiver<meater::Event>,
+        mut input: mpsc::Receiver<input::Event>,
+    ) -> anyhow::Result<()> {
+        let mut frames = tokio::time::interval(render::FRAME_INTERVAL);
+        frames.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let mut rendered = tokio::time::Instant::now();
+        let mut pending = false;
+
+        loop {
+            let due = rendered + self.render_interval;
+
+            let redraw = tokio::select! {
+                event = receiver.recv() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+
+                    self.handle(event)?
+                }
+                Some(event) = input.recv() => {
+                    self.handle_input(event);
+                    true
+                }
+                _ = frames.tick() => {
+                    if let Some(contrast) = self.night.update() {
+                        self.display.set_contrast(contrast)?;
+                    }
+
+                    let expired = self.expire_adjustment();
+                    self.renderer.tick(&self.model) || expired
+                }
+                _ = tokio::time::sleep_until(due), if pending => false,
+            };
+
+            let woke = match (self.awake, self.screensaver.is_awake()) {
+                (true, false) => {
+                    tracing::debug!("display going to sleep");
+                    self.display.clear()?;
+                    self.display.flush()?;
+                    self.display.set_power(false)?;
+                    self.awake = false;
+                    false
+                }
+                (false, true) => {
+                    tracing::debug!("display waking up");
+                    self.display.set_power(true)?;
+                    self.awake = true;
+                    true
+                }
+                _ => false,
+            };
+
+            // Nothing is drawn while asleep, waking up draws everything anyway.
+            pending = self.awake && (pending || redraw || woke);
+
+            if pending && tokio::time::Instant::now() >= due {
+                self.renderer.render(self.display.as_mut(), &self.model)?;
+                rendered = tokio::time::Instant::now();
+                pending = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Process `event` and return `true` if it changed what is shown.
+    fn handle(&mut self, event: meater::Event) -> anyhow::Result<bool> {
+        // Probes keep repeating the same values, which need no new frame.
+        let repeated = match event {
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => self.model.temperatures.get(&probe) == Some(&(tip, ambient)),
+            meater::Event::Battery { probe, percent } => {
+                self.model.batteries.get(&probe) == Some(&percent)
+            }
+            meater::Event::Rssi { probe, rssi } => self.model.rssi.get(&probe) == Some(&rssi),
+            _ => false,
+        };
+
+        if let Some(alarm) = self.alarm.as_mut() {
+            alarm.update(&event)?;
+        }
+
+        self.screensaver.update(&event);
+        self.model.update(event);
+
+        if *self.timer.borrow() != self.model.timer {
+            self.timer.send_replace(self.model.timer);
+        }
+
+        Ok(!repeated)
+    }
+
+    fn handle_input(&mut self, event: input::Event) {
+        // Remote changes neither need nor wake up the display.
+        if let input::Event::Targets(targets) = event {
+            self.set_targets(targets);
+            self.model.preset = None;
+            return;
+        }
+
+        self.screensaver.wake();
+
+        // The first press only wakes up the display.
+        if !self.awake {
+            return;
+        }
+
+        let key = match event {
+            input::Event::Button(key) => key,
+            input::Event::Rotate(steps) => {
+                self.adjust(steps);
+                return;
+            }
+            input::Event::Targets(_) => return,
+        };
+
+        match key {
+            input::Key::Next => self.renderer.next_page(),
+            input::Key::Previous => self.renderer.previous_page(),
+            input::Key::Preset => {
+                let preset = self.model.preset.map_or_else(Preset::first, Preset::next);
+                tracing::info!(preset = ?preset, "switching preset");
+
+                self.set_targets(vec![preset.celsius()]);
+                self.model.preset = Some(preset);
+            }
+            input::Key::Confirm => {
+                if let Some(target) = self.model.adjusting.take() {
+                    tracing::info!(target, "setting target");
+
+                    self.adjusted = None;
+                    self.set_targets(vec![target]);
+                    self.model.preset = None;
+                }
+            }
+            input::Key::Reset => {
+                tracing::info!("resetting temperature ranges");
+                self.model.extremes.clear();
+            }
+            input::Key::Timer => {
+                tracing::info!("restarting cook timer");
+                self.model.timer = Some(Instant::now());
+                self.timer.send_replace(self.model.timer);
+            }
+            input::Key::Wake => {}
+        }
+    }
+
+    /// Move the target being adjusted by `steps` degrees of the display unit.
+    fn adjust(&mut self, steps: i32) {
+        let current = self
+            .model
+            .adjusting
+            .or_else(|| self.model.targets.first().copied())
+            .unwrap_or(DEFAULT_TARGET);
+
+        let value = self.unit.convert(current).round() + steps as f32;
+        self.model.adjusting = Some(self.unit.to_celsius(value));
+        self.adjusted = Some(Instant::now());
+    }
+
+    /// Discard an adjustment that was not confirmed in time and return `true` if it was.
+    fn expire_adjustment(&mut self) -> bool {
+        if self
+            .adjusted
+            .is_some_and(|adjusted| adjusted.elapsed() >= ADJUSTMENT_TIMEOUT)
+        {
+            tracing::debug!("discarding unconfirmed target");
+            self.adjusted = None;
+            self.model.adjusting = None;
+            return true;
+        }
+
+        false
+    }
+
+    /// Replace the target temperatures given in °C everywhere.
+    fn set_targets(&mut self, targets: Vec<f32>) {
+        if let Some(alarm) = self.alarm.as_mut() {
+            alarm.set_targets(targets.clone());
+        }
+
+        self.model.targets = targets.clone();
+        self.targets.send_replace(targets);
+    }
+}
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
+    let args = Args::parse();
+    let calibrate = args.calibrate;
+    #[cfg(feature = "json")]
+    let output = args.output;
+    let config = Config::load(args.config.as_deref())?.merge(args);
 
-    #[cfg(feature = "device")]
-    let mut display = {
-        let interface = rppal::i2c::I2c::new().context("unable to create I2c")?;
+    let subscriber =
+        tracing_subscriber::registry().with(LevelFilter::from_level(config.logging.level));
 
-        let mut display: sh1106::mode::GraphicsMode<_> = sh1106::Builder::new()
-            .with_size(sh1106::displaysize::DisplaySize::Display128x64)
-            .connect_i2c(interface)
-            .into();
+    // The journal keeps the fields of every message, logging to stderr as well would only add
+    // copies without them.
+    #[cfg(feature = "journald")]
+    let (subscriber, stderr) = {
+        let journald = config
+            .logging
+            .journald
+            .then(|| tracing_journald::layer().map(|layer| layer.with_field_prefix(None)))
+            .transpose()
+            .map_err(|err| anyhow::anyhow!("unable to connect to journald: {err}"))?;
 
-        display.init().unwrap();
-        display.flush().unwrap();
-        display.clear();
-        display
+        (subscriber.with(journald), !config.logging.journald)
     };
+    #[cfg(not(feature = "journald"))]
+    let stderr = true;
 
-    #[cfg(feature = "host")]
-    let (mut display, mut window) = {
-        use embedded_graphics::geometry::Size;
-        use embedded_graphics_simulator::{
-            BinaryColorTheme, OutputSettingsBuilder, SimulatorDisplay, Window,
-        };
+    let subscriber = subscriber
+        .with(stderr.then(|| tracing_subscriber::fmt::layer().with_writer(std::io::stderr)));
+
+    let log_file = match &config.logging.file {
+        Some(path) => Some(log_file::RotatingFile::open(
+            path,
+            config.logging.file_max_mb * 1024 * 1024,
+            config.logging.file_rotation,
+            config.logging.file_keep,
+        )?),
+        None => None,
+    };
+
+    let subscriber = subscriber.with(log_file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_ansi(false)
+            .with_writer(file)
+    }));
+
+    #[cfg(feature = "otel")]
+    let subscriber = subscriber.with(match &config.telemetry.endpoint {
+        Some(endpoint) => Some(telemetry::layer(endpoint, &config.telemetry)?),
+        None => None,
+    });
+
+    subscriber.init();
+
+    if let Some(reference) = calibrate {
+        return calibrate::run(config, reference).await;
+    }
+
+    let mut display = display::open(&config.display);
 
-        let settings = OutputSettingsBuilder::new()
-            .theme(BinaryColorTheme::OledWhite)
-            .build();
+    if let Some(contrast) = config.display.contrast {
+        display.set_contrast(contrast)?;
+    }
 
-        let window = Window::new("MEATER emulated display", &settings);
-        (
-            SimulatorDisplay::<BinaryColor>::new(Size::new(128, 64)),
-            window,
-        )
+    let unit = config.units.temperature;
+
+    let targets: Vec<f32> = match config.alerts.preset {
+        Some(preset) => vec![preset.celsius()],
+        None => config
+            .alerts
+            .targets
+            .iter()
+            .map(|target| unit.to_celsius(*target))
+            .collect(),
     };
 
-    let not_found_icon = tinybmp::Bmp::from_slice(include_bytes!("assets/not-found.bmp")).unwrap();
-    let connecting_icon =
-        tinybmp::Bmp::from_slice(include_bytes!("assets/connecting.bmp")).unwrap();
-    let battery_icon_25 =
-        tinybmp::Bmp::from_slice(include_bytes!("assets/battery-25.bmp")).unwrap();
-    let battery_icon_50 =
-        tinybmp::Bmp::from_slice(include_bytes!("assets/battery-50.bmp")).unwrap();
-    let battery_icon_75 =
-        tinybmp::Bmp::from_slice(include_bytes!("assets/battery-75.bmp")).unwrap();
-    let battery_icon_100 =
-        tinybmp::Bmp::from_slice(include_bytes!("assets/battery-100.bmp")).unwrap();
+    let mut model = Model::new();
+    model.stall = stall::Detector::new(&config.stall, unit);
+    model.targets = targets.clone();
+    model.preset = config.alerts.preset;
+
+    if let Some(path) = &config.logging.state {
+        model.stats = stats::Stats::load(path)?;
+    }
 
-    Image::new(&not_found_icon, Point::new(47, 16)).draw(&mut display)?;
+    let icons = Icons::new(config.display.assets.as_deref())?;
+    let renderer = Renderer::new(icons, unit)
+        .rotation(config.display.rotation)
+        .burn_in(BurnIn::new(&config.burn_in))
+        .pages(ui::Pages::new(&config.ui))
+        .splash(
+            &format!("{:?}", config.probe.kind).to_lowercase(),
+            &format!("{:?}", config.display.kind).to_lowercase(),
+        );
+    renderer.render(display.as_mut(), &model)?;
 
-    #[cfg(feature = "device")]
-    display.flush().unwrap();
+    let mut buttons = Vec::new();
 
-    #[cfg(feature = "host")]
-    window.update(&display);
+    for (key, pin) in [
+        (input::Key::Next, config.input.next_pin),
+        (input::Key::Previous, config.input.previous_pin),
+        (input::Key::Preset, config.alerts.preset_pin),
+        (input::Key::Wake, config.screensaver.wake_pin),
+        (input::Key::Confirm, config.input.encoder_sw_pin),
+        (input::Key::Reset, config.input.reset_pin),
+        (input::Key::Timer, config.input.timer_pin),
+    ] {
+        if let Some(pin) = pin {
+            buttons.push((key, Button::new(pin)?));
+        }
+    }
 
-    let temperature_style = MonoTextStyle::new(&PROFONT_24_POINT, BinaryColor::On);
-    let mut temperature: Option<(f32, f32)> = None;
-    let mut battery: Option<u16> = None;
+    let encoder = config
+        .input
+        .encoder_clk_pin
+        .zip(config.input.encoder_dt_pin);
 
-    let (client, mut receiver) = meater::Client::new();
+    let ambient_min = config.alerts.ambient_min.map(|min| unit.to_celsius(min));
+    let ambient_max = config.alerts.ambient_max.map(|max| unit.to_celsius(max));
 
-    let event_handling = async move {
-        let mut state = meater::State::Disconnected;
+    // Targets may be set later on, so the buzzer is needed with a preset button or encoder as
+    // well.
+    let alarm = if targets.is_empty()
+        && config.alerts.preset_pin.is_none()
+        && encoder.is_none()
+        && ambient_min.is_none()
+        && ambient_max.is_none()
+    {
+        None
+    } else {
+        let alarm = Alarm::new(
+            targets.clone(),
+            unit.difference_to_celsius(config.alerts.hysteresis),
+            Buzzer::new(config.alerts.buzzer_pin)?,
+        );
 
-        while let Some(event) = receiver.recv().await {
-            match event {
-                meater::Event::State(new_state) => state = new_state,
-                meater::Event::Temperature { tip, ambient } => {
-                    temperature.replace((tip, ambient));
-                }
-                meater::Event::Battery { percent } => {
-                    battery.replace(percent);
-                }
+        Some(alarm.ambient(ambient_min, ambient_max))
+    };
+
+    let screensaver = Screensaver::new(&config.screensaver);
+    let night = Night::new(&config.night, config.display.contrast)?;
+    let (targets, targets_receiver) = watch::channel(targets);
+    let timer = watch::Sender::new(None);
+    model.session = session::Tracker::new(targets.subscribe());
+    let (input_sender, input) = mpsc::channel(16);
+
+    let mut bus = bus::Bus::new(config.buffer.capacity);
+
+    if let Some(timeout) = watchdog::timeout() {
+        let receiver = bus.subscribe();
+        tokio::spawn(watchdog::run(timeout, receiver));
+    }
+
+    {
+        let receiver = bus.subscribe();
+
+        tokio::spawn(async move {
+            if let Err(err) = session::run(targets_receiver, receiver).await {
+                tracing::error!("tracking cook sessions failed: {err}");
             }
+        });
+    }
 
-            #[cfg(feature = "device")]
-            display.clear();
+    // Alerts of rules are delivered by the notifiers if there are any.
+    #[cfg(feature = "notify")]
+    let (rule_alerts, rule_alerts_receiver) = {
+        let (sender, receiver) = mpsc::channel(16);
+        (config.notify.is_enabled().then_some(sender), receiver)
+    };
 
-            #[cfg(feature = "host")]
-            display
-                .clear(BinaryColor::Off)
-                .context("unable to clear display")?;
+    #[cfg(not(feature = "notify"))]
+    let rule_alerts = None;
 
-            match state {
-                meater::State::Disconnected => {
-                    Image::new(&not_found_icon, Point::new(47, 16)).draw(&mut display)?;
-                }
-                meater::State::Connecting => {
-                    Image::new(&connecting_icon, Point::new(47, 16)).draw(&mut display)?;
-                }
-                meater::State::Connected => {
-                    if let Some((tip, _ambient)) = temperature {
-                        Text::new(&format!("{tip:.0}°C"), Point::new(0, 38), temperature_style)
-                            .draw(&mut display)?;
-                    }
+    let mut rules = config.alerts.rules.clone();
 
-                    if let Some(percent) = battery {
-                        let icon = match percent {
-                            ..=25 => battery_icon_25,
-                            26..=50 => battery_icon_50,
-                            51..=75 => battery_icon_75,
-                            _ => battery_icon_100,
-                        };
+    rules.extend(alerts::ambient_band(
+        config.alerts.ambient_min,
+        config.alerts.ambient_max,
+        config.alerts.hysteresis,
+    ));
 
-                        Image::new(&icon, Point::new(112, 0)).draw(&mut display)?;
-                    }
-                }
+    // Low batteries raise alerts even without any rules.
+    let engine = alerts::Engine::new(&rules, unit);
+    tokio::spawn(alerts::run(engine, bus.subscribe(), rule_alerts));
+
+    if let Some(output) = config.led.output.clone() {
+        let receiver = bus.subscribe();
+
+        tokio::spawn(async move {
+            if let Err(err) = led::run(output, receiver).await {
+                tracing::error!("driving the status LED failed: {err}");
+            }
+        });
+    }
+
+    if let Some(path) = config.logging.csv.clone() {
+        let receiver = bus.subscribe();
+
+        tokio::spawn(async move {
+            if let Err(err) = csv_log::run(path, receiver).await {
+                tracing::error!("CSV logging failed: {err}");
             }
+        });
+    }
 
-            #[cfg(feature = "device")]
-            display.flush().unwrap();
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = config.logging.history.clone() {
+        let receiver = bus.subscribe();
 
-            #[cfg(feature = "host")]
-            window.update(&display);
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = history::run(&path, receiver) {
+                tracing::error!("storing history failed: {err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "json")]
+    if let Some(json::Output::Json) = output {
+        let receiver = bus.subscribe();
+
+        tokio::spawn(async move {
+            if let Err(err) = json::run(receiver).await {
+                tracing::error!("printing events failed: {err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "mqtt")]
+    if let Some(host) = config.mqtt.host.clone() {
+        let receiver = bus.subscribe();
+        let options = config.mqtt.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = mqtt::run(host, options, receiver).await {
+                tracing::error!("MQTT publishing failed: {err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "influxdb")]
+    if let Some(url) = config.influxdb.url.clone() {
+        let receiver = bus.subscribe();
+        let options = config.influxdb.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = influxdb::run(url, options, receiver).await {
+                tracing::error!("InfluxDB export failed: {err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "statsd")]
+    if let Some(host) = config.statsd.host.clone() {
+        let receiver = bus.subscribe();
+        let options = config.statsd.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = statsd::run(host, options, receiver).await {
+                tracing::error!("StatsD export failed: {err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "otel")]
+    if let Some(endpoint) = config.telemetry.endpoint.clone() {
+        let receiver = bus.subscribe();
+        let options = config.telemetry.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = telemetry::run(endpoint, options, receiver).await {
+                tracing::error!("exporting metrics failed: {err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "peripheral")]
+    if config.peripheral.enabled {
+        let receiver = bus.subscribe();
+        let options = config.peripheral.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = peripheral::run(options, receiver).await {
+                tracing::error!("serving temperatures via Bluetooth failed: {err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "notify")]
+    if config.notify.is_enabled() {
+        let receiver = bus.subscribe();
+        let options = config.notify.clone();
+        let stall = stall::Detector::new(&config.stall, unit);
+        let targets = targets.subscribe();
+        let timer = timer.subscribe();
+
+        tokio::spawn(async move {
+            let result = notify::run(
+                options,
+                unit,
+                stall,
+                targets,
+                timer,
+                receiver,
+                rule_alerts_receiver,
+            )
+            .await;
+
+            if let Err(err) = result {
+                tracing::error!("sending notifications failed: {err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "web")]
+    if let Some(address) = config.web.listen {
+        let receiver = bus.subscribe();
+        let targets = targets.subscribe();
+        let input = input_sender.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = web::run(address, unit, targets, input, receiver).await {
+                tracing::error!("serving the dashboard failed: {err}");
+            }
+        });
+    }
+
+    let (source, receiver) = probes::create(&config)?;
+    tracing::info!("reading {} probes", source.name());
+
+    let (session_end, own) = mpsc::channel(1);
+
+    tokio::spawn(async move {
+        if let Err(err) = session::end_on_signal(session_end).await {
+            tracing::error!("ending cook sessions on SIGUSR1 failed: {err}");
         }
+    });
 
-        Ok::<_, anyhow::Error>(())
+    let events = bus.subscribe();
+    tokio::spawn(bus.run(receiver, own, Smoother::new(&config.smoothing)));
+
+    let ui = Ui {
+        display,
+        renderer,
+        model,
+        screensaver,
+        night,
+        alarm,
+        targets,
+        timer,
+        unit,
+        adjusted: None,
+        awake: true,
+        render_interval: config.display.render_interval(),
     };
 
+    input::spawn(buttons, encoder, input_sender)?;
+
+    watchdog::ready();
+
     tokio::select! {
-        _ = client.run() => {},
-        _ = event_handling => {},
+        _ = source.run() => {},
+        _ = ui.run(events, input) => {},
         _ = tokio::signal::ctrl_c() => {
             tracing::debug!("received SIGINT, exiting ...");
         },
     }
 
+    #[cfg(feature = "otel")]
+    telemetry::shutdown();
+
     Ok(())
 }
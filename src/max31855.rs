@@ -0,0 +1,178 @@
+//! K-type thermocouple read through a MAX31855 converter on the SPI bus, e.g. for pizza ovens far
+//! beyond the range of wireless probes.
+//!
+//! The thermocouple is reported as the tip and ambient temperature of a single probe. While the
+//! converter reports a fault, e.g. because the thermocouple is unplugged, the probe counts as
+//! disconnected.
+
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::Context;
+use futures::future::BoxFuture;
+use meater::{Event, ProbeSource, State};
+use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
+use tokio::sync::mpsc;
+
+/// Interval the converter is read in.
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Clock speed of the bus, the converter supports up to 5 MHz.
+const CLOCK_SPEED: u32 = 1_000_000;
+
+/// Fault detected by the converter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Fault {
+    OpenCircuit,
+    ShortToGround,
+    ShortToVcc,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OpenCircuit => write!(f, "thermocouple not connected"),
+            Self::ShortToGround => write!(f, "thermocouple shorted to ground"),
+            Self::ShortToVcc => write!(f, "thermocouple shorted to VCC"),
+        }
+    }
+}
+
+/// Decode a 32 bit frame of the converter into the thermocouple temperature in °C.
+fn decode(frame: u32) -> Result<f32, Fault> {
+    if frame & (1 << 16) != 0 {
+        return Err(if frame & 0b001 != 0 {
+            Fault::OpenCircuit
+        } else if frame & 0b010 != 0 {
+            Fault::ShortToGround
+        } else {
+            Fault::ShortToVcc
+        });
+    }
+
+    // Signed upper 14 bits in quarter degrees.
+    Ok(((frame as i32) >> 18) as f32 * 0.25)
+}
+
+/// Reads the converter and emits [`Event`]s for its thermocouple.
+pub struct Max31855 {
+    sender: mpsc::Sender<Event>,
+    spi: Spi,
+}
+
+impl Max31855 {
+    /// Open the converter selected with chip select `select` of the first SPI bus.
+    pub fn new(select: u8) -> anyhow::Result<(Self, mpsc::Receiver<Event>)> {
+        let select = match select {
+            0 => SlaveSelect::Ss0,
+            1 => SlaveSelect::Ss1,
+            _ => anyhow::bail!("SPI chip select must be 0 or 1, not {select}"),
+        };
+
+        let spi = Spi::new(Bus::Spi0, select, CLOCK_SPEED, Mode::Mode0)
+            .context("unable to open SPI bus")?;
+
+        let (sender, receiver) = mpsc::channel(16);
+
+        Ok((Self { sender, spi }, receiver))
+    }
+
+    /// Read the converter until the receiver is dropped.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        let mut interval = tokio::time::interval(INTERVAL);
+        let mut connected = false;
+        let mut fault = None;
+
+        self.sender.send(Event::State(State::Disconnected)).await?;
+
+        loop {
+            interval.tick().await;
+
+            let mut frame = [0; 4];
+            self.spi
+                .read(&mut frame)
+                .context("unable to read MAX31855")?;
+
+            match decode(u32::from_be_bytes(frame)) {
+                Ok(tip) => {
+                    fault = None;
+
+                    if !connected {
+                        connected = true;
+                        self.sender.send(Event::State(State::Connected)).await?;
+                    }
+
+                    self.sender
+                        .send(Event::Temperature {
+                            probe: 0,
+                            tip,
+                            ambient: tip,
+                        })
+                        .await?;
+                }
+                Err(err) => {
+                    if fault.replace(err) != Some(err) {
+                        tracing::warn!("MAX31855 fault: {err}");
+                    }
+
+                    if connected {
+                        connected = false;
+                        self.sender.send(Event::State(State::Disconnected)).await?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ProbeSource for Max31855 {
+    fn name(&self) -> &'static str {
+        "MAX31855"
+    }
+
+    fn run(self: Box<Self>) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(Max31855::run(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frame with the 14 bit thermocouple value `raw`, an internal temperature of 25 °C and no
+    /// fault.
+    fn frame(raw: u32) -> u32 {
+        raw << 18 | 0x190 << 4
+    }
+
+    #[test]
+    fn decode_positive_temperatures() {
+        // Examples of the datasheet.
+        assert_eq!(decode(frame(0b01_1001_0000_0000)), Ok(1600.0));
+        assert_eq!(decode(frame(0b00_0001_1001_0011)), Ok(100.75));
+        assert_eq!(decode(frame(0b00_0000_0110_0100)), Ok(25.0));
+        assert_eq!(decode(frame(0)), Ok(0.0));
+    }
+
+    #[test]
+    fn decode_negative_temperatures() {
+        assert_eq!(decode(frame(0b11_1111_1111_1111)), Ok(-0.25));
+        assert_eq!(decode(frame(0b11_1111_1111_1100)), Ok(-1.0));
+        assert_eq!(decode(frame(0b11_1100_0001_1000)), Ok(-250.0));
+    }
+
+    #[test]
+    fn decode_faults() {
+        let fault = 1 << 16;
+
+        assert_eq!(decode(fault | 0b001), Err(Fault::OpenCircuit));
+        assert_eq!(decode(fault | 0b010), Err(Fault::ShortToGround));
+        assert_eq!(decode(fault | 0b100), Err(Fault::ShortToVcc));
+
+        // The fault bit wins over whatever temperature is reported alongside.
+        assert_eq!(
+            decode(frame(0b00_0000_0110_0100) | fault | 0b001),
+            Err(Fault::OpenCircuit)
+        );
+    }
+}
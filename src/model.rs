@@ -0,0 +1,250 @@
+//! State of all probes as seen by the UI.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+use crate::alarm;
+use crate::carryover::Predictor;
+use crate::eta::Estimator;
+use crate::presets::Preset;
+use crate::session;
+use crate::stall;
+use crate::stats::Stats;
+use crate::units::Unit;
+
+/// Number of tip temperatures kept per probe for the trend graph.
+pub const TREND_SAMPLES: usize = 64;
+
+/// Time span covered by the trend graph.
+const TREND_WINDOW: Duration = Duration::from_secs(45 * 60);
+
+/// Ring buffer of tip temperatures sampled evenly over [`TREND_WINDOW`].
+#[derive(Default)]
+pub struct Trend {
+    samples: VecDeque<f32>,
+    last: Option<Instant>,
+}
+
+impl Trend {
+    fn push(&mut self, tip: f32) {
+        let interval = TREND_WINDOW / TREND_SAMPLES as u32;
+
+        if self.last.is_some_and(|last| last.elapsed() < interval) {
+            return;
+        }
+
+        if self.samples.len() == TREND_SAMPLES {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(tip);
+        self.last = Some(Instant::now());
+    }
+
+    /// Samples from oldest to newest.
+    pub fn samples(&self) -> impl Iterator<Item = f32> + '_ {
+        self.samples.iter().copied()
+    }
+}
+
+/// Error reported by the client.
+#[derive(Clone, Copy, Debug)]
+pub struct Failure {
+    /// Kind of error, see [`meater::MeaterError::code`].
+    pub code: u16,
+    /// Random id the error was logged with.
+    pub id: u16,
+}
+
+/// Identification of a probe for troubleshooting.
+#[derive(Clone, Debug, Default)]
+pub struct Device {
+    pub firmware: Option<String>,
+    pub serial: Option<String>,
+}
+
+/// Lowest and highest temperatures of a probe in °C.
+#[derive(Clone, Copy, Debug)]
+pub struct Extremes {
+    pub tip_min: f32,
+    pub tip_max: f32,
+    pub ambient_min: f32,
+    pub ambient_max: f32,
+}
+
+/// Latest values received from the client.
+pub struct Model {
+    pub state: meater::State,
+    pub temperatures: BTreeMap<usize, (f32, f32)>,
+    pub batteries: BTreeMap<usize, u16>,
+    /// Whether the battery of each probe is low or charging.
+    pub battery_states: BTreeMap<usize, meater::BatteryState>,
+    pub rssi: BTreeMap<usize, i16>,
+    pub devices: BTreeMap<usize, Device>,
+    pub trends: BTreeMap<usize, Trend>,
+    /// Temperature range of each probe since the session started or it was reset.
+    pub extremes: BTreeMap<usize, Extremes>,
+    /// First tip temperature of each probe.
+    pub starts: BTreeMap<usize, f32>,
+    pub estimators: BTreeMap<usize, Estimator>,
+    /// Probes pulled out of the meat, their estimates are paused.
+    pub removed: BTreeSet<usize>,
+    /// Carryover of each probe once it rests.
+    pub carryover: BTreeMap<usize, Predictor>,
+    /// Target temperatures in °C, the n-th applies to the n-th probe, the last to all others.
+    pub targets: Vec<f32>,
+    /// Preset the targets were set from.
+    pub preset: Option<Preset>,
+    /// Target temperature in °C currently dialed in with the encoder but not yet confirmed.
+    pub adjusting: Option<f32>,
+    pub stall: stall::Detector,
+    /// Cook session in progress.
+    pub session: session::Tracker,
+    /// Last error, until a temperature arrives or a probe connects again.
+    pub error: Option<Failure>,
+    /// Start of the cook timer, set with the first temperature after connecting or by button.
+    pub timer: Option<Instant>,
+    /// Statistics of all cooks, kept across restarts if a state file is configured.
+    pub stats: Stats,
+}
+
+impl Model {
+    pub fn new() -> Self {
+        Self {
+            state: meater::State::Disconnected,
+            temperatures: BTreeMap::new(),
+            batteries: BTreeMap::new(),
+            battery_states: BTreeMap::new(),
+            rssi: BTreeMap::new(),
+            devices: BTreeMap::new(),
+            trends: BTreeMap::new(),
+            extremes: BTreeMap::new(),
+            starts: BTreeMap::new(),
+            estimators: BTreeMap::new(),
+            removed: BTreeSet::new(),
+            carryover: BTreeMap::new(),
+            targets: Vec::new(),
+            preset: None,
+            adjusting: None,
+            stall: stall::Detector::new(&stall::Options::default(), Unit::Celsius),
+            session: session::Tracker::new(watch::channel(Vec::new()).1),
+            error: None,
+            timer: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Return the progress of `probe` between 0 and 1 towards its target temperature, measured
+    /// from the first temperature seen.
+    pub fn progress(&self, probe: usize) -> Option<f32> {
+        let (tip, _) = self.temperatures.get(&probe)?;
+        let target = alarm::target(&self.targets, probe)?;
+        let start = self.starts.get(&probe).copied().unwrap_or(*tip);
+
+        Some(if *tip >= target {
+            1.0
+        } else if start >= target {
+            0.0
+        } else {
+            (tip - start) / (target - start)
+        })
+    }
+
+    pub fn update(&mut self, event: meater::Event) {
+        if let Some(change) = self.stall.update(&event, Instant::now()) {
+            tracing::info!(change = ?change, "stall changed");
+        }
+
+        // Summaries of finished sessions are logged by the session task.
+        self.session.update(&event);
+        self.stats.update(&event);
+
+        match event {
+            meater::Event::State(state) => {
+                if state == meater::State::Connected {
+                    self.error = None;
+                }
+
+                // The session ends along with the ranges and the timer.
+                if state == meater::State::Disconnected {
+                    self.extremes.clear();
+                    self.timer = None;
+                }
+
+                self.state = state;
+            }
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => {
+                self.error = None;
+                self.timer.get_or_insert_with(Instant::now);
+                self.temperatures.insert(probe, (tip, ambient));
+                self.trends.entry(probe).or_default().push(tip);
+
+                self.extremes
+                    .entry(probe)
+                    .and_modify(|extremes| {
+                        extremes.tip_min = extremes.tip_min.min(tip);
+                        extremes.tip_max = extremes.tip_max.max(tip);
+                        extremes.ambient_min = extremes.ambient_min.min(ambient);
+                        extremes.ambient_max = extremes.ambient_max.max(ambient);
+                    })
+                    .or_insert(Extremes {
+                        tip_min: tip,
+                        tip_max: tip,
+                        ambient_min: ambient,
+                        ambient_max: ambient,
+                    });
+                self.starts.entry(probe).or_insert(tip);
+
+                if !self.removed.contains(&probe) {
+                    self.estimators
+                        .entry(probe)
+                        .or_default()
+                        .push(Instant::now(), tip);
+                }
+
+                self.carryover
+                    .entry(probe)
+                    .or_default()
+                    .push(Instant::now(), tip, ambient);
+            }
+            meater::Event::Battery { probe, percent } => {
+                self.batteries.insert(probe, percent);
+            }
+            meater::Event::BatteryState { probe, state } => {
+                self.battery_states.insert(probe, state);
+            }
+            meater::Event::Rssi { probe, rssi } => {
+                self.rssi.insert(probe, rssi);
+            }
+            meater::Event::ProbeRemoved { probe } => {
+                // Temperatures in the air say nothing about the meat.
+                self.removed.insert(probe);
+                self.estimators.remove(&probe);
+            }
+            meater::Event::ProbeInserted { probe } => {
+                self.removed.remove(&probe);
+            }
+            meater::Event::DeviceInfo {
+                probe,
+                firmware,
+                serial,
+            } => {
+                self.devices.insert(probe, Device { firmware, serial });
+            }
+            meater::Event::Error { code, id, .. } => {
+                self.error = Some(Failure { code, id });
+            }
+            meater::Event::SessionEnded => {
+                self.extremes.clear();
+                self.timer = None;
+            }
+            meater::Event::Sensors { .. } | meater::Event::DecodeError { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Publishing of events to an MQTT broker.
+//!
+//! The connection state is published to `<prefix>/state`, probe values to
+//! `<prefix>/probe/<n>/{tip,ambient,battery}` with probes numbered from one like on the display.
+//!
+//! With Home Assistant discovery enabled, retained sensor configurations are published to
+//! `<discovery prefix>/sensor/<prefix>/<object>/config` for the connection state and for each probe
+//! as soon as it is first seen.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, MqttOptions, QoS};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+/// Connection settings of the MQTT sink.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// MQTT broker to publish events to, publishing is disabled if not set.
+    pub host: Option<String>,
+    /// Port of the MQTT broker.
+    pub port: u16,
+    /// Prefix of all published topics.
+    pub prefix: String,
+    /// Announce sensors via Home Assistant MQTT discovery.
+    pub homeassistant: bool,
+    /// Topic prefix Home Assistant listens on for discovery messages.
+    pub homeassistant_prefix: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            host: None,
+            port: 1883,
+            prefix: "meater".to_string(),
+            homeassistant: false,
+            homeassistant_prefix: "homeassistant".to_string(),
+        }
+    }
+}
+
+impl Options {
+    /// Device all discovered entities belong to.
+    fn device(&self) -> serde_json::Value {
+        json!({
+            "identifiers": [self.prefix],
+            "name": "MEATER",
+            "manufacturer": "Apption Labs",
+            "model": "MEATER",
+            "sw_version": env!("CARGO_PKG_VERSION"),
+        })
+    }
+
+    /// Return the discovery message for a sensor with `object_id` reporting on `state_topic`.
+    fn sensor_config(
+        &self,
+        object_id: &str,
+        name: &str,
+        state_topic: String,
+        attributes: serde_json::Value,
+    ) -> (String, String) {
+        let mut config = json!({
+            "name": name,
+            "unique_id": format!("{}_{object_id}", self.prefix),
+            "state_topic": state_topic,
+            "device": self.device(),
+        });
+
+        if let (Some(config), Some(attributes)) = (config.as_object_mut(), attributes.as_object()) {
+            config.extend(attributes.clone());
+        }
+
+        let topic = format!(
+            "{}/sensor/{}/{object_id}/config",
+            self.homeassistant_prefix, self.prefix
+        );
+
+        (topic, config.to_string())
+    }
+
+    /// Return discovery messages for the connection state sensor.
+    fn state_discovery(&self) -> Vec<(String, String)> {
+        vec![self.sensor_config(
+            "state",
+            "Connection state",
+            format!("{}/state", self.prefix),
+            json!({
+                "device_class": "enum",
+                "options": meater::State::ALL.map(|state| state.to_string()),
+                "icon": "mdi:bluetooth",
+            }),
+        )]
+    }
+
+    /// Return discovery messages for the sensors of `probe`.
+    fn probe_discovery(&self, probe: usize) -> Vec<(String, String)> {
+        let number = probe + 1;
+        let topic = |value: &str| format!("{}/probe/{number}/{value}", self.prefix);
+        let temperature = json!({
+            "device_class": "temperature",
+            "state_class": "measurement",
+            "unit_of_measurement": "°C",
+        });
+
+        vec![
+            self.sensor_config(
+                &format!("probe_{number}_tip"),
+                &format!("Probe {number} tip"),
+                topic("tip"),
+                temperature.clone(),
+            ),
+            self.sensor_config(
+                &format!("probe_{number}_ambient"),
+                &format!("Probe {number} ambient"),
+                topic("ambient"),
+                temperature,
+            ),
+            self.sensor_config(
+                &format!("probe_{number}_battery"),
+                &format!("Probe {number} battery"),
+                topic("battery"),
+                json!({
+                    "device_class": "battery",
+                    "state_class": "measurement",
+                    "unit_of_measurement": "%",
+                }),
+            ),
+            self.sensor_config(
+                &format!("probe_{number}_rssi"),
+                &format!("Probe {number} signal strength"),
+                topic("rssi"),
+                json!({
+                    "device_class": "signal_strength",
+                    "state_class": "measurement",
+                    "unit_of_measurement": "dBm",
+                    "entity_category": "diagnostic",
+                }),
+            ),
+        ]
+    }
+}
+
+/// Return the topic and payload pairs to publish for `event`.
+fn messages(prefix: &str, event: &meater::Event) -> Vec<(String, String)> {
+    match event {
+        meater::Event::State(state) => vec![(format!("{prefix}/state"), state.to_string())],
+        meater::Event::Temperature {
+            probe,
+            tip,
+            ambient,
+        } => vec![
+            (
+                format!("{prefix}/probe/{}/tip", probe + 1),
+                format!("{tip:.1}"),
+            ),
+            (
+                format!("{prefix}/probe/{}/ambient", probe + 1),
+                format!("{ambient:.1}"),
+            ),
+        ],
+        meater::Event::Battery { probe, percent } => vec![(
+            format!("{prefix}/probe/{}/battery", probe + 1),
+            percent.to_string(),
+        )],
+        meater::Event::BatteryState { probe, state } => vec![(
+            format!("{prefix}/probe/{}/battery_state", probe + 1),
+            state.to_string(),
+        )],
+        meater::Event::Rssi { probe, rssi } => vec![(
+            format!("{prefix}/probe/{}/rssi", probe + 1),
+            rssi.to_string(),
+        )],
+        meater::Event::Sensors {
+            probe,
+            temperatures,
+            surface,
+        } => temperatures
+            .iter()
+            .enumerate()
+            .map(|(sensor, temperature)| {
+                (
+                    format!("{prefix}/probe/{}/sensor/{}", probe + 1, sensor + 1),
+                    format!("{temperature:.1}"),
+                )
+            })
+            .chain(surface.map(|surface| {
+                (
+                    format!("{prefix}/probe/{}/surface", probe + 1),
+                    format!("{surface:.1}"),
+                )
+            }))
+            .collect(),
+        meater::Event::ProbeRemoved { probe } => vec![(
+            format!("{prefix}/probe/{}/removed", probe + 1),
+            true.to_string(),
+        )],
+        meater::Event::ProbeInserted { probe } => vec![(
+            format!("{prefix}/probe/{}/removed", probe + 1),
+            false.to_string(),
+        )],
+        meater::Event::DeviceInfo {
+            probe,
+            firmware,
+            serial,
+        } => [("firmware", firmware), ("serial", serial)]
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let value = value.clone()?;
+                Some((format!("{prefix}/probe/{}/{name}", probe + 1), value))
+            })
+            .collect(),
+        meater::Event::DecodeError { .. }
+        | meater::Event::Error { .. }
+        | meater::Event::SessionEnded => Vec::new(),
+    }
+}
+
+/// Publish all events received on `receiver` to the broker `host`.
+pub async fn run(
+    host: String,
+    options: Options,
+    mut receiver: mpsc::Receiver<meater::Event>,
+) -> anyhow::Result<()> {
+    let options = &options;
+
+    let mut mqtt_options = MqttOptions::new("meater", host, options.port);
+    mqtt_options.set_keep_alive(Duration::from_secs(30));
+
+    let (client, mut eventloop) = AsyncClient::new(mqtt_options, 16);
+
+    // The event loop has to be polled for anything to be sent, it reconnects on the next poll
+    // after an error.
+    let connection = async move {
+        loop {
+            if let Err(err) = eventloop.poll().await {
+                tracing::warn!("MQTT connection error: {err}");
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+    };
+
+    let publishing = async move {
+        let mut announced = HashSet::new();
+
+        if options.homeassistant {
+            for (topic, payload) in options.state_discovery() {
+                client
+                    .publish(topic, QoS::AtLeastOnce, true, payload)
+                    .await?;
+            }
+        }
+
+        while let Some(event) = receiver.recv().await {
+            if let meater::Event::Temperature { probe, .. } | meater::Event::Battery { probe, .. } =
+                event
+            {
+                if options.homeassistant && announced.insert(probe) {
+                    for (topic, payload) in options.probe_discovery(probe) {
+                        client
+                            .publish(topic, QoS::AtLeastOnce, true, payload)
+                            .await?;
+                    }
+                }
+            }
+
+            for (topic, payload) in messages(&options.prefix, &event) {
+                client
+                    .publish(topic, QoS::AtLeastOnce, false, payload)
+                    .await?;
+            }
+        }
+
+        Ok::<_, anyhow::Error>(())
+    };
+
+    tokio::select! {
+        _ = connection => Ok(()),
+        result = publishing => result,
+    }
+}
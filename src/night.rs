@@ -0,0 +1,195 @@
+//! Dimming the display at night, when the OLED at full contrast is blinding.
+//!
+//! Night is either a fixed time of the day or, with a light sensor on a channel of an MCP3008 ADC,
+//! as long as the sensor reads dark.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use chrono::{Local, NaiveTime};
+use serde::Deserialize;
+
+/// Interval the clock or the light sensor are checked in.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Contrast restored at the end of the night if none is configured.
+const DEFAULT_CONTRAST: u8 = 0x80;
+
+/// Counts the light sensor has to read above the threshold before it is day again.
+#[cfg(feature = "device")]
+const HYSTERESIS: u16 = 32;
+
+/// Night mode settings.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Contrast from 0 to 255 the display is dimmed to at night.
+    pub contrast: u8,
+    /// Time of the day the night starts, e.g. `22:00`.
+    pub start: Option<String>,
+    /// Time of the day the night ends, e.g. `06:30`.
+    pub end: Option<String>,
+    /// Channel of the MCP3008 a light sensor is connected to, used instead of the times.
+    pub light_channel: Option<u8>,
+    /// SPI chip select of the first bus the MCP3008 is connected to.
+    pub light_select: u8,
+    /// Reading of the light sensor from 0 to 1023 below which it is night.
+    pub dark_below: u16,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            contrast: 1,
+            start: None,
+            end: None,
+            light_channel: None,
+            light_select: 1,
+            dark_below: 100,
+        }
+    }
+}
+
+/// Parse the time of the day `time` given as hours and minutes.
+fn parse_time(time: &str) -> anyhow::Result<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M").with_context(|| format!("invalid time {time}"))
+}
+
+/// Light sensor read through an MCP3008 ADC on the first SPI bus.
+#[cfg(feature = "device")]
+struct LightSensor {
+    spi: rppal::spi::Spi,
+    channel: u8,
+}
+
+#[cfg(feature = "device")]
+impl LightSensor {
+    fn new(select: u8, channel: u8) -> anyhow::Result<Self> {
+        use rppal::spi::{Bus, Mode, SlaveSelect, Spi};
+
+        let select = match select {
+            0 => SlaveSelect::Ss0,
+            1 => SlaveSelect::Ss1,
+            _ => anyhow::bail!("SPI chip select must be 0 or 1, not {select}"),
+        };
+
+        if channel > 7 {
+            anyhow::bail!("MCP3008 channel must be between 0 and 7, not {channel}");
+        }
+
+        let spi = Spi::new(Bus::Spi0, select, 1_000_000, Mode::Mode0)
+            .context("unable to open SPI bus")?;
+
+        Ok(Self { spi, channel })
+    }
+
+    /// Read the sensor, from 0 for dark to 1023 for bright.
+    fn read(&mut self) -> anyhow::Result<u16> {
+        // Start bit, single ended channel and room for the 10 bit result.
+        let request = [1, (8 + self.channel) << 4, 0];
+        let mut response = [0; 3];
+
+        self.spi
+            .transfer(&mut response, &request)
+            .context("unable to read MCP3008")?;
+
+        Ok(u16::from(response[1] & 0b11) << 8 | u16::from(response[2]))
+    }
+}
+
+/// How the night is recognized.
+enum Detector {
+    /// Night from `start` until `end`, possibly across midnight.
+    Schedule { start: NaiveTime, end: NaiveTime },
+    /// Night while the sensor reads below `dark_below`.
+    #[cfg(feature = "device")]
+    Light {
+        sensor: LightSensor,
+        dark_below: u16,
+    },
+}
+
+/// Decides when the display is dimmed.
+pub struct Night {
+    detector: Option<Detector>,
+    /// Contrast during the day.
+    day: u8,
+    /// Contrast during the night.
+    night: u8,
+    is_night: bool,
+    checked: Option<Instant>,
+}
+
+impl Night {
+    /// Create from `options`, restoring `day` contrast in the morning.
+    pub fn new(options: &Options, day: Option<u8>) -> anyhow::Result<Self> {
+        let detector = match (options.light_channel, &options.start, &options.end) {
+            #[cfg(feature = "device")]
+            (Some(channel), _, _) => Some(Detector::Light {
+                sensor: LightSensor::new(options.light_select, channel)?,
+                dark_below: options.dark_below,
+            }),
+            #[cfg(not(feature = "device"))]
+            (Some(_), _, _) => anyhow::bail!("light sensors are only supported on the device"),
+            (None, Some(start), Some(end)) => Some(Detector::Schedule {
+                start: parse_time(start)?,
+                end: parse_time(end)?,
+            }),
+            (None, None, None) => None,
+            (None, _, _) => anyhow::bail!("night mode needs both a start and an end"),
+        };
+
+        Ok(Self {
+            detector,
+            day: day.unwrap_or(DEFAULT_CONTRAST),
+            night: options.contrast,
+            is_night: false,
+            checked: None,
+        })
+    }
+
+    /// Check whether night began or ended and return the contrast to switch the display to.
+    pub fn update(&mut self) -> Option<u8> {
+        let detector = self.detector.as_mut()?;
+
+        if self
+            .checked
+            .is_some_and(|checked| checked.elapsed() < CHECK_INTERVAL)
+        {
+            return None;
+        }
+
+        self.checked = Some(Instant::now());
+
+        let is_night = match detector {
+            Detector::Schedule { start, end } => {
+                let now = Local::now().time();
+
+                if start <= end {
+                    *start <= now && now < *end
+                } else {
+                    now >= *start || now < *end
+                }
+            }
+            #[cfg(feature = "device")]
+            Detector::Light { sensor, dark_below } => match sensor.read() {
+                // Stay dimmed until it is clearly brighter to not flicker at dusk.
+                Ok(light) if self.is_night => light < dark_below.saturating_add(HYSTERESIS),
+                Ok(light) => light < *dark_below,
+                Err(err) => {
+                    tracing::warn!("unable to read light sensor: {err:#}");
+                    self.is_night
+                }
+            },
+        };
+
+        if is_night == self.is_night {
+            return None;
+        }
+
+        self.is_night = is_night;
+        tracing::info!(is_night, "switching night mode");
+
+        Some(if is_night { self.night } else { self.day })
+    }
+}
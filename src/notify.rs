@@ -0,0 +1,417 @@
+//! Notifications about the cook sent to any number of [`Notifier`]s.
+//!
+//! An alert is raised when a probe reaches its target, when its temperature stalls, when the probe
+//! stays disconnected for longer than a threshold and when the [`alerts`](crate::alerts) engine
+//! raises one, e.g. for a configured rule or a battery running low. Each alert is delivered to
+//! every configured sink: ntfy topics, webhooks, Telegram chats and shell commands.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+
+use crate::alarm;
+use crate::alerts;
+use crate::stall;
+use crate::units::Unit;
+
+mod command;
+mod ntfy;
+mod telegram;
+mod webhook;
+
+use command::Command;
+use ntfy::Ntfy;
+use telegram::Telegram;
+use webhook::Webhook;
+
+/// Time after which a notification that could not be delivered is given up.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Service alerts are delivered to.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Sink {
+    /// ntfy topic at `url`, e.g. `https://ntfy.sh/my-grill`.
+    Ntfy { url: String },
+    /// URL a JSON object with `type`, `title` and `message` is posted to.
+    Webhook { url: String },
+    /// Telegram `chat` the bot with `token` sends to and answers `/status` in.
+    Telegram { token: String, chat: i64 },
+    /// Shell command run with the alert in `MEATER_ALERT`, `MEATER_TITLE` and `MEATER_MESSAGE`.
+    Command { command: String },
+}
+
+/// Notification settings.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Sinks every alert is delivered to.
+    pub sinks: Vec<Sink>,
+    /// Minutes the probe has to be disconnected before a notification is sent.
+    pub disconnect_minutes: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            disconnect_minutes: 5,
+        }
+    }
+}
+
+impl Options {
+    /// Return `true` if notifications are sent anywhere.
+    pub fn is_enabled(&self) -> bool {
+        !self.sinks.is_empty()
+    }
+}
+
+/// Reason for a notification, temperatures are in °C.
+#[derive(Clone, Debug)]
+pub enum AlertEvent {
+    /// `elapsed` is the time on the cook timer if it runs, as with stalls.
+    TargetReached {
+        probe: usize,
+        tip: f32,
+        elapsed: Option<Duration>,
+    },
+    Stalled {
+        probe: usize,
+        tip: f32,
+        elapsed: Option<Duration>,
+    },
+    Disconnected {
+        minutes: u64,
+    },
+    /// `percent` is unknown if the level was missed, e.g. by a lagging consumer.
+    LowBattery {
+        probe: usize,
+        percent: Option<u16>,
+    },
+    Rule(alerts::Alert),
+}
+
+impl AlertEvent {
+    /// Name of the kind of alert for machines.
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TargetReached { .. } => "target-reached",
+            Self::Stalled { .. } => "stalled",
+            Self::Disconnected { .. } => "disconnected",
+            Self::LowBattery { .. } => "low-battery",
+            Self::Rule(_) => "rule",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::TargetReached { .. } => "Target reached",
+            Self::Stalled { .. } => "Temperature stalled",
+            Self::Disconnected { .. } => "Probe disconnected",
+            Self::LowBattery { .. } => "Low battery",
+            Self::Rule(_) => "Alert",
+        }
+    }
+
+    fn message(&self, unit: Unit) -> String {
+        match self {
+            Self::TargetReached {
+                probe,
+                tip,
+                elapsed,
+            } => format!(
+                "Probe {} reached {:.0}{}{}",
+                probe + 1,
+                unit.convert(*tip),
+                unit.symbol(),
+                after(*elapsed)
+            ),
+            Self::Stalled {
+                probe,
+                tip,
+                elapsed,
+            } => format!(
+                "Probe {} stalled at {:.0}{}{}",
+                probe + 1,
+                unit.convert(*tip),
+                unit.symbol(),
+                after(*elapsed)
+            ),
+            Self::Disconnected { minutes } => {
+                format!("No probe connected for {minutes} minutes")
+            }
+            Self::LowBattery {
+                probe,
+                percent: Some(percent),
+            } => format!("Battery of probe {} is at {percent}%", probe + 1),
+            Self::LowBattery {
+                probe,
+                percent: None,
+            } => format!("Battery of probe {} is low", probe + 1),
+            Self::Rule(alert) => alert.to_string(),
+        }
+    }
+}
+
+/// Format the time on the cook timer, e.g. `6h31m`.
+fn cook_time(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    format!("{}h{:02}m", minutes / 60, minutes % 60)
+}
+
+/// Return the time on the cook timer as suffix of a message if it runs.
+fn after(elapsed: Option<Duration>) -> String {
+    elapsed
+        .map(|elapsed| format!(" after {}", cook_time(elapsed)))
+        .unwrap_or_default()
+}
+
+/// Turns events into alerts, each one only once until its condition cleared.
+struct Watcher {
+    /// Target temperatures in °C, the n-th applies to the n-th probe, the last to all others.
+    targets: watch::Receiver<Vec<f32>>,
+    /// Start of the cook timer.
+    timer: watch::Receiver<Option<std::time::Instant>>,
+    disconnect_after: Duration,
+    state: meater::State,
+    /// Latest tip and ambient temperatures of each probe.
+    temperatures: BTreeMap<usize, (f32, f32)>,
+    stall: stall::Detector,
+    /// Probes that reached their target.
+    reached: BTreeSet<usize>,
+    /// Time the disconnect alert is due if no probe connects until then.
+    disconnect_due: Option<Instant>,
+    connected: bool,
+}
+
+impl Watcher {
+    fn new(
+        options: &Options,
+        stall: stall::Detector,
+        targets: watch::Receiver<Vec<f32>>,
+        timer: watch::Receiver<Option<std::time::Instant>>,
+    ) -> Self {
+        Self {
+            targets,
+            timer,
+            disconnect_after: Duration::from_secs(options.disconnect_minutes * 60),
+            state: meater::State::Disconnected,
+            temperatures: BTreeMap::new(),
+            stall,
+            reached: BTreeSet::new(),
+            disconnect_due: None,
+            connected: false,
+        }
+    }
+
+    /// Update from `event` and return the alert it raised.
+    fn update(&mut self, event: &meater::Event) -> Option<AlertEvent> {
+        // New targets may be reached again.
+        if self.targets.has_changed().unwrap_or(false) {
+            self.targets.borrow_and_update();
+            self.reached.clear();
+        }
+
+        let stall = self.stall.update(event, Instant::now().into_std());
+        let elapsed = self.timer.borrow().map(|started| started.elapsed());
+
+        match *event {
+            meater::Event::State(state) => {
+                self.state = state;
+
+                match state {
+                    meater::State::Connected => {
+                        self.connected = true;
+                        self.disconnect_due = None;
+                    }
+                    // Another probe connecting or going stale does not lose the connection.
+                    meater::State::Disconnected | meater::State::Retrying { .. }
+                        if self.connected =>
+                    {
+                        self.connected = false;
+                        self.disconnect_due = Some(Instant::now() + self.disconnect_after);
+                    }
+                    _ => {}
+                }
+
+                if state == meater::State::Disconnected {
+                    self.temperatures.clear();
+                }
+
+                None
+            }
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => {
+                self.temperatures.insert(probe, (tip, ambient));
+
+                let target = alarm::target(&self.targets.borrow(), probe);
+
+                if target.is_some_and(|target| tip >= target) {
+                    return self
+                        .reached
+                        .insert(probe)
+                        .then_some(AlertEvent::TargetReached {
+                            probe,
+                            tip,
+                            elapsed,
+                        });
+                }
+
+                self.reached.remove(&probe);
+
+                match stall {
+                    Some(stall::Change::Started { probe, tip }) => Some(AlertEvent::Stalled {
+                        probe,
+                        tip,
+                        elapsed,
+                    }),
+                    _ => None,
+                }
+            }
+            meater::Event::Battery { .. }
+            | meater::Event::BatteryState { .. }
+            | meater::Event::Rssi { .. }
+            | meater::Event::Sensors { .. }
+            | meater::Event::ProbeRemoved { .. }
+            | meater::Event::ProbeInserted { .. }
+            | meater::Event::DecodeError { .. }
+            | meater::Event::DeviceInfo { .. }
+            | meater::Event::Error { .. }
+            | meater::Event::SessionEnded => None,
+        }
+    }
+
+    /// Return the current state and temperatures as message.
+    fn status(&self, unit: Unit) -> String {
+        let format = |celsius: f32| format!("{:.0}{}", unit.convert(celsius), unit.symbol());
+        let mut lines = vec![format!("Probe {}", self.state)];
+
+        if let Some(started) = *self.timer.borrow() {
+            lines.push(format!("Cooking for {}", cook_time(started.elapsed())));
+        }
+
+        for (probe, (tip, ambient)) in &self.temperatures {
+            let mut line = format!(
+                "Probe {}: {} (ambient {})",
+                probe + 1,
+                format(*tip),
+                format(*ambient)
+            );
+
+            if let Some(target) = alarm::target(&self.targets.borrow(), *probe) {
+                line.push_str(&format!(", target {}", format(target)));
+            }
+
+            lines.push(line);
+        }
+
+        lines.join("\n")
+    }
+
+    /// Return the disconnect alert, clearing it until the next disconnect.
+    fn disconnected(&mut self) -> AlertEvent {
+        self.disconnect_due = None;
+
+        AlertEvent::Disconnected {
+            minutes: self.disconnect_after.as_secs() / 60,
+        }
+    }
+}
+
+/// Destination alerts are delivered to.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Deliver `event`.
+    async fn notify(&self, event: AlertEvent) -> anyhow::Result<()>;
+}
+
+/// Send notifications for the events received on `receiver` and the alerts of rules received on
+/// `rules`.
+pub async fn run(
+    options: Options,
+    unit: Unit,
+    stall: stall::Detector,
+    targets: watch::Receiver<Vec<f32>>,
+    timer: watch::Receiver<Option<std::time::Instant>>,
+    mut receiver: mpsc::Receiver<meater::Event>,
+    mut rules: mpsc::Receiver<alerts::Alert>,
+) -> anyhow::Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+
+    // Telegram bots asked for the status.
+    let (requests, mut status_requests) = mpsc::channel(4);
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+
+    for sink in options.sinks.iter().cloned() {
+        notifiers.push(match sink {
+            Sink::Ntfy { url } => Box::new(Ntfy::new(client.clone(), url, unit)),
+            Sink::Webhook { url } => Box::new(Webhook::new(client.clone(), url, unit)),
+            Sink::Telegram { token, chat } => {
+                let telegram = Telegram::new(client.clone(), &token, chat, unit);
+                tokio::spawn(telegram.clone().poll(requests.clone()));
+                Box::new(telegram)
+            }
+            Sink::Command { command } => Box::new(Command::new(command, unit)),
+        });
+    }
+
+    let mut watcher = Watcher::new(&options, stall, targets, timer);
+
+    loop {
+        let due = watcher.disconnect_due;
+
+        let alert = tokio::select! {
+            event = receiver.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                watcher.update(&event)
+            }
+            _ = tokio::time::sleep_until(due.unwrap_or_else(Instant::now)), if due.is_some() => {
+                Some(watcher.disconnected())
+            }
+            Some(alert) = rules.recv() => Some(match (alert.kind, alert.probe) {
+                (alerts::Kind::LowBattery { percent }, Some(probe)) => {
+                    AlertEvent::LowBattery { probe, percent }
+                }
+                _ => AlertEvent::Rule(alert),
+            }),
+            Some(telegram) = status_requests.recv() => {
+                if let Err(err) = telegram.send(&watcher.status(unit)).await {
+                    tracing::warn!("unable to answer Telegram status request: {err}");
+                }
+
+                None
+            }
+        };
+
+        let Some(alert) = alert else {
+            continue;
+        };
+
+        tracing::info!(alert = ?alert, "sending notifications");
+
+        let deliveries = notifiers
+            .iter()
+            .map(|notifier| notifier.notify(alert.clone()));
+
+        let results = futures::future::join_all(deliveries).await;
+
+        for err in results.into_iter().filter_map(Result::err) {
+            tracing::warn!("unable to send notification: {err}");
+        }
+    }
+
+    Ok(())
+}
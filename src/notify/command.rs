@@ -0,0 +1,40 @@
+use anyhow::Context;
+use async_trait::async_trait;
+
+use super::{AlertEvent, Notifier, REQUEST_TIMEOUT};
+use crate::units::Unit;
+
+/// Runs a shell command with the alert in the `MEATER_ALERT`, `MEATER_TITLE` and
+/// `MEATER_MESSAGE` environment variables.
+pub struct Command {
+    command: String,
+    unit: Unit,
+}
+
+impl Command {
+    pub fn new(command: String, unit: Unit) -> Self {
+        Self { command, unit }
+    }
+}
+
+#[async_trait]
+impl Notifier for Command {
+    async fn notify(&self, event: AlertEvent) -> anyhow::Result<()> {
+        let status = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("MEATER_ALERT", event.kind())
+            .env("MEATER_TITLE", event.title())
+            .env("MEATER_MESSAGE", event.message(self.unit))
+            .kill_on_drop(true)
+            .status();
+
+        let status = tokio::time::timeout(REQUEST_TIMEOUT, status)
+            .await
+            .with_context(|| format!("`{}` timed out", self.command))?
+            .with_context(|| format!("unable to run `{}`", self.command))?;
+
+        anyhow::ensure!(status.success(), "`{}` failed with {status}", self.command);
+        Ok(())
+    }
+}
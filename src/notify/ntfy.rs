@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+
+use super::{AlertEvent, Notifier};
+use crate::units::Unit;
+
+/// Publishes the message as body and the title in the `Title` header to an ntfy topic.
+pub struct Ntfy {
+    client: reqwest::Client,
+    url: String,
+    unit: Unit,
+}
+
+impl Ntfy {
+    pub fn new(client: reqwest::Client, url: String, unit: Unit) -> Self {
+        Self { client, url, unit }
+    }
+}
+
+#[async_trait]
+impl Notifier for Ntfy {
+    async fn notify(&self, event: AlertEvent) -> anyhow::Result<()> {
+        self.client
+            .post(&self.url)
+            .header("Title", event.title())
+            .body(event.message(self.unit))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
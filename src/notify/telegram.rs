@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use super::{AlertEvent, Notifier, REQUEST_TIMEOUT};
+use crate::units::Unit;
+
+/// Time the Telegram API holds a request for updates open waiting for messages.
+const POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time to wait before asking Telegram for updates again after an error.
+const POLL_RETRY: Duration = Duration::from_secs(5);
+
+/// Message received by the bot.
+#[derive(Deserialize)]
+struct Message {
+    chat: Chat,
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+/// Update received by the bot.
+#[derive(Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<Message>,
+}
+
+/// Response of the Telegram API to `getUpdates`.
+#[derive(Deserialize)]
+struct Updates {
+    result: Vec<Update>,
+}
+
+/// Telegram bot talking to a single chat.
+#[derive(Clone)]
+pub struct Telegram {
+    client: reqwest::Client,
+    /// Base URL of all API methods, including the token, which is why errors are stripped of the
+    /// URL before being passed on.
+    url: String,
+    chat: i64,
+    unit: Unit,
+}
+
+impl Telegram {
+    pub fn new(client: reqwest::Client, token: &str, chat: i64, unit: Unit) -> Self {
+        Self {
+            client,
+            url: format!("https://api.telegram.org/bot{token}"),
+            chat,
+            unit,
+        }
+    }
+
+    /// Send `text` to the chat.
+    pub async fn send(&self, text: &str) -> anyhow::Result<()> {
+        self.client
+            .post(format!("{}/sendMessage", self.url))
+            .json(&json!({ "chat_id": self.chat, "text": text }))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(reqwest::Error::without_url)?;
+
+        Ok(())
+    }
+
+    /// Return the updates following `offset`, waiting for them up to [`POLL_TIMEOUT`].
+    async fn updates(&self, offset: i64) -> anyhow::Result<Vec<Update>> {
+        let updates: Updates = self
+            .client
+            .get(format!("{}/getUpdates", self.url))
+            .query(&[
+                ("offset", offset),
+                ("timeout", POLL_TIMEOUT.as_secs() as i64),
+            ])
+            .timeout(POLL_TIMEOUT + REQUEST_TIMEOUT)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(reqwest::Error::without_url)?
+            .json()
+            .await
+            .map_err(reqwest::Error::without_url)
+            .context("invalid response to getUpdates")?;
+
+        Ok(updates.result)
+    }
+
+    /// Forward this bot to `requests` for each `/status` command received from the chat.
+    pub async fn poll(self, requests: mpsc::Sender<Telegram>) {
+        let mut offset = 0;
+
+        loop {
+            let updates = match self.updates(offset).await {
+                Ok(updates) => updates,
+                Err(err) => {
+                    tracing::warn!("unable to get Telegram updates: {err}");
+                    tokio::time::sleep(POLL_RETRY).await;
+                    continue;
+                }
+            };
+
+            for update in updates {
+                offset = offset.max(update.update_id + 1);
+
+                let Some(message) = update.message else {
+                    continue;
+                };
+
+                // Commands in groups may be addressed as /status@bot.
+                let command = message
+                    .text
+                    .as_deref()
+                    .and_then(|text| text.split_whitespace().next())
+                    .and_then(|command| command.split('@').next());
+
+                if message.chat.id == self.chat
+                    && command == Some("/status")
+                    && requests.send(self.clone()).await.is_err()
+                {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for Telegram {
+    async fn notify(&self, event: AlertEvent) -> anyhow::Result<()> {
+        self.send(&format!("{}: {}", event.title(), event.message(self.unit)))
+            .await
+    }
+}
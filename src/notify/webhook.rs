@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{AlertEvent, Notifier};
+use crate::units::Unit;
+
+/// Posts a JSON object with `type`, `title` and `message` to a URL.
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    unit: Unit,
+}
+
+impl Webhook {
+    pub fn new(client: reqwest::Client, url: String, unit: Unit) -> Self {
+        Self { client, url, unit }
+    }
+}
+
+#[async_trait]
+impl Notifier for Webhook {
+    async fn notify(&self, event: AlertEvent) -> anyhow::Result<()> {
+        let body = json!({
+            "type": event.kind(),
+            "title": event.title(),
+            "message": event.message(self.unit),
+        });
+
+        self.client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+//! Wired DS18B20 sensors read through the 1-Wire bus of the Raspberry Pi.
+//!
+//! With the `w1-gpio` overlay loaded, the kernel exposes each sensor as directory starting with
+//! the family code `28-` below `/sys/bus/w1/devices`, its `w1_slave` file containing the checked
+//! raw reading and the temperature in milli degrees Celsius. Sensors get their number in the order
+//! they were found, starting from zero.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::sync::mpsc;
+
+use crate::client::{Event, State};
+use crate::source::ProbeSource;
+
+/// Directory the kernel lists 1-Wire devices in.
+pub const DEVICES: &str = "/sys/bus/w1/devices";
+
+/// Prefix of the directories of DS18B20 sensors.
+const FAMILY: &str = "28-";
+
+/// Parse the content of a `w1_slave` file into a temperature in °C, `None` if the CRC check
+/// failed.
+pub fn parse(content: &str) -> Option<f32> {
+    let mut lines = content.lines();
+
+    if !lines.next()?.trim_end().ends_with("YES") {
+        return None;
+    }
+
+    let (_, millis) = lines.next()?.split_once("t=")?;
+    let millis: i32 = millis.trim().parse().ok()?;
+
+    Some(millis as f32 / 1000.0)
+}
+
+/// Read all sensors in `directory` and return their ids with the temperature, `None` for failed
+/// readings.
+fn read(directory: &Path) -> anyhow::Result<Vec<(String, Option<f32>)>> {
+    let mut ids = std::fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(FAMILY))
+        .collect::<Vec<_>>();
+
+    ids.sort();
+
+    let readings = ids
+        .into_iter()
+        .map(|id| {
+            let temperature = std::fs::read_to_string(directory.join(&id).join("w1_slave"))
+                .ok()
+                .and_then(|content| parse(&content));
+
+            if temperature.is_none() {
+                tracing::warn!(id, "unable to read DS18B20");
+            }
+
+            (id, temperature)
+        })
+        .collect();
+
+    Ok(readings)
+}
+
+/// Polls DS18B20 sensors and emits [`Event`]s for them.
+pub struct OneWire {
+    sender: mpsc::Sender<Event>,
+    directory: PathBuf,
+    interval: Duration,
+    ambient_probe: Option<usize>,
+}
+
+impl OneWire {
+    pub fn new() -> (Self, mpsc::Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel(16);
+
+        let onewire = Self {
+            sender,
+            directory: PathBuf::from(DEVICES),
+            interval: Duration::from_secs(2),
+            ambient_probe: None,
+        };
+
+        (onewire, receiver)
+    }
+
+    /// Look for sensors in `directory` instead of [`DEVICES`].
+    pub fn directory(mut self, directory: PathBuf) -> Self {
+        self.directory = directory;
+        self
+    }
+
+    /// Read the sensors every `interval`.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Report the sensor `probe`, numbered from zero, as ambient temperature of all others instead
+    /// of as a probe.
+    pub fn ambient_probe(mut self, probe: usize) -> Self {
+        self.ambient_probe = Some(probe);
+        self
+    }
+
+    /// Poll the sensors until the receiver is dropped.
+    pub async fn run(self) -> anyhow::Result<()> {
+        let mut indices = HashMap::new();
+        let mut interval = tokio::time::interval(self.interval);
+        let mut connected = false;
+
+        self.sender.send(Event::State(State::Disconnected)).await?;
+
+        loop {
+            interval.tick().await;
+
+            // Each reading blocks for the conversion time of the sensor.
+            let directory = self.directory.clone();
+            let readings = tokio::task::spawn_blocking(move || read(&directory)).await?;
+
+            let readings = readings.unwrap_or_else(|err| {
+                tracing::warn!("unable to list 1-Wire devices: {err}");
+                Vec::new()
+            });
+
+            if connected == readings.is_empty() {
+                connected = !connected;
+                let state = if connected {
+                    State::Connected
+                } else {
+                    State::Disconnected
+                };
+
+                self.sender.send(Event::State(state)).await?;
+            }
+
+            let temperatures = readings
+                .into_iter()
+                .map(|(id, temperature)| {
+                    let next = indices.len();
+                    let probe = *indices.entry(id).or_insert(next);
+                    (probe, temperature)
+                })
+                .collect::<BTreeMap<_, _>>();
+
+            let ambient = self
+                .ambient_probe
+                .and_then(|probe| *temperatures.get(&probe)?);
+
+            for (probe, tip) in temperatures {
+                let Some(tip) = tip else {
+                    continue;
+                };
+
+                if Some(probe) == self.ambient_probe {
+                    continue;
+                }
+
+                self.sender
+                    .send(Event::Temperature {
+                        probe,
+                        tip,
+                        ambient: ambient.unwrap_or(tip),
+                    })
+                    .await?;
+            }
+        }
+    }
+}
+
+impl ProbeSource for OneWire {
+    fn name(&self) -> &'static str {
+        "DS18B20"
+    }
+
+    fn run(self: Box<Self>) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(OneWire::run(*self))
+    }
+}
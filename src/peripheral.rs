@@ -0,0 +1,314 @@
+//! GATT server re-broadcasting the temperatures as standard Environmental Sensing Service.
+//!
+//! The service is registered with BlueZ over D-Bus along with an advertisement, turning the Pi into
+//! a range extender any app or device reading the Environmental Sensing Service can connect to.
+//! Tip and ambient of each probe are separate Temperature characteristics, told apart by their
+//! user description like `Probe 1 tip`, which can be read and notify changes.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::channel::{MatchingReceiver, Sender};
+use dbus::message::{MatchRule, SignalArgs};
+use dbus::nonblock::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged;
+use dbus::nonblock::{Proxy, SyncConnection};
+use dbus::Path;
+use dbus_crossroads::{Crossroads, IfaceBuilder};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+/// Environmental Sensing Service.
+const SERVICE_UUID: &str = "0000181a-0000-1000-8000-00805f9b34fb";
+
+/// Temperature characteristic, a little-endian `i16` in 0.01 °C.
+const TEMPERATURE_UUID: &str = "00002a6e-0000-1000-8000-00805f9b34fb";
+
+/// Characteristic User Description descriptor.
+const USER_DESCRIPTION_UUID: &str = "00002901-0000-1000-8000-00805f9b34fb";
+
+/// Temperature telling the value is not known, e.g. before the first reading.
+const UNKNOWN: i16 = i16::MIN;
+
+/// Object path of the application registered with BlueZ.
+const APPLICATION_PATH: &str = "/org/meater/peripheral";
+
+/// Object path of the only service of the application.
+const SERVICE_PATH: &str = "/org/meater/peripheral/service0";
+
+/// Object path of the advertisement registered with BlueZ.
+const ADVERTISEMENT_PATH: &str = "/org/meater/advertisement0";
+
+/// Time after which BlueZ is considered unresponsive.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// GATT server settings.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Serve the temperatures to other Bluetooth devices.
+    pub enabled: bool,
+    /// Name of the Bluetooth adapter to advertise with.
+    pub adapter: String,
+    /// Name advertised to other devices.
+    pub name: String,
+    /// Number of probes served, characteristics cannot be added later on.
+    pub probes: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            adapter: "hci0".to_string(),
+            name: "MEATER Relay".to_string(),
+            probes: 1,
+        }
+    }
+}
+
+/// Latest temperatures in 0.01 °C, tip and ambient of each probe in turn.
+type Values = Arc<Mutex<Vec<i16>>>;
+
+/// A Temperature characteristic.
+struct Characteristic {
+    index: usize,
+    values: Values,
+}
+
+impl Characteristic {
+    fn value(&self) -> Vec<u8> {
+        let value = self
+            .values
+            .lock()
+            .ok()
+            .and_then(|values| values.get(self.index).copied())
+            .unwrap_or(UNKNOWN);
+
+        value.to_le_bytes().to_vec()
+    }
+}
+
+/// The user description of a characteristic.
+struct Description {
+    characteristic: Path<'static>,
+    text: String,
+}
+
+fn characteristic_path(index: usize) -> Path<'static> {
+    Path::from(format!("{SERVICE_PATH}/char{index}"))
+}
+
+/// Return `celsius` as characteristic value.
+fn encode(celsius: f32) -> i16 {
+    (celsius * 100.0)
+        .round()
+        .clamp(f32::from(i16::MIN + 1), f32::from(i16::MAX)) as i16
+}
+
+/// Register the objects of the service and the advertisement with `crossroads`.
+fn register(crossroads: &mut Crossroads, options: &Options, values: &Values) {
+    let service = crossroads.register("org.bluez.GattService1", |b: &mut IfaceBuilder<()>| {
+        b.property("UUID").get(|_, _| Ok(SERVICE_UUID.to_string()));
+        b.property("Primary").get(|_, _| Ok(true));
+    });
+
+    let characteristic = crossroads.register(
+        "org.bluez.GattCharacteristic1",
+        |b: &mut IfaceBuilder<Characteristic>| {
+            b.property("UUID")
+                .get(|_, _| Ok(TEMPERATURE_UUID.to_string()));
+            b.property("Service")
+                .get(|_, _| Ok(Path::from(SERVICE_PATH)));
+            b.property("Flags")
+                .get(|_, _| Ok(vec!["read".to_string(), "notify".to_string()]));
+            b.property("Value")
+                .get(|_, characteristic| Ok(characteristic.value()));
+            b.method(
+                "ReadValue",
+                ("options",),
+                ("value",),
+                |_, characteristic, (_,): (PropMap,)| Ok((characteristic.value(),)),
+            );
+            // Changes are always signalled, BlueZ only forwards them to subscribed devices.
+            b.method("StartNotify", (), (), |_, _, (): ()| Ok(()));
+            b.method("StopNotify", (), (), |_, _, (): ()| Ok(()));
+        },
+    );
+
+    let description = crossroads.register(
+        "org.bluez.GattDescriptor1",
+        |b: &mut IfaceBuilder<Description>| {
+            b.property("UUID")
+                .get(|_, _| Ok(USER_DESCRIPTION_UUID.to_string()));
+            b.property("Characteristic")
+                .get(|_, description| Ok(description.characteristic.clone()));
+            b.property("Flags").get(|_, _| Ok(vec!["read".to_string()]));
+            b.method(
+                "ReadValue",
+                ("options",),
+                ("value",),
+                |_, description, (_,): (PropMap,)| Ok((description.text.as_bytes().to_vec(),)),
+            );
+        },
+    );
+
+    let advertisement = crossroads.register(
+        "org.bluez.LEAdvertisement1",
+        |b: &mut IfaceBuilder<String>| {
+            b.property("Type").get(|_, _| Ok("peripheral".to_string()));
+            b.property("ServiceUUIDs")
+                .get(|_, _| Ok(vec![SERVICE_UUID.to_string()]));
+            b.property("LocalName").get(|_, name| Ok(name.clone()));
+            b.method("Release", (), (), |_, _, (): ()| {
+                tracing::info!("advertisement released by BlueZ");
+                Ok(())
+            });
+        },
+    );
+
+    let object_manager = crossroads.object_manager();
+    crossroads.insert(APPLICATION_PATH, &[object_manager], ());
+    crossroads.insert(SERVICE_PATH, &[service], ());
+
+    for index in 0..options.probes * 2 {
+        let path = characteristic_path(index);
+        let kind = if index % 2 == 0 { "tip" } else { "ambient" };
+
+        crossroads.insert(
+            format!("{path}/desc0"),
+            &[description],
+            Description {
+                characteristic: path.clone(),
+                text: format!("Probe {} {kind}", index / 2 + 1),
+            },
+        );
+
+        crossroads.insert(
+            path,
+            &[characteristic],
+            Characteristic {
+                index,
+                values: values.clone(),
+            },
+        );
+    }
+
+    crossroads.insert(ADVERTISEMENT_PATH, &[advertisement], options.name.clone());
+}
+
+/// Signal the new value of the characteristic `index`.
+fn notify(connection: &SyncConnection, index: usize, value: i16) -> anyhow::Result<()> {
+    let mut changed = PropMap::new();
+    changed.insert(
+        "Value".to_string(),
+        Variant(Box::new(value.to_le_bytes().to_vec()) as Box<dyn RefArg>),
+    );
+
+    let signal = PropertiesPropertiesChanged {
+        interface_name: "org.bluez.GattCharacteristic1".to_string(),
+        changed_properties: changed,
+        invalidated_properties: Vec::new(),
+    };
+
+    connection
+        .send(signal.to_emit_message(&characteristic_path(index)))
+        .map_err(|_| anyhow!("unable to send notification"))?;
+
+    Ok(())
+}
+
+/// Serve the temperatures received on `receiver` as GATT server advertised by the adapter.
+pub async fn run(
+    options: Options,
+    mut receiver: mpsc::Receiver<meater::Event>,
+) -> anyhow::Result<()> {
+    let (resource, connection) =
+        dbus_tokio::connection::new_system_sync().context("unable to connect to the system bus")?;
+
+    // Only returns once the connection to the bus is lost.
+    let mut lost = tokio::spawn(resource);
+
+    let values: Values = Arc::new(Mutex::new(vec![UNKNOWN; options.probes * 2]));
+
+    let mut crossroads = Crossroads::new();
+    register(&mut crossroads, &options, &values);
+
+    connection.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(move |message, connection| {
+            let _ = crossroads.handle_message(message, connection);
+            true
+        }),
+    );
+
+    let adapter = Proxy::new(
+        "org.bluez",
+        format!("/org/bluez/{}", options.adapter),
+        TIMEOUT,
+        connection.clone(),
+    );
+
+    adapter
+        .method_call::<(), _, _, _>(
+            "org.bluez.GattManager1",
+            "RegisterApplication",
+            (Path::from(APPLICATION_PATH), PropMap::new()),
+        )
+        .await
+        .context("unable to register the GATT application")?;
+
+    adapter
+        .method_call::<(), _, _, _>(
+            "org.bluez.LEAdvertisingManager1",
+            "RegisterAdvertisement",
+            (Path::from(ADVERTISEMENT_PATH), PropMap::new()),
+        )
+        .await
+        .context("unable to register the advertisement")?;
+
+    tracing::info!(
+        name = options.name,
+        "serving temperatures as Environmental Sensing Service"
+    );
+
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                let changed = match event {
+                    meater::Event::Temperature { probe, tip, ambient } => {
+                        vec![(probe * 2, encode(tip)), (probe * 2 + 1, encode(ambient))]
+                    }
+                    // Stale values must not be served as current ones.
+                    meater::Event::State(meater::State::Disconnected) => {
+                        (0..options.probes * 2).map(|index| (index, UNKNOWN)).collect()
+                    }
+                    _ => continue,
+                };
+
+                for (index, value) in changed {
+                    let Ok(mut values) = values.lock() else {
+                        break;
+                    };
+
+                    let Some(current) = values.get_mut(index) else {
+                        continue;
+                    };
+
+                    if std::mem::replace(current, value) != value {
+                        drop(values);
+                        notify(&connection, index, value)?;
+                    }
+                }
+            }
+            err = &mut lost => return Err(anyhow!("lost connection to the system bus: {err:?}")),
+        }
+    }
+
+    Ok(())
+}
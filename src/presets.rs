@@ -0,0 +1,76 @@
+//! Doneness presets setting the target temperature.
+//!
+//! Temperatures follow common doneness levels for beef and the USDA safe minimum internal
+//! temperatures for everything else.
+
+use serde::Deserialize;
+
+/// Kind of meat and doneness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    BeefRare,
+    BeefMediumRare,
+    BeefMedium,
+    BeefWellDone,
+    Poultry,
+    Pork,
+    Fish,
+    GroundMeat,
+}
+
+/// All presets in the order they are cycled through.
+const ALL: [Preset; 8] = [
+    Preset::BeefRare,
+    Preset::BeefMediumRare,
+    Preset::BeefMedium,
+    Preset::BeefWellDone,
+    Preset::Poultry,
+    Preset::Pork,
+    Preset::Fish,
+    Preset::GroundMeat,
+];
+
+impl Preset {
+    /// Target tip temperature in °C.
+    pub fn celsius(self) -> f32 {
+        match self {
+            Self::BeefRare => 52.0,
+            Self::BeefMediumRare => 57.0,
+            Self::BeefMedium => 63.0,
+            Self::BeefWellDone => 71.0,
+            Self::Poultry => 74.0,
+            Self::Pork => 63.0,
+            Self::Fish => 63.0,
+            Self::GroundMeat => 71.0,
+        }
+    }
+
+    /// Short name fitting the display.
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Self::BeefRare => "RARE",
+            Self::BeefMediumRare => "MR",
+            Self::BeefMedium => "MED",
+            Self::BeefWellDone => "WELL",
+            Self::Poultry => "POUL",
+            Self::Pork => "PORK",
+            Self::Fish => "FISH",
+            Self::GroundMeat => "GRND",
+        }
+    }
+
+    /// Return the preset following `self`, wrapping around after the last one.
+    pub fn next(self) -> Self {
+        let index = ALL
+            .iter()
+            .position(|preset| *preset == self)
+            .unwrap_or_default();
+        ALL[(index + 1) % ALL.len()]
+    }
+
+    /// Return the first preset.
+    pub fn first() -> Self {
+        ALL[0]
+    }
+}
@@ -0,0 +1,175 @@
+//! Thermometers the events are read from, selectable at runtime.
+
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use meater::ProbeSource;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::config::Config;
+
+/// Thermometer selectable at runtime.
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// MEATER probes and blocks via Bluetooth.
+    #[default]
+    Meater,
+    /// Inkbird IBT-2X and IBT-4XS grill thermometers via Bluetooth.
+    Inkbird,
+    /// Combustion Inc. Predictive Thermometers via Bluetooth advertisements.
+    Combustion,
+    /// Wired DS18B20 sensors on the 1-Wire bus.
+    Ds18b20,
+    /// K-type thermocouple connected through a MAX31855 on the SPI bus.
+    #[cfg(feature = "device")]
+    Max31855,
+    /// Simulated probe going through a whole cook.
+    Simulated,
+    /// Notifications recorded from MEATER probes.
+    Replay,
+}
+
+/// Thermometer settings.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Options {
+    pub kind: Kind,
+    /// Probe of an Inkbird or DS18B20 sensor, numbered from one, clipped to the grill and reported
+    /// as ambient temperature of all others.
+    pub ambient_probe: Option<usize>,
+    /// Chip select of the MAX31855 on the first SPI bus, 0 or 1.
+    pub spi_select: u8,
+    /// Maximum noise in °C added to simulated readings.
+    pub noise: f32,
+    /// Average number of simulated disconnects per hour of the cook.
+    pub disconnects_per_hour: f32,
+    /// Factor the simulated or replayed cook runs faster than real time.
+    pub speed: f32,
+    /// Recording of MEATER notifications to replay.
+    pub replay: Option<PathBuf>,
+    /// Offsets of the MEATER probes, the n-th applies to the n-th probe.
+    pub calibration: Vec<meater::Calibration>,
+    /// Battery percentage below which a probe counts as low on battery, shown as warning and
+    /// raising an alert.
+    pub low_battery: u16,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            kind: Kind::default(),
+            ambient_probe: None,
+            spi_select: 0,
+            noise: 0.3,
+            disconnects_per_hour: 0.5,
+            speed: 1.0,
+            replay: None,
+            calibration: Vec::new(),
+            low_battery: 20,
+        }
+    }
+}
+
+/// Create the thermometer selected in `config` and the receiver of its events.
+pub fn create(
+    config: &Config,
+) -> anyhow::Result<(Box<dyn ProbeSource>, mpsc::Receiver<meater::Event>)> {
+    let source: (Box<dyn ProbeSource>, _) = match config.probe.kind {
+        Kind::Meater => {
+            let (client, receiver) = meater::Client::new();
+            let mut client = client
+                .adapter(config.bluetooth.adapter.clone())
+                .backoff(config.bluetooth.backoff())
+                .stale_after(config.bluetooth.stale_after())
+                .scan_timeout(config.bluetooth.scan_timeout())
+                .unfiltered_after(config.bluetooth.unfiltered_after())
+                .names(config.bluetooth.names.clone())
+                .passive(config.bluetooth.passive)
+                .calibrations(config.probe.calibration.clone())
+                .low_battery(config.probe.low_battery);
+
+            if let Some(address) = config.bluetooth.device {
+                client = client.device(address);
+            }
+
+            if let Some(path) = config.bluetooth.last_device.clone() {
+                client = client.last_device(path);
+            }
+
+            if let Some(path) = config.bluetooth.record.clone() {
+                client = client.record(path);
+            }
+
+            #[cfg(feature = "esphome")]
+            if let Some(address) = config.bluetooth.esphome_proxy.clone() {
+                let password = config.bluetooth.esphome_password.clone();
+                client = client.esphome_proxy(address, password);
+            }
+
+            (Box::new(client), receiver)
+        }
+        Kind::Inkbird => {
+            let (inkbird, receiver) = meater::inkbird::Inkbird::new();
+            let mut inkbird = inkbird
+                .adapter(config.bluetooth.adapter.clone())
+                .backoff(config.bluetooth.backoff());
+
+            if let Some(probe) = config.probe.ambient_probe {
+                inkbird = inkbird.ambient_probe(probe.saturating_sub(1));
+            }
+
+            (Box::new(inkbird), receiver)
+        }
+        Kind::Combustion => {
+            let (combustion, receiver) = meater::combustion::Combustion::new();
+            let combustion = combustion
+                .adapter(config.bluetooth.adapter.clone())
+                .backoff(config.bluetooth.backoff());
+
+            (Box::new(combustion), receiver)
+        }
+        Kind::Ds18b20 => {
+            let (mut onewire, receiver) = meater::onewire::OneWire::new();
+
+            if let Some(probe) = config.probe.ambient_probe {
+                onewire = onewire.ambient_probe(probe.saturating_sub(1));
+            }
+
+            (Box::new(onewire), receiver)
+        }
+        #[cfg(feature = "device")]
+        Kind::Max31855 => {
+            let (max31855, receiver) = crate::max31855::Max31855::new(config.probe.spi_select)?;
+            (Box::new(max31855), receiver)
+        }
+        Kind::Simulated => {
+            let (simulated, receiver) = meater::simulated::Simulated::new();
+            let simulated = simulated
+                .noise(config.probe.noise)
+                .disconnects_per_hour(config.probe.disconnects_per_hour)
+                .speed(config.probe.speed)
+                .low_battery(config.probe.low_battery);
+
+            (Box::new(simulated), receiver)
+        }
+        Kind::Replay => {
+            let path = config
+                .probe
+                .replay
+                .clone()
+                .ok_or_else(|| anyhow!("no recording to replay given"))?;
+
+            let (replay, receiver) = meater::replay::Replay::new(path);
+            let replay = replay
+                .speed(config.probe.speed)
+                .calibrations(config.probe.calibration.clone())
+                .low_battery(config.probe.low_battery);
+
+            (Box::new(replay), receiver)
+        }
+    };
+
+    Ok(source)
+}
@@ -0,0 +1,212 @@
+//! Constants and conversion functions of the MEATER BLE protocols.
+//!
+//! Devices are told apart by the service they advertise, which determines the [`Layout`] of their
+//! characteristics and the [`Payload`] of their temperature notifications.
+
+use std::fmt;
+use std::str::FromStr;
+
+use uuid::{uuid, Uuid};
+
+/// Name MEATER probes advertise themselves with.
+pub const NAME: &str = "MEATER";
+
+/// Service advertised by MEATER probes.
+pub const SERVICE_UUID: Uuid = uuid!("a75cc7fc-c956-488f-ac2a-2dbc08b63a04");
+
+/// Characteristic notifying the battery level.
+pub const BATTERY_UUID: Uuid = uuid!("2adb4877-68d8-4884-bd3c-d83853bf27b8");
+
+/// Characteristic notifying the raw tip and ambient sensor values.
+pub const TEMPERATURE_UUID: Uuid = uuid!("7edda774-045e-4bbf-909b-45d1991a2876");
+
+/// Characteristic of the standard Device Information service holding the firmware revision.
+pub const FIRMWARE_REVISION_UUID: Uuid = uuid!("00002a26-0000-1000-8000-00805f9b34fb");
+
+/// Characteristic of the standard Device Information service holding the serial number.
+pub const SERIAL_NUMBER_UUID: Uuid = uuid!("00002a25-0000-1000-8000-00805f9b34fb");
+
+/// Encoding of temperature notifications.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// Eight bytes with the raw tip value and the raw ambient sensor values the ambient
+    /// temperature is computed from.
+    Classic,
+    /// Raw little-endian values of the internal sensors from the tip towards the handle followed
+    /// by the raw ambient value, as sent by the MEATER 2 Plus. No layout uses it yet, the
+    /// services and characteristics of the MEATER 2 Plus are not known.
+    Sensors,
+}
+
+/// Characteristics a device notifies the probe values on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// Service the device advertises.
+    pub service: Uuid,
+    pub temperature: Uuid,
+    pub battery: Uuid,
+    pub payload: Payload,
+}
+
+impl Layout {
+    /// Decode the temperature notification `value` into tip and ambient temperature in °C.
+    pub fn decode(&self, value: &[u8]) -> Option<(f32, f32)> {
+        match self.payload {
+            Payload::Classic => decode_classic(value),
+            Payload::Sensors => decode_sensors(value),
+        }
+    }
+}
+
+/// Layout of a probe connected directly.
+pub const PROBE: Layout = Layout {
+    service: SERVICE_UUID,
+    temperature: TEMPERATURE_UUID,
+    battery: BATTERY_UUID,
+    payload: Payload::Classic,
+};
+
+/// Pattern the advertised name of a probe is matched against, for firmware variants and renamed
+/// probes.
+///
+/// Parsed from `prefix:<text>` for names starting with the text, `regex:<expression>` for names
+/// matching the regular expression and any other text for exactly that name.
+#[derive(Clone, Debug)]
+pub enum NamePattern {
+    Exact(String),
+    Prefix(String),
+    Regex(regex::Regex),
+}
+
+impl NamePattern {
+    /// Return `true` if `name` matches the pattern.
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Exact(exact) => name == exact,
+            Self::Prefix(prefix) => name.starts_with(prefix.as_str()),
+            Self::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+impl FromStr for NamePattern {
+    type Err = regex::Error;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        if let Some(prefix) = pattern.strip_prefix("prefix:") {
+            Ok(Self::Prefix(prefix.to_string()))
+        } else if let Some(regex) = pattern.strip_prefix("regex:") {
+            Ok(Self::Regex(regex::Regex::new(regex)?))
+        } else {
+            Ok(Self::Exact(pattern.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(exact) => f.write_str(exact),
+            Self::Prefix(prefix) => write!(f, "prefix:{prefix}"),
+            Self::Regex(regex) => write!(f, "regex:{regex}"),
+        }
+    }
+}
+
+/// Return the layout of a device named `name` advertising `services`.
+///
+/// Probes are recognized by their service or a name matching any of `names`, so with no names
+/// only the service counts.
+pub fn layout(name: Option<&str>, services: &[Uuid], names: &[NamePattern]) -> Option<Layout> {
+    let named = name.is_some_and(|name| names.iter().any(|pattern| pattern.matches(name)));
+
+    (named || services.contains(&SERVICE_UUID)).then_some(PROBE)
+}
+
+/// Return the layout notifying on the characteristic `uuid`.
+pub fn characteristic_layout(uuid: Uuid) -> Option<Layout> {
+    [PROBE]
+        .into_iter()
+        .find(|layout| layout.temperature == uuid || layout.battery == uuid)
+}
+
+/// Combine two bytes into a `u16`.
+pub fn to_u16(msb: u8, lsb: u8) -> u16 {
+    u16::from(msb) * 256 + u16::from(lsb)
+}
+
+/// Compute the raw ambient value from the raw tip value `tip` and the raw ambient sensor values
+/// `ra` and `oa`.
+///
+/// The ambient value is never below the tip value. Returns `None` if the sensor values yield an
+/// ambient value out of range.
+pub fn ambient(tip: u16, ra: u16, oa: u16) -> Option<u16> {
+    let offset = ((i64::from(ra) - i64::from(oa.min(48))) * 16 * 589 / 1487).max(0);
+    u16::try_from(i64::from(tip) + offset).ok()
+}
+
+/// Convert a raw temperature value to degree Celsius.
+pub fn to_degree_celsius(value: u16) -> f32 {
+    (f32::from(value) + 8.0) / 16.0
+}
+
+/// Decode a string characteristic of the Device Information service, possibly padded with NUL
+/// bytes.
+///
+/// Empty values and invalid UTF-8 yield `None`.
+pub fn decode_string(value: &[u8]) -> Option<String> {
+    let value = std::str::from_utf8(value)
+        .ok()?
+        .trim_end_matches('\0')
+        .trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Decode a battery notification into the battery level in percent.
+///
+/// The probe reports the level in tenths as little-endian `u16`. Any level above full, as reported
+/// right out of the charger but also by garbled payloads, is clamped to 100. Payloads shorter than
+/// two bytes and levels overflowing `u16` in percent yield `None`.
+pub fn decode_battery(value: &[u8]) -> Option<u16> {
+    let [lsb, msb, ..] = *value else {
+        return None;
+    };
+
+    to_u16(msb, lsb)
+        .checked_mul(10)
+        .map(|percent| percent.min(100))
+}
+
+/// Decode a [`Payload::Classic`] notification.
+///
+/// Returns `None` if the length is wrong or the ambient value is out of range.
+pub fn decode_classic(value: &[u8]) -> Option<(f32, f32)> {
+    if value.len() != 8 {
+        return None;
+    }
+
+    let tip = to_u16(value[1], value[0]);
+    let ra = to_u16(value[3], value[2]);
+    let oa = to_u16(value[5], value[4]);
+    let ambient = ambient(tip, ra, oa)?;
+
+    Some((to_degree_celsius(tip), to_degree_celsius(ambient)))
+}
+
+/// Decode a [`Payload::Sensors`] notification.
+///
+/// The lowest internal sensor is the core of the meat and reported as tip.
+pub fn decode_sensors(value: &[u8]) -> Option<(f32, f32)> {
+    if value.len() < 4 || !value.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let mut values = value
+        .chunks_exact(2)
+        .map(|bytes| to_degree_celsius(to_u16(bytes[1], bytes[0])));
+
+    let ambient = values.next_back()?;
+    let tip = values.reduce(f32::min)?;
+
+    Some((tip, ambient))
+}
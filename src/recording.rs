@@ -0,0 +1,88 @@
+//! Recordings of raw notifications.
+//!
+//! Each line holds the milliseconds since the recording started, the probe number, the UUID of the
+//! characteristic and the payload in hex, separated by spaces.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use uuid::Uuid;
+
+use crate::error::MeaterError;
+
+/// A recorded notification.
+pub(crate) struct Record {
+    /// Time since the recording started.
+    pub(crate) offset: Duration,
+    pub(crate) probe: usize,
+    pub(crate) uuid: Uuid,
+    pub(crate) value: Vec<u8>,
+}
+
+impl Record {
+    /// Parse a line of a recording.
+    pub(crate) fn parse(line: &str) -> anyhow::Result<Self> {
+        let mut fields = line.split_whitespace();
+        let mut field = || fields.next().ok_or_else(|| anyhow!("line is incomplete"));
+
+        let offset = Duration::from_millis(field()?.parse()?);
+        let probe = field()?.parse()?;
+        let uuid = field()?.parse()?;
+        let hex = field()?;
+
+        let value = (0..hex.len())
+            .step_by(2)
+            .map(|index| {
+                let byte = hex.get(index..index + 2).unwrap_or_default();
+                u8::from_str_radix(byte, 16).map_err(|_| anyhow!("invalid payload {hex}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            offset,
+            probe,
+            uuid,
+            value,
+        })
+    }
+}
+
+/// Appends notifications of all probes to a file.
+pub(crate) struct Recorder {
+    file: Mutex<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub(crate) fn create(path: &Path) -> Result<Self, MeaterError> {
+        let file = File::create(path).map_err(|source| MeaterError::Record {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        tracing::info!("recording notifications to {}", path.display());
+
+        Ok(Self {
+            file: Mutex::new(file),
+            start: Instant::now(),
+        })
+    }
+
+    /// Append the notification `value` of characteristic `uuid` of `probe`.
+    pub(crate) fn write(&self, probe: usize, uuid: Uuid, value: &[u8]) {
+        let hex = value
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        let millis = self.start.elapsed().as_millis();
+        let mut file = self.file.lock().unwrap_or_else(|err| err.into_inner());
+
+        if let Err(err) = writeln!(file, "{millis} {probe} {uuid} {hex}") {
+            tracing::warn!("unable to record notification: {err}");
+        }
+    }
+}
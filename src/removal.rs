@@ -0,0 +1,131 @@
+//! Detection of a probe pulled out of the meat.
+//!
+//! Inside the meat the tip is usually far from the ambient temperature. Once pulled out, both
+//! sensors measure the same air and converge within a minute or two.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Time span the tip and ambient temperatures have to converge in.
+const WINDOW: Duration = Duration::from_secs(2 * 60);
+
+/// Difference between tip and ambient temperature in °C below which they count as converged.
+const CONVERGED: f32 = 2.0;
+
+/// Difference between tip and ambient temperature in °C the probe must have seen within the
+/// window before converging, and above which it counts as inserted again.
+const INSERTED: f32 = 5.0;
+
+/// Change of a probe being inside the meat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Change {
+    Removed,
+    Inserted,
+}
+
+/// Tracks the difference between tip and ambient temperature of a single probe.
+#[derive(Clone, Default)]
+pub(crate) struct Detector {
+    differences: VecDeque<(Instant, f32)>,
+    removed: bool,
+}
+
+impl Detector {
+    /// Update with `tip` and `ambient` measured at `at` and return whether the probe was pulled
+    /// out or inserted again.
+    pub(crate) fn update(&mut self, at: Instant, tip: f32, ambient: f32) -> Option<Change> {
+        let difference = (ambient - tip).abs();
+        self.differences.push_back((at, difference));
+
+        while self
+            .differences
+            .front()
+            .is_some_and(|(first, _)| at.duration_since(*first) > WINDOW)
+        {
+            self.differences.pop_front();
+        }
+
+        if self.removed {
+            if difference > INSERTED {
+                self.removed = false;
+                return Some(Change::Inserted);
+            }
+
+            return None;
+        }
+
+        let apart = self
+            .differences
+            .iter()
+            .any(|(_, difference)| *difference >= INSERTED);
+
+        if apart && difference <= CONVERGED {
+            self.removed = true;
+            return Some(Change::Removed);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(start: Instant, seconds: u64) -> Instant {
+        start + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn detect_removal_once_converged() {
+        let mut detector = Detector::default();
+        let start = Instant::now();
+
+        assert_eq!(detector.update(start, 40.0, 120.0), None);
+        assert_eq!(detector.update(seconds(start, 30), 60.0, 70.0), None);
+        assert_eq!(
+            detector.update(seconds(start, 60), 25.0, 26.0),
+            Some(Change::Removed)
+        );
+        assert_eq!(detector.update(seconds(start, 90), 25.0, 25.0), None);
+    }
+
+    #[test]
+    fn detect_insertion_after_removal() {
+        let mut detector = Detector::default();
+        let start = Instant::now();
+
+        detector.update(start, 40.0, 120.0);
+        detector.update(seconds(start, 60), 25.0, 26.0);
+
+        // Still within the debounce margin between converged and inserted.
+        assert_eq!(detector.update(seconds(start, 70), 25.0, 29.0), None);
+        assert_eq!(
+            detector.update(seconds(start, 80), 20.0, 30.0),
+            Some(Change::Inserted)
+        );
+        assert_eq!(detector.update(seconds(start, 90), 20.0, 40.0), None);
+    }
+
+    #[test]
+    fn ignore_probes_never_apart() {
+        let mut detector = Detector::default();
+        let start = Instant::now();
+
+        // A probe lying next to the grill from the start was never inside the meat.
+        assert_eq!(detector.update(start, 25.0, 26.0), None);
+        assert_eq!(detector.update(seconds(start, 30), 25.0, 28.0), None);
+        assert_eq!(detector.update(seconds(start, 60), 25.0, 25.0), None);
+    }
+
+    #[test]
+    fn ignore_slow_convergence() {
+        let mut detector = Detector::default();
+        let start = Instant::now();
+
+        // Tip and ambient apart only longer than the window ago.
+        assert_eq!(detector.update(start, 40.0, 120.0), None);
+        assert_eq!(detector.update(seconds(start, 100), 90.0, 94.0), None);
+        assert_eq!(detector.update(seconds(start, 200), 90.0, 91.0), None);
+    }
+}
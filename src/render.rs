@@ -0,0 +1,350 @@
+//! Drawing of the [`Model`] onto a display.
+
+use std::time::{Duration, Instant};
+
+use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+
+use crate::burn_in::{BurnIn, Transform};
+use crate::display::{DisplayBackend, Rotation, WIDTH};
+use crate::font;
+use crate::icons::Icons;
+use crate::model::Model;
+use crate::session;
+use crate::ui::{self, Pages};
+use crate::units::Unit;
+
+/// Time between two frames of animations.
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Time the splash screen is shown at startup.
+const SPLASH_DURATION: Duration = Duration::from_secs(3);
+
+/// Screen naming the version and setup at startup.
+struct Splash {
+    /// Probes and display in use.
+    setup: String,
+    until: Instant,
+}
+
+/// Draws the model with the configured icons and settings.
+pub struct Renderer {
+    icons: Icons,
+    unit: Unit,
+    /// Number of frames shown so far, selects the current frame of animations.
+    frame: usize,
+    burn_in: Option<BurnIn>,
+    /// Burn-in transformation as of the last tick.
+    transform: Transform,
+    rotation: Rotation,
+    pages: Pages,
+    splash: Option<Splash>,
+}
+
+impl Renderer {
+    pub fn new(icons: Icons, unit: Unit) -> Self {
+        Self {
+            icons,
+            unit,
+            frame: 0,
+            burn_in: None,
+            transform: Transform::default(),
+            rotation: Rotation::default(),
+            pages: Pages::default(),
+            splash: None,
+        }
+    }
+
+    /// Show the next page.
+    pub fn next_page(&mut self) {
+        self.pages.next();
+    }
+
+    /// Show the previous page.
+    pub fn previous_page(&mut self) {
+        self.pages.previous();
+    }
+
+    /// Show `pages` while connected instead of the default ones.
+    pub fn pages(mut self, pages: Pages) -> Self {
+        self.pages = pages;
+        self
+    }
+
+    /// Show the version along with the `probes` and `display` in use for a few seconds first.
+    pub fn splash(mut self, probes: &str, display: &str) -> Self {
+        self.splash = Some(Splash {
+            setup: format!("{probes} on {display}"),
+            until: Instant::now() + SPLASH_DURATION,
+        });
+        self
+    }
+
+    /// Turn the content by `rotation` to match how the display is mounted.
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Move and invert the content over time according to `burn_in`.
+    pub fn burn_in(mut self, burn_in: BurnIn) -> Self {
+        self.burn_in = Some(burn_in);
+        self
+    }
+
+    /// Advance animations by one frame and return `true` if `model` has to be drawn again.
+    pub fn tick(&mut self, model: &Model) -> bool {
+        self.frame = self.frame.wrapping_add(1);
+
+        let transform = self.transform();
+        let moved = transform != self.transform;
+        self.transform = transform;
+
+        let done = self.progress(model).is_some_and(|progress| progress >= 1.0);
+
+        let splashed = self
+            .splash
+            .as_ref()
+            .is_some_and(|splash| Instant::now() >= splash.until);
+
+        if splashed {
+            self.splash = None;
+        }
+
+        // Only pages showing temperatures are cycled through.
+        let cycled = !model.temperatures.is_empty() && self.pages.tick();
+
+        moved
+            || done
+            || splashed
+            || cycled
+            || matches!(
+                model.state,
+                meater::State::Searching { .. }
+                    | meater::State::Connecting
+                    | meater::State::Retrying { .. }
+            )
+    }
+
+    /// Return the progress between 0 and 1 of the probe closest to its target temperature,
+    /// measured from the first temperature seen.
+    fn progress(&self, model: &Model) -> Option<f32> {
+        if model.state != meater::State::Connected {
+            return None;
+        }
+
+        model
+            .temperatures
+            .keys()
+            .filter_map(|probe| model.progress(*probe))
+            .reduce(f32::max)
+    }
+
+    /// Current burn-in transformation.
+    fn transform(&self) -> Transform {
+        self.burn_in
+            .as_ref()
+            .map(BurnIn::transform)
+            .unwrap_or_default()
+    }
+
+    /// Draw the current `model` and flush it to `display`.
+    pub fn render(&self, display: &mut dyn DisplayBackend, model: &Model) -> anyhow::Result<()> {
+        display.clear()?;
+
+        if let Some(splash) = &self.splash {
+            render_splash(display, splash)?;
+            return self.present(display);
+        }
+
+        if let Some(target) = model.adjusting {
+            self.render_adjustment(display, target)?;
+            return self.present(display);
+        }
+
+        if let Some(error) = model.error {
+            display.draw_icon(&self.icons.error, Point::new(47, 16))?;
+            render_caption(display, &format!("E{} #{:04x}", error.code, error.id))?;
+            return self.present(display);
+        }
+
+        match model.state {
+            meater::State::Disconnected => {
+                display.draw_icon(&self.icons.not_found, Point::new(47, 16))?;
+                render_caption(display, "searching")?;
+            }
+            meater::State::Searching { elapsed } => {
+                display.draw_icon(&self.icons.not_found, Point::new(47, 16))?;
+                self.render_spinner(display)?;
+                render_caption(
+                    display,
+                    &format!("searching {}", session::hours_minutes(elapsed)),
+                )?;
+            }
+            meater::State::Connecting => {
+                display.draw_icon(&self.icons.connecting, Point::new(47, 16))?;
+                self.render_spinner(display)?;
+                render_caption(display, "connecting")?;
+            }
+            meater::State::Retrying { attempt } => {
+                display.draw_icon(&self.icons.connecting, Point::new(47, 16))?;
+                self.render_spinner(display)?;
+                render_caption(display, &format!("retry {attempt}"))?;
+            }
+            // Keep showing the last temperatures until the probe is connected again.
+            meater::State::Connected | meater::State::Stale => {
+                let progress = self.progress(model);
+
+                // Leave room for the progress bar at the bottom.
+                let bottom = if progress.is_some() { 57 } else { 64 };
+
+                if model.temperatures.is_empty() {
+                    // Subscribed but no notification arrived yet.
+                    display.draw_icon(&self.icons.connected, Point::new(47, 16))?;
+                    render_caption(display, "waiting for data")?;
+                } else {
+                    let context = ui::Context {
+                        model,
+                        icons: &self.icons,
+                        unit: self.unit,
+                        bottom,
+                    };
+
+                    self.pages.current().render(display, &context)?;
+                }
+
+                if let Some(progress) = progress {
+                    self.render_progress(display, progress)?;
+                }
+
+                self.render_status(display, model)?;
+            }
+        }
+
+        self.present(display)
+    }
+
+    /// Apply the burn-in transformation and rotation to the drawn frame and flush it.
+    fn present(&self, display: &mut dyn DisplayBackend) -> anyhow::Result<()> {
+        self.transform().apply(display.canvas());
+
+        match self.rotation {
+            Rotation::Rotate0 => {}
+            Rotation::Rotate90 => display.canvas().rotate_quarter_turn(true),
+            Rotation::Rotate180 => display.canvas().rotate_half_turn(),
+            Rotation::Rotate270 => display.canvas().rotate_quarter_turn(false),
+        }
+
+        display.flush()
+    }
+
+    /// Draw the `target` temperature being dialed in with the encoder.
+    fn render_adjustment(
+        &self,
+        display: &mut dyn DisplayBackend,
+        target: f32,
+    ) -> anyhow::Result<()> {
+        let probe_style = font::MEDIUM.style();
+
+        display.draw_text("Target", Point::new(0, 12), probe_style)?;
+        display.draw_temperature(target, self.unit, Point::new(0, 38), font::LARGE)?;
+        display.draw_text("Press to set", Point::new(0, 60), probe_style)
+    }
+
+    /// Draw the battery level of the most depleted and the signal strength of the weakest probe,
+    /// a warning if the connection went stale and the selected preset in the top right corner.
+    fn render_status(&self, display: &mut dyn DisplayBackend, model: &Model) -> anyhow::Result<()> {
+        let mut right = WIDTH as i32;
+
+        let depleted = model.batteries.iter().min_by_key(|(_, percent)| **percent);
+
+        if let Some((probe, percent)) = depleted {
+            let state = model.battery_states.get(probe).copied();
+            right = self.render_battery(display, *percent, state)?;
+        }
+
+        if let Some(rssi) = model.rssi.values().min().copied() {
+            let icon = self.icons.signal(rssi);
+            right -= 2 + icon.size().width as i32;
+            display.draw_icon(icon, Point::new(right, 0))?;
+        }
+
+        if model.state == meater::State::Stale {
+            let icon = &self.icons.stale;
+            right -= 2 + icon.size().width as i32;
+            display.draw_icon(icon, Point::new(right, 0))?;
+        }
+
+        if let Some(preset) = model.preset {
+            let text = preset.abbreviation();
+            let x = font::SMALL.right_aligned(text, right - 2);
+            display.draw_text(text, Point::new(x, 9), font::SMALL.style())?;
+        }
+
+        Ok(())
+    }
+
+    /// Draw the progress bar along the bottom, blinking once the target is reached.
+    fn render_progress(
+        &self,
+        display: &mut dyn DisplayBackend,
+        progress: f32,
+    ) -> anyhow::Result<()> {
+        if progress >= 1.0 && (self.frame / 2) % 2 == 1 {
+            return Ok(());
+        }
+
+        let area = Rectangle::new(Point::new(0, 59), Size::new(WIDTH, 5));
+        display.draw_progress(progress, area)
+    }
+
+    /// Draw the current frame of the spinner right of the connecting icon.
+    fn render_spinner(&self, display: &mut dyn DisplayBackend) -> anyhow::Result<()> {
+        let icon = &self.icons.spinner[self.frame % self.icons.spinner.len()];
+        display.draw_icon(icon, Point::new(86, 27))
+    }
+
+    /// Draw the battery icon matching `percent` and `state` with the charge level right next to
+    /// it in the top right corner and return the left edge of the drawn area.
+    fn render_battery(
+        &self,
+        display: &mut dyn DisplayBackend,
+        percent: u16,
+        state: Option<meater::BatteryState>,
+    ) -> anyhow::Result<i32> {
+        let icon = match state {
+            Some(meater::BatteryState::Charging) => &self.icons.battery_charging,
+            Some(meater::BatteryState::Low) => &self.icons.battery_low,
+            _ => self.icons.battery(percent),
+        };
+
+        display.draw_icon(icon, Point::new(112, 0))?;
+
+        let text = format!("{percent}%");
+        let x = font::SMALL.right_aligned(&text, 110);
+        display.draw_text(&text, Point::new(x, 9), font::SMALL.style())?;
+
+        Ok(x)
+    }
+}
+
+/// Draw `text` centered below the large icon in the middle.
+fn render_caption(display: &mut dyn DisplayBackend, text: &str) -> anyhow::Result<()> {
+    let x = font::SMALL.centered(text);
+    display.draw_text(text, Point::new(x, 60), font::SMALL.style())
+}
+
+/// Draw the name and version of the binary above its `splash` setup, all centered.
+fn render_splash(display: &mut dyn DisplayBackend, splash: &Splash) -> anyhow::Result<()> {
+    let name = env!("CARGO_PKG_NAME");
+    let x = font::MEDIUM.centered(name);
+    display.draw_text(name, Point::new(x, 20), font::MEDIUM.style())?;
+
+    let version = concat!("v", env!("CARGO_PKG_VERSION"));
+    let x = font::SMALL.centered(version);
+    display.draw_text(version, Point::new(x, 34), font::SMALL.style())?;
+
+    // Long display names may not fit, cut them off on the right instead of the left.
+    let x = font::SMALL.centered(&splash.setup).max(0);
+    display.draw_text(&splash.setup, Point::new(x, 52), font::SMALL.style())
+}
@@ -0,0 +1,122 @@
+//! Replay of notifications recorded with [`Client::record`](crate::Client::record).
+//!
+//! The notifications are decoded like those of a live probe and sent at their original pace or
+//! faster, so real cooks can serve as fixtures.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::Context;
+use futures::future::BoxFuture;
+use tokio::sync::mpsc;
+
+use crate::client::{Calibration, Event, State, LOW_BATTERY};
+use crate::decoder::Decoder;
+use crate::protocol;
+use crate::recording::Record;
+use crate::source::ProbeSource;
+
+/// Replays a recording and emits the [`Event`]s of the probes in it.
+pub struct Replay {
+    sender: mpsc::Sender<Event>,
+    path: PathBuf,
+    speed: f32,
+    calibrations: Vec<Calibration>,
+    low_battery: u16,
+}
+
+impl Replay {
+    pub fn new(path: PathBuf) -> (Self, mpsc::Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel(16);
+
+        let replay = Self {
+            sender,
+            path,
+            speed: 1.0,
+            calibrations: Vec::new(),
+            low_battery: LOW_BATTERY,
+        };
+
+        (replay, receiver)
+    }
+
+    /// Replay `speed` times faster than recorded.
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.01);
+        self
+    }
+
+    /// Correct the temperatures of the n-th probe by the n-th of `calibrations`.
+    pub fn calibrations(mut self, calibrations: Vec<Calibration>) -> Self {
+        self.calibrations = calibrations;
+        self
+    }
+
+    /// Report a battery below `percent` as [`BatteryState::Low`](crate::BatteryState::Low).
+    pub fn low_battery(mut self, percent: u16) -> Self {
+        self.low_battery = percent;
+        self
+    }
+
+    /// Send the events of the recording, then wait until the receiver is dropped.
+    pub async fn run(self) -> anyhow::Result<()> {
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("unable to read recording {}", self.path.display()))?;
+
+        let records = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                Record::parse(line).with_context(|| {
+                    format!("invalid line {} of {}", number + 1, self.path.display())
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        tracing::info!("replaying {} notifications", records.len());
+
+        self.sender.send(Event::State(State::Connecting)).await?;
+        self.sender.send(Event::State(State::Connected)).await?;
+
+        let start = Instant::now();
+        let started = tokio::time::Instant::now();
+        let mut decoders = HashMap::new();
+
+        for record in records {
+            let Some(layout) = protocol::characteristic_layout(record.uuid) else {
+                continue;
+            };
+
+            tokio::time::sleep_until(started + record.offset.div_f32(self.speed)).await;
+
+            // Decoders see the original timing regardless of the speed.
+            let decoder = decoders.entry(record.probe).or_insert_with(|| {
+                let calibration = self.calibrations.get(record.probe).copied();
+                let calibration = calibration.unwrap_or_default();
+                Decoder::new(layout, record.probe, calibration, self.low_battery)
+            });
+
+            for event in decoder.decode(start + record.offset, record.uuid, &record.value) {
+                self.sender.send(event).await?;
+            }
+        }
+
+        tracing::info!("replay finished");
+        self.sender.send(Event::State(State::Disconnected)).await?;
+        self.sender.closed().await;
+
+        Ok(())
+    }
+}
+
+impl ProbeSource for Replay {
+    fn name(&self) -> &'static str {
+        "replayed"
+    }
+
+    fn run(self: Box<Self>) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(Replay::run(*self))
+    }
+}
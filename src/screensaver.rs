@@ -0,0 +1,94 @@
+//! Turning the display off while nothing happens.
+//!
+//! Once the temperatures have not changed meaningfully for a while, the display is switched off.
+//! It wakes up again on state changes, larger temperature changes or a press of any button.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+/// Screensaver settings.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Minutes without activity after which the display is turned off, never if not set.
+    pub timeout_minutes: Option<u64>,
+    /// Degrees Celsius a temperature has to change to count as activity.
+    pub threshold: f32,
+    /// GPIO pin (BCM numbering) of a push button to ground only waking up the display.
+    pub wake_pin: Option<u8>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            timeout_minutes: None,
+            threshold: 1.0,
+            wake_pin: None,
+        }
+    }
+}
+
+/// Decides whether the display should be on.
+pub struct Screensaver {
+    timeout: Option<Duration>,
+    threshold: f32,
+    last_activity: Instant,
+    /// Tip temperatures at the last activity.
+    reference: BTreeMap<usize, f32>,
+}
+
+impl Screensaver {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            timeout: options
+                .timeout_minutes
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            threshold: options.threshold,
+            last_activity: Instant::now(),
+            reference: BTreeMap::new(),
+        }
+    }
+
+    /// Register `event` as activity if it changes the state or a temperature meaningfully.
+    pub fn update(&mut self, event: &meater::Event) {
+        match event {
+            // Repeated while no probe is around, which must not keep the display on.
+            meater::Event::State(meater::State::Searching { .. }) => {}
+            meater::Event::State(_)
+            | meater::Event::BatteryState { .. }
+            | meater::Event::ProbeRemoved { .. }
+            | meater::Event::ProbeInserted { .. }
+            | meater::Event::Error { .. }
+            | meater::Event::SessionEnded => self.wake(),
+            meater::Event::Temperature { probe, tip, .. } => {
+                let changed = self
+                    .reference
+                    .get(probe)
+                    .is_none_or(|reference| (tip - reference).abs() >= self.threshold);
+
+                if changed {
+                    self.reference.insert(*probe, *tip);
+                    self.wake();
+                }
+            }
+            meater::Event::Battery { .. }
+            | meater::Event::Rssi { .. }
+            | meater::Event::Sensors { .. }
+            | meater::Event::DeviceInfo { .. }
+            | meater::Event::DecodeError { .. } => {}
+        }
+    }
+
+    /// Return `true` if the display should be on.
+    pub fn is_awake(&self) -> bool {
+        self.timeout
+            .is_none_or(|timeout| self.last_activity.elapsed() < timeout)
+    }
+
+    /// Count as activity, e.g. because the user interacted.
+    pub fn wake(&mut self) {
+        self.last_activity = Instant::now();
+    }
+}
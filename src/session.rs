@@ -0,0 +1,203 @@
+//! Tracking of cook sessions and their summary.
+//!
+//! A session starts with the first temperature of a connected probe and ends when the last probe
+//! disconnects or the user sends `SIGUSR1`. The summary of each session is logged when it ends.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{mpsc, watch};
+
+use crate::alarm;
+
+/// Statistics of a single probe during a session.
+#[derive(Clone, Copy, Debug)]
+pub struct ProbeSummary {
+    pub min: f32,
+    pub max: f32,
+    /// Time since the start of the session the target temperature was first reached.
+    pub target_reached: Option<Duration>,
+}
+
+/// Summary of a finished cook session.
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub started: DateTime<Local>,
+    pub duration: Duration,
+    pub probes: BTreeMap<usize, ProbeSummary>,
+}
+
+/// Format `duration` as hours and minutes.
+pub fn hours_minutes(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "started {}, lasted {}",
+            self.started.format("%Y-%m-%d %H:%M"),
+            hours_minutes(self.duration)
+        )?;
+
+        for (probe, summary) in &self.probes {
+            write!(
+                f,
+                ", probe {} {:.1}-{:.1} °C",
+                probe + 1,
+                summary.min,
+                summary.max
+            )?;
+
+            if let Some(reached) = summary.target_reached {
+                write!(f, " (target after {})", hours_minutes(reached))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Session currently in progress.
+struct Session {
+    started: DateTime<Local>,
+    start: Instant,
+    probes: BTreeMap<usize, ProbeSummary>,
+}
+
+/// Follows events and turns them into sessions.
+pub struct Tracker {
+    /// Target temperatures in °C, the n-th applies to the n-th probe, the last to all others.
+    targets: watch::Receiver<Vec<f32>>,
+    current: Option<Session>,
+}
+
+impl Tracker {
+    pub fn new(targets: watch::Receiver<Vec<f32>>) -> Self {
+        Self {
+            targets,
+            current: None,
+        }
+    }
+
+    /// Update the current session from `event` and return its summary if it ended.
+    pub fn update(&mut self, event: &meater::Event) -> Option<Summary> {
+        match event {
+            meater::Event::State(meater::State::Disconnected) | meater::Event::SessionEnded => {
+                self.end()
+            }
+            meater::Event::Temperature { probe, tip, .. } => {
+                let session = self.current.get_or_insert_with(|| Session {
+                    started: Local::now(),
+                    start: Instant::now(),
+                    probes: BTreeMap::new(),
+                });
+
+                let summary = session.probes.entry(*probe).or_insert(ProbeSummary {
+                    min: *tip,
+                    max: *tip,
+                    target_reached: None,
+                });
+
+                summary.min = summary.min.min(*tip);
+                summary.max = summary.max.max(*tip);
+
+                let reached = alarm::target(&self.targets.borrow(), *probe)
+                    .is_some_and(|target| *tip >= target);
+
+                if reached && summary.target_reached.is_none() {
+                    summary.target_reached = Some(session.start.elapsed());
+                }
+
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the summary of the session in progress so far.
+    pub fn current(&self) -> Option<Summary> {
+        let session = self.current.as_ref()?;
+
+        Some(Summary {
+            started: session.started,
+            duration: session.start.elapsed(),
+            probes: session.probes.clone(),
+        })
+    }
+
+    /// End the current session and return its summary.
+    pub fn end(&mut self) -> Option<Summary> {
+        let summary = self.current();
+        self.current = None;
+        summary
+    }
+}
+
+/// Send [`meater::Event::SessionEnded`] to `sender` whenever the user sends `SIGUSR1`.
+pub async fn end_on_signal(sender: mpsc::Sender<meater::Event>) -> anyhow::Result<()> {
+    let mut user_end = signal(SignalKind::user_defined1())?;
+
+    while user_end.recv().await.is_some() {
+        tracing::info!("received SIGUSR1, ending cook session");
+        sender.send(meater::Event::SessionEnded).await?;
+    }
+
+    Ok(())
+}
+
+/// Track sessions from events received on `receiver` and log their summaries.
+pub async fn run(
+    targets: watch::Receiver<Vec<f32>>,
+    mut receiver: mpsc::Receiver<meater::Event>,
+) -> anyhow::Result<()> {
+    let mut tracker = Tracker::new(targets);
+
+    while let Some(event) = receiver.recv().await {
+        if let Some(summary) = tracker.update(&event) {
+            tracing::info!("cook session ended: {summary}");
+        }
+    }
+
+    if let Some(summary) = tracker.end() {
+        tracing::info!("cook session ended: {summary}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temperature(tip: f32) -> meater::Event {
+        meater::Event::Temperature {
+            probe: 0,
+            tip,
+            ambient: 25.0,
+        }
+    }
+
+    #[test]
+    fn end_session_ended_by_user() {
+        let mut tracker = Tracker::new(watch::channel(vec![60.0]).1);
+
+        assert!(tracker.update(&temperature(40.0)).is_none());
+        assert!(tracker.update(&temperature(62.0)).is_none());
+
+        let summary = tracker.update(&meater::Event::SessionEnded).unwrap();
+        let probe = summary.probes[&0];
+        assert_eq!((probe.min, probe.max), (40.0, 62.0));
+        assert!(probe.target_reached.is_some());
+        assert!(tracker.current().is_none());
+
+        // The next temperature starts a new session although the probe stayed connected.
+        tracker.update(&temperature(30.0));
+        assert_eq!(tracker.current().unwrap().probes[&0].max, 30.0);
+    }
+}
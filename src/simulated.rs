@@ -0,0 +1,250 @@
+//! Simulated probe going through a complete cook, for demos and testing without hardware.
+//!
+//! A cold piece of meat heats up in a smoker, stalls, is pulled at its target and rests while the
+//! tip still climbs a little and then cools down. Readings are blurred with noise and the probe
+//! disconnects every now and then.
+
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use rand::Rng;
+use tokio::sync::mpsc;
+
+use crate::battery;
+use crate::client::{Event, State, LOW_BATTERY};
+use crate::source::ProbeSource;
+
+/// Interval readings are sent in.
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Temperature in °C of the meat fresh from the fridge.
+const START: f32 = 5.0;
+
+/// Temperature in °C of the smoker.
+const SMOKER: f32 = 110.0;
+
+/// Temperature in °C of the kitchen the meat rests in.
+const ROOM: f32 = 22.0;
+
+/// Tip temperature in °C the meat is pulled at.
+const PULL: f32 = 95.0;
+
+/// Rate at which the tip approaches the smoker temperature, per second.
+const HEATING: f32 = 1.2e-4;
+
+/// Band of the tip temperature in °C the meat stalls in.
+const STALL_LOW: f32 = 65.0;
+const STALL_HIGH: f32 = 72.0;
+
+/// Seconds the stall lasts.
+const STALL_DURATION: f32 = 90.0 * 60.0;
+
+/// Rise of the tip in °C per second during the stall.
+const STALL_RATE: f32 = 0.02 / 60.0;
+
+/// Rise of the tip in °C per second right after pulling, decaying with [`CARRYOVER_DECAY`].
+const CARRYOVER_RATE: f32 = 0.015;
+
+/// Time constant in seconds of the decay of the carryover.
+const CARRYOVER_DECAY: f32 = 300.0;
+
+/// Rate at which the tip approaches the room temperature while resting, per second.
+const COOLING: f32 = 1.0e-4;
+
+/// Phase of the cook.
+#[derive(Clone, Copy)]
+enum Phase {
+    Cooking,
+    /// Pulled from the smoker at the elapsed seconds.
+    Resting {
+        since: f32,
+    },
+}
+
+/// Temperatures of the simulated cook.
+struct Cook {
+    phase: Phase,
+    /// Simulated seconds since the start.
+    elapsed: f32,
+    tip: f32,
+    ambient: f32,
+    /// Seconds spent in the stall.
+    stalled: f32,
+}
+
+impl Cook {
+    fn new() -> Self {
+        Self {
+            phase: Phase::Cooking,
+            elapsed: 0.0,
+            tip: START,
+            ambient: SMOKER,
+            stalled: 0.0,
+        }
+    }
+
+    /// Advance the cook by `step` seconds.
+    fn advance(&mut self, step: f32) {
+        match self.phase {
+            Phase::Cooking => {
+                // The smoker is never quite stable.
+                self.ambient = SMOKER + 3.0 * (self.elapsed / 600.0).sin();
+
+                if (STALL_LOW..STALL_HIGH).contains(&self.tip) && self.stalled < STALL_DURATION {
+                    self.stalled += step;
+                    self.tip += STALL_RATE * step;
+                } else {
+                    self.tip += HEATING * (self.ambient - self.tip) * step;
+                }
+
+                if self.tip >= PULL {
+                    self.phase = Phase::Resting {
+                        since: self.elapsed,
+                    };
+                }
+            }
+            Phase::Resting { since } => {
+                let rested = self.elapsed - since;
+                let carryover = CARRYOVER_RATE * (-rested / CARRYOVER_DECAY).exp();
+
+                self.ambient += (ROOM - self.ambient) * (step / 120.0).min(1.0);
+                self.tip += (carryover + COOLING * (ROOM - self.tip)) * step;
+            }
+        }
+
+        self.elapsed += step;
+    }
+}
+
+/// Simulates a single probe and emits [`Event`]s for it.
+pub struct Simulated {
+    sender: mpsc::Sender<Event>,
+    noise: f32,
+    disconnects_per_hour: f32,
+    speed: f32,
+    low_battery: u16,
+}
+
+impl Simulated {
+    pub fn new() -> (Self, mpsc::Receiver<Event>) {
+        let (sender, receiver) = mpsc::channel(16);
+
+        let simulated = Self {
+            sender,
+            noise: 0.3,
+            disconnects_per_hour: 0.5,
+            speed: 1.0,
+            low_battery: LOW_BATTERY,
+        };
+
+        (simulated, receiver)
+    }
+
+    /// Vary readings randomly by up to `noise` °C.
+    pub fn noise(mut self, noise: f32) -> Self {
+        self.noise = noise.max(0.0);
+        self
+    }
+
+    /// Disconnect for a few seconds `disconnects` times per simulated hour on average.
+    pub fn disconnects_per_hour(mut self, disconnects: f32) -> Self {
+        self.disconnects_per_hour = disconnects.max(0.0);
+        self
+    }
+
+    /// Run the cook `speed` times faster than real time.
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.1);
+        self
+    }
+
+    /// Report a battery below `percent` as [`BatteryState::Low`](crate::BatteryState::Low).
+    pub fn low_battery(mut self, percent: u16) -> Self {
+        self.low_battery = percent;
+        self
+    }
+
+    /// Simulate a cook until the receiver is dropped.
+    pub async fn run(self) -> anyhow::Result<()> {
+        let mut cook = Cook::new();
+        let mut interval = tokio::time::interval(INTERVAL);
+        let step = INTERVAL.as_secs_f32() * self.speed;
+        let mut reported = f32::MIN;
+        let mut battery = battery::Detector::new(self.low_battery);
+
+        self.connect().await?;
+
+        loop {
+            interval.tick().await;
+            cook.advance(step);
+
+            let disconnect = self.disconnects_per_hour * step / 3600.0;
+
+            if rand::thread_rng().gen::<f32>() < disconnect {
+                let seconds = rand::thread_rng().gen_range(3..20);
+                tracing::info!("simulating a disconnect for {seconds} s");
+                self.sender.send(Event::State(State::Disconnected)).await?;
+                tokio::time::sleep(Duration::from_secs(seconds)).await;
+                self.connect().await?;
+                reported = f32::MIN;
+                battery = battery::Detector::new(self.low_battery);
+                continue;
+            }
+
+            let noise = |value: f32| {
+                if self.noise > 0.0 {
+                    value + rand::thread_rng().gen_range(-self.noise..=self.noise)
+                } else {
+                    value
+                }
+            };
+
+            let (tip, ambient) = (noise(cook.tip), noise(cook.ambient));
+
+            self.sender
+                .send(Event::Temperature {
+                    probe: 0,
+                    tip,
+                    ambient,
+                })
+                .await?;
+
+            // Battery and signal strength once per simulated minute.
+            if cook.elapsed - reported >= 60.0 {
+                reported = cook.elapsed;
+                let drained = (cook.elapsed / 600.0) as u16;
+                let percent = 100u16.saturating_sub(drained).max(10) / 10 * 10;
+                let rssi = rand::thread_rng().gen_range(-75..-55);
+
+                self.sender
+                    .send(Event::Battery { probe: 0, percent })
+                    .await?;
+
+                if let Some(state) = battery.update(percent) {
+                    self.sender
+                        .send(Event::BatteryState { probe: 0, state })
+                        .await?;
+                }
+
+                self.sender.send(Event::Rssi { probe: 0, rssi }).await?;
+            }
+        }
+    }
+
+    /// Announce the probe as connected.
+    async fn connect(&self) -> anyhow::Result<()> {
+        self.sender.send(Event::State(State::Connecting)).await?;
+        self.sender.send(Event::State(State::Connected)).await?;
+        Ok(())
+    }
+}
+
+impl ProbeSource for Simulated {
+    fn name(&self) -> &'static str {
+        "simulated"
+    }
+
+    fn run(self: Box<Self>) -> BoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(Simulated::run(*self))
+    }
+}
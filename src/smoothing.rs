@@ -0,0 +1,193 @@
+//! Smoothing of noisy temperature readings.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::Deserialize;
+
+/// Filter applied to temperatures.
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    /// Pass readings through unchanged.
+    #[default]
+    None,
+    /// Average of the last `window` readings.
+    Average,
+    /// Exponential moving average weighting the latest reading with `alpha`.
+    Exponential,
+}
+
+/// Smoothing settings.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Options {
+    pub filter: Filter,
+    /// Number of readings averaged by the moving average.
+    pub window: usize,
+    /// Weight of the latest reading between 0 and 1 for the exponential filter.
+    pub alpha: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            filter: Filter::None,
+            window: 5,
+            alpha: 0.3,
+        }
+    }
+}
+
+/// State of the filter for one temperature series.
+#[derive(Default)]
+struct Series {
+    readings: VecDeque<f32>,
+    value: Option<f32>,
+}
+
+/// Smooths tip and ambient temperatures of each probe independently.
+pub struct Smoother {
+    filter: Filter,
+    window: usize,
+    alpha: f32,
+    series: BTreeMap<(usize, bool), Series>,
+}
+
+impl Smoother {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            filter: options.filter,
+            window: options.window.max(1),
+            alpha: options.alpha.clamp(0.0, 1.0),
+            series: BTreeMap::new(),
+        }
+    }
+
+    /// Return `event` with smoothed temperatures.
+    ///
+    /// Once disconnected, the filter starts over so readings from before do not drag down those
+    /// after connecting again.
+    pub fn apply(&mut self, event: meater::Event) -> meater::Event {
+        match event {
+            meater::Event::State(meater::State::Disconnected) => {
+                self.series.clear();
+                event
+            }
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => meater::Event::Temperature {
+                probe,
+                tip: self.smooth((probe, true), tip),
+                ambient: self.smooth((probe, false), ambient),
+            },
+            event => event,
+        }
+    }
+
+    /// Feed `value` into the series identified by probe and whether it is the tip.
+    fn smooth(&mut self, key: (usize, bool), value: f32) -> f32 {
+        let series = self.series.entry(key).or_default();
+
+        match self.filter {
+            Filter::None => value,
+            Filter::Average => {
+                if series.readings.len() == self.window {
+                    series.readings.pop_front();
+                }
+
+                series.readings.push_back(value);
+                series.readings.iter().sum::<f32>() / series.readings.len() as f32
+            }
+            Filter::Exponential => {
+                let smoothed = series
+                    .value
+                    .map_or(value, |previous| previous + self.alpha * (value - previous));
+
+                series.value = Some(smoothed);
+                smoothed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smoother(filter: Filter) -> Smoother {
+        Smoother::new(&Options {
+            filter,
+            window: 4,
+            alpha: 0.5,
+        })
+    }
+
+    fn tip(smoother: &mut Smoother, probe: usize, tip: f32) -> f32 {
+        let event = meater::Event::Temperature {
+            probe,
+            tip,
+            ambient: 100.0,
+        };
+
+        match smoother.apply(event) {
+            meater::Event::Temperature { tip, .. } => tip,
+            event => panic!("unexpected {event:?}"),
+        }
+    }
+
+    #[test]
+    fn pass_first_sample_through() {
+        for filter in [Filter::None, Filter::Average, Filter::Exponential] {
+            assert_eq!(tip(&mut smoother(filter), 0, 42.0), 42.0);
+        }
+    }
+
+    #[test]
+    fn average_over_window() {
+        let mut smoother = smoother(Filter::Average);
+
+        assert_eq!(tip(&mut smoother, 0, 10.0), 10.0);
+        assert_eq!(tip(&mut smoother, 0, 20.0), 15.0);
+        assert_eq!(tip(&mut smoother, 0, 30.0), 20.0);
+        assert_eq!(tip(&mut smoother, 0, 40.0), 25.0);
+        // The first reading dropped out of the window.
+        assert_eq!(tip(&mut smoother, 0, 50.0), 35.0);
+    }
+
+    #[test]
+    fn converge_exponentially() {
+        let mut smoother = smoother(Filter::Exponential);
+
+        assert_eq!(tip(&mut smoother, 0, 0.0), 0.0);
+        assert_eq!(tip(&mut smoother, 0, 8.0), 4.0);
+        assert_eq!(tip(&mut smoother, 0, 8.0), 6.0);
+
+        let converged = (0..20).fold(0.0, |_, _| tip(&mut smoother, 0, 8.0));
+        assert!((converged - 8.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn smooth_probes_independently() {
+        let mut smoother = smoother(Filter::Exponential);
+
+        tip(&mut smoother, 0, 0.0);
+        assert_eq!(tip(&mut smoother, 1, 50.0), 50.0);
+        assert_eq!(tip(&mut smoother, 0, 10.0), 5.0);
+    }
+
+    #[test]
+    fn reset_once_disconnected() {
+        for filter in [Filter::Average, Filter::Exponential] {
+            let mut smoother = smoother(filter);
+
+            tip(&mut smoother, 0, 20.0);
+            smoother.apply(meater::Event::State(meater::State::Connecting));
+            assert_eq!(tip(&mut smoother, 0, 40.0), 30.0);
+
+            smoother.apply(meater::Event::State(meater::State::Disconnected));
+            assert_eq!(tip(&mut smoother, 0, 60.0), 60.0);
+        }
+    }
+}
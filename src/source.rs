@@ -0,0 +1,15 @@
+//! Thermometers producing the [`Event`](crate::Event) stream.
+
+use futures::future::BoxFuture;
+
+/// Thermometer sending [`Event`](crate::Event)s on the receiver returned when it was created.
+///
+/// The MEATER [`Client`](crate::Client) is one source, backends for other thermometers implement
+/// this trait to feed the same events to all consumers.
+pub trait ProbeSource: Send {
+    /// Name of the thermometer for log messages.
+    fn name(&self) -> &'static str;
+
+    /// Produce events until the receiver is dropped or the source fails for good.
+    fn run(self: Box<Self>) -> BoxFuture<'static, anyhow::Result<()>>;
+}
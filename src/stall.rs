@@ -0,0 +1,174 @@
+//! Detection of the stall, the plateau of the tip temperature of large cuts while moisture
+//! evaporates from the surface.
+//!
+//! A probe is stalled while its tip temperature stayed within the stall band and rose less than a
+//! few degrees over the configured time.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::units::Unit;
+
+/// Lower end of the stall band in °C if not configured.
+const DEFAULT_LOW: f32 = 65.0;
+
+/// Upper end of the stall band in °C if not configured.
+const DEFAULT_HIGH: f32 = 75.0;
+
+/// Stall detection settings.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Lower end of the stall band in the configured unit [default: 65 °C].
+    pub low: Option<f32>,
+    /// Upper end of the stall band in the configured unit [default: 75 °C].
+    pub high: Option<f32>,
+    /// Minutes the tip has to plateau before the probe counts as stalled.
+    pub minutes: u64,
+    /// Maximum rise of the tip in degrees of the configured unit during a plateau.
+    pub max_rise: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            low: None,
+            high: None,
+            minutes: 30,
+            max_rise: 1.0,
+        }
+    }
+}
+
+/// Change of the stall state of a probe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Change {
+    /// The probe entered the stall at this tip temperature in °C.
+    Started { probe: usize, tip: f32 },
+    /// The probe left the stall.
+    Ended { probe: usize },
+}
+
+/// Detects the stall of each probe from its tip temperatures.
+pub struct Detector {
+    /// Stall band in °C.
+    low: f32,
+    high: f32,
+    window: Duration,
+    /// Maximum rise in °C during a plateau.
+    max_rise: f32,
+    /// Tip temperatures of each probe covering at least the window.
+    samples: BTreeMap<usize, VecDeque<(Instant, f32)>>,
+    stalled: BTreeSet<usize>,
+}
+
+impl Detector {
+    pub fn new(options: &Options, unit: Unit) -> Self {
+        Self {
+            low: options.low.map_or(DEFAULT_LOW, |low| unit.to_celsius(low)),
+            high: options
+                .high
+                .map_or(DEFAULT_HIGH, |high| unit.to_celsius(high)),
+            window: Duration::from_secs(options.minutes * 60),
+            max_rise: unit.difference_to_celsius(options.max_rise),
+            samples: BTreeMap::new(),
+            stalled: BTreeSet::new(),
+        }
+    }
+
+    /// Return `true` if `probe` is stalled.
+    pub fn is_stalled(&self, probe: usize) -> bool {
+        self.stalled.contains(&probe)
+    }
+
+    /// Update from `event` received at `now` and return the change of the stall state it caused.
+    pub fn update(&mut self, event: &meater::Event, now: Instant) -> Option<Change> {
+        match *event {
+            meater::Event::Temperature { probe, tip, .. } => {
+                let samples = self.samples.entry(probe).or_default();
+                samples.push_back((now, tip));
+
+                // Keep a single sample older than the window to know it is covered completely.
+                while samples
+                    .get(1)
+                    .is_some_and(|(at, _)| now.duration_since(*at) >= self.window)
+                {
+                    samples.pop_front();
+                }
+
+                let covered = samples
+                    .front()
+                    .is_some_and(|(at, _)| now.duration_since(*at) >= self.window);
+
+                let (min, max) = samples
+                    .iter()
+                    .fold((f32::MAX, f32::MIN), |(min, max), (_, tip)| {
+                        (min.min(*tip), max.max(*tip))
+                    });
+
+                let stalled =
+                    covered && min >= self.low && max <= self.high && max - min <= self.max_rise;
+
+                if stalled && self.stalled.insert(probe) {
+                    Some(Change::Started { probe, tip })
+                } else if !stalled && self.stalled.remove(&probe) {
+                    Some(Change::Ended { probe })
+                } else {
+                    None
+                }
+            }
+            meater::Event::State(meater::State::Disconnected) => {
+                self.samples.clear();
+                self.stalled.clear();
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temperature(tip: f32) -> meater::Event {
+        meater::Event::Temperature {
+            probe: 0,
+            tip,
+            ambient: 100.0,
+        }
+    }
+
+    fn detects_stall(unit: Unit, max_rise: f32) -> bool {
+        let options = Options {
+            max_rise,
+            ..Options::default()
+        };
+
+        let mut detector = Detector::new(&options, unit);
+        let start = Instant::now();
+        let end = start + Duration::from_secs(31 * 60);
+
+        assert_eq!(detector.update(&temperature(70.0), start), None);
+
+        match detector.update(&temperature(70.5), end) {
+            Some(Change::Started { probe: 0, .. }) => detector.is_stalled(0),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn detect_stall_in_celsius() {
+        assert!(detects_stall(Unit::Celsius, 1.0));
+        assert!(!detects_stall(Unit::Celsius, 0.4));
+    }
+
+    #[test]
+    fn detect_stall_in_fahrenheit() {
+        // 0.5 °C are 0.9 °F.
+        assert!(detects_stall(Unit::Fahrenheit, 1.0));
+        assert!(!detects_stall(Unit::Fahrenheit, 0.8));
+    }
+}
@@ -0,0 +1,213 @@
+//! Statistics of all cooks kept in a state file across restarts.
+//!
+//! A cook is counted with the first temperature after connecting and lasts until the last probe
+//! disconnects. If the program restarts during a cook, the cook is resumed instead of counted
+//! again as long as the state file was written shortly before.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+/// Interval the state file is written in while connected.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Seconds within which a cook interrupted by a restart is resumed.
+const RESUME_WINDOW: i64 = 10 * 60;
+
+/// Current time in seconds since the UNIX epoch.
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Statistics over some span of time.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Totals {
+    /// Number of cooks started.
+    pub cooks: u32,
+    /// Seconds any probe was connected.
+    pub connected: u64,
+    /// Highest tip temperature in °C.
+    pub max_tip: Option<f32>,
+}
+
+impl Totals {
+    /// Hours any probe was connected.
+    pub fn hours(&self) -> f32 {
+        self.connected as f32 / 3600.0
+    }
+}
+
+/// Content of the state file.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct State {
+    total: Totals,
+    today: Totals,
+    /// Day `today` applies to, e.g. `2024-05-01`.
+    day: String,
+    /// Whether a cook was in progress when the file was written.
+    cooking: bool,
+    /// Seconds since the UNIX epoch the file was written.
+    saved: i64,
+}
+
+/// Follows events and updates the statistics.
+#[derive(Default)]
+pub struct Stats {
+    /// Hands the content to write to the task writing the state file.
+    writer: Option<watch::Sender<String>>,
+    state: State,
+    /// Time connected time was last added.
+    connected: Option<Instant>,
+    saved: Option<Instant>,
+}
+
+impl Stats {
+    /// Load the statistics from the state file at `path`, a missing one starts from scratch.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut state: State = match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("unable to parse {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => State::default(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("unable to read {}", path.display()))
+            }
+        };
+
+        if state.cooking && now() - state.saved < RESUME_WINDOW {
+            tracing::info!("resuming cook interrupted by a restart");
+        } else {
+            state.cooking = false;
+        }
+
+        let (writer, receiver) = watch::channel(String::new());
+        tokio::spawn(write(path.to_owned(), receiver));
+
+        Ok(Self {
+            writer: Some(writer),
+            state,
+            connected: None,
+            saved: None,
+        })
+    }
+
+    /// Statistics of all cooks.
+    pub fn total(&self) -> Totals {
+        self.state.total
+    }
+
+    /// Statistics of the cooks of the current day.
+    pub fn today(&self) -> Totals {
+        if self.state.day == today() {
+            self.state.today
+        } else {
+            Totals::default()
+        }
+    }
+
+    pub fn update(&mut self, event: &meater::Event) {
+        self.roll_over();
+        self.count_connected();
+
+        let mut changed = false;
+
+        match event {
+            meater::Event::State(meater::State::Connected) => {
+                self.connected.get_or_insert_with(Instant::now);
+            }
+            meater::Event::State(meater::State::Disconnected) => {
+                self.connected = None;
+                changed = self.state.cooking;
+                self.state.cooking = false;
+            }
+            meater::Event::Temperature { tip, .. } => {
+                if !self.state.cooking {
+                    self.state.cooking = true;
+                    self.state.total.cooks += 1;
+                    self.state.today.cooks += 1;
+                    changed = true;
+                }
+
+                for totals in [&mut self.state.total, &mut self.state.today] {
+                    totals.max_tip = Some(totals.max_tip.map_or(*tip, |max| max.max(*tip)));
+                }
+            }
+            _ => {}
+        }
+
+        let due = self
+            .saved
+            .is_none_or(|saved| saved.elapsed() >= SAVE_INTERVAL);
+
+        if changed || (due && self.connected.is_some()) {
+            self.save();
+        }
+    }
+
+    /// Start over with the statistics of the day once it changed.
+    fn roll_over(&mut self) {
+        let today = today();
+
+        if self.state.day != today {
+            self.state.day = today;
+            self.state.today = Totals::default();
+        }
+    }
+
+    /// Add the time connected since the last event.
+    fn count_connected(&mut self) {
+        let Some(connected) = self.connected.as_mut() else {
+            return;
+        };
+
+        let seconds = connected.elapsed().as_secs();
+
+        // Keep the remainder to not lose fractions of seconds between events.
+        *connected += Duration::from_secs(seconds);
+        self.state.total.connected += seconds;
+        self.state.today.connected += seconds;
+    }
+
+    fn save(&mut self) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+
+        self.saved = Some(Instant::now());
+        self.state.saved = now();
+
+        match toml::to_string(&self.state) {
+            Ok(content) => {
+                writer.send_replace(content);
+            }
+            Err(err) => tracing::warn!("unable to serialize statistics: {err}"),
+        }
+    }
+}
+
+/// Write the latest content of `receiver` to `path` until the statistics are dropped.
+///
+/// Writing in a task of its own keeps a slow SD card from stalling the display, while content
+/// saved in the meantime is written only once, in order.
+async fn write(path: PathBuf, mut receiver: watch::Receiver<String>) {
+    while receiver.changed().await.is_ok() {
+        let content = receiver.borrow_and_update().clone();
+
+        if let Err(err) = tokio::fs::write(&path, content).await {
+            tracing::warn!("unable to write {}: {err}", path.display());
+        }
+    }
+}
+
+/// Current day, e.g. `2024-05-01`.
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
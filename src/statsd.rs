@@ -0,0 +1,125 @@
+//! Export of metrics via UDP to StatsD or Graphite.
+//!
+//! Tip, ambient and battery of each probe are sent as `<prefix>.probe.<n>.{tip,ambient,battery}`
+//! with probes numbered from one like on the display, and the connection state as
+//! `<prefix>.connected`, 1 while connected and 0 otherwise. StatsD receives them as gauges,
+//! Graphite in its plaintext protocol with the current time.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use serde::Deserialize;
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+
+/// Protocol the metrics are sent in.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    /// Gauges like `meater.probe.1.tip:54.2|g`.
+    #[default]
+    Statsd,
+    /// Plaintext like `meater.probe.1.tip 54.2 1700000000`.
+    Graphite,
+}
+
+/// Settings of the StatsD/Graphite exporter.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Address of the server, e.g. `localhost:8125`, exporting is disabled if not set.
+    pub host: Option<String>,
+    pub protocol: Protocol,
+    /// Prefix of all metric names.
+    pub prefix: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            host: None,
+            protocol: Protocol::default(),
+            prefix: "meater".to_string(),
+        }
+    }
+}
+
+/// Return the metric name and value pairs to send for `event`.
+fn metrics(prefix: &str, event: &meater::Event) -> Vec<(String, f32)> {
+    match event {
+        meater::Event::State(state) => {
+            let connected = *state == meater::State::Connected;
+            vec![(
+                format!("{prefix}.connected"),
+                f32::from(u8::from(connected)),
+            )]
+        }
+        meater::Event::Temperature {
+            probe,
+            tip,
+            ambient,
+        } => vec![
+            (format!("{prefix}.probe.{}.tip", probe + 1), *tip),
+            (format!("{prefix}.probe.{}.ambient", probe + 1), *ambient),
+        ],
+        meater::Event::Battery { probe, percent } => {
+            vec![(
+                format!("{prefix}.probe.{}.battery", probe + 1),
+                f32::from(*percent),
+            )]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Return the line for the metric `name` with `value` in `protocol`.
+fn line(protocol: Protocol, name: &str, value: f32) -> String {
+    match protocol {
+        Protocol::Statsd => format!("{name}:{value:.1}|g"),
+        Protocol::Graphite => {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+
+            format!("{name} {value:.1} {timestamp}\n")
+        }
+    }
+}
+
+/// Send metrics of all events received on `receiver` to the server at `host`.
+pub async fn run(
+    host: String,
+    options: Options,
+    mut receiver: mpsc::Receiver<meater::Event>,
+) -> anyhow::Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket
+        .connect(&host)
+        .await
+        .with_context(|| format!("unable to resolve {host}"))?;
+
+    while let Some(event) = receiver.recv().await {
+        let lines: Vec<String> = metrics(&options.prefix, &event)
+            .into_iter()
+            .map(|(name, value)| line(options.protocol, &name, value))
+            .collect();
+
+        if lines.is_empty() {
+            continue;
+        }
+
+        // Both protocols accept several metrics in one datagram separated by newlines.
+        let datagram = match options.protocol {
+            Protocol::Statsd => lines.join("\n"),
+            Protocol::Graphite => lines.concat(),
+        };
+
+        // The server may just be restarting, losing a sample is fine.
+        if let Err(err) = socket.send(datagram.as_bytes()).await {
+            tracing::warn!("unable to send metrics to {host}: {err}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+//! Export of traces and metrics to an OpenTelemetry collector via OTLP over HTTP.
+//!
+//! All tracing spans are exported, most notably those of the Bluetooth connection lifecycle: the
+//! `scan` of an adapter, the `probe` handling a connected probe and within it `connect`,
+//! `subscribe`, `listen` and one `notification` span per received value.
+//!
+//! The latest tip, ambient, battery and signal strength of each probe are exported as gauges
+//! `meater.tip`, `meater.ambient`, `meater.battery` and `meater.rssi` with the probe numbered from
+//! one like on the display as `probe` attribute.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use opentelemetry::metrics::{MeterProvider as _, Unit};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::runtime::TokioCurrentThread;
+use opentelemetry_sdk::trace::Tracer;
+use opentelemetry_sdk::Resource;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Settings of the OpenTelemetry exporter.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Base URL of the OTLP/HTTP collector, e.g. `http://localhost:4318`, exporting is disabled if
+    /// not set.
+    pub endpoint: Option<String>,
+    /// Service name traces and metrics are reported under.
+    pub service_name: String,
+    /// Seconds between exports of the gauges.
+    pub metrics_interval: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            service_name: "meater".to_string(),
+            metrics_interval: 30.0,
+        }
+    }
+}
+
+impl Options {
+    fn resource(&self) -> Resource {
+        Resource::new([KeyValue::new("service.name", self.service_name.clone())])
+    }
+}
+
+/// Return a layer exporting all spans to the collector at `endpoint`.
+///
+/// Has to be called within the runtime, the spans are exported in batches by a task.
+pub fn layer<S>(endpoint: &str, options: &Options) -> anyhow::Result<OpenTelemetryLayer<S, Tracer>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .http()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(opentelemetry_sdk::trace::config().with_resource(options.resource()))
+        .install_batch(TokioCurrentThread)?;
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Flush and stop exporting spans.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+/// Latest values of a probe.
+#[derive(Default, Clone, Copy)]
+struct Values {
+    tip: Option<f64>,
+    ambient: Option<f64>,
+    battery: Option<f64>,
+    rssi: Option<f64>,
+}
+
+type Shared = Arc<Mutex<BTreeMap<usize, Values>>>;
+
+/// Value of a probe exported as gauge.
+struct Gauge {
+    name: &'static str,
+    description: &'static str,
+    unit: &'static str,
+    value: fn(&Values) -> Option<f64>,
+}
+
+const GAUGES: [Gauge; 4] = [
+    Gauge {
+        name: "meater.tip",
+        description: "Tip temperature",
+        unit: "°C",
+        value: |values| values.tip,
+    },
+    Gauge {
+        name: "meater.ambient",
+        description: "Ambient temperature",
+        unit: "°C",
+        value: |values| values.ambient,
+    },
+    Gauge {
+        name: "meater.battery",
+        description: "Battery level",
+        unit: "%",
+        value: |values| values.battery,
+    },
+    Gauge {
+        name: "meater.rssi",
+        description: "Signal strength",
+        unit: "dBm",
+        value: |values| values.rssi,
+    },
+];
+
+/// Export the latest values from events received on `receiver` as gauges to the collector at
+/// `endpoint`.
+pub async fn run(
+    endpoint: String,
+    options: Options,
+    mut receiver: mpsc::Receiver<meater::Event>,
+) -> anyhow::Result<()> {
+    let provider = opentelemetry_otlp::new_pipeline()
+        .metrics(TokioCurrentThread)
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .http()
+                .with_endpoint(endpoint),
+        )
+        .with_resource(options.resource())
+        .with_period(Duration::from_secs_f32(options.metrics_interval.max(1.0)))
+        .build()?;
+
+    let meter = provider.meter("meater");
+    let values = Shared::default();
+
+    // The gauges have to be kept alive for their callbacks to be called.
+    let gauges = GAUGES.map(|gauge| {
+        let values = values.clone();
+        let value = gauge.value;
+
+        meter
+            .f64_observable_gauge(gauge.name)
+            .with_description(gauge.description)
+            .with_unit(Unit::new(gauge.unit))
+            .with_callback(move |observer| {
+                let Ok(values) = values.lock() else {
+                    return;
+                };
+
+                for (probe, probe_values) in values.iter() {
+                    if let Some(value) = value(probe_values) {
+                        observer.observe(value, &[KeyValue::new("probe", *probe as i64 + 1)]);
+                    }
+                }
+            })
+            .init()
+    });
+
+    while let Some(event) = receiver.recv().await {
+        let Ok(mut values) = values.lock() else {
+            break;
+        };
+
+        match event {
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => {
+                let probe = values.entry(probe).or_default();
+                probe.tip = Some(f64::from(tip));
+                probe.ambient = Some(f64::from(ambient));
+            }
+            meater::Event::Battery { probe, percent } => {
+                values.entry(probe).or_default().battery = Some(f64::from(percent));
+            }
+            meater::Event::Rssi { probe, rssi } => {
+                values.entry(probe).or_default().rssi = Some(f64::from(rssi));
+            }
+            // Gauges of probes that are gone would keep reporting stale values.
+            meater::Event::State(meater::State::Disconnected) => values.clear(),
+            _ => {}
+        }
+    }
+
+    drop(gauges);
+    provider.shutdown()?;
+    Ok(())
+}
@@ -0,0 +1,249 @@
+//! Pages shown while a probe is connected and the manager switching between them.
+//!
+//! Each [`Page`] draws one view of the [`Model`] into the area left free by the status line and
+//! the progress bar. [`Pages`] holds the configured pages and switches between them on button
+//! presses and, if enabled, every few seconds.
+
+use std::time::{Duration, Instant};
+
+use embedded_graphics::geometry::Point;
+use embedded_graphics::primitives::Rectangle;
+use serde::Deserialize;
+
+use crate::display::DisplayBackend;
+use crate::font;
+use crate::icons::Icons;
+use crate::model::Model;
+use crate::units::Unit;
+
+mod about;
+mod ambient;
+mod battery;
+mod extremes;
+mod graph;
+mod info;
+mod session;
+mod timer;
+mod tip;
+
+pub use about::About;
+pub use ambient::Ambient;
+pub use battery::Battery;
+pub use extremes::Extremes;
+pub use graph::Graph;
+pub use info::Info;
+pub use session::Session;
+pub use timer::Timer;
+pub use tip::Tip;
+
+/// Everything a page is drawn from.
+pub struct Context<'a> {
+    pub model: &'a Model,
+    pub icons: &'a Icons,
+    pub unit: Unit,
+    /// Lowest row available to the page, the progress bar is drawn below.
+    pub bottom: i32,
+}
+
+impl Context<'_> {
+    /// Format a temperature given in °C in the configured unit.
+    pub fn format(&self, celsius: f32) -> String {
+        format!("{:.0}{}", self.unit.convert(celsius), self.unit.symbol())
+    }
+}
+
+/// A single view of the model.
+pub trait Page {
+    /// Name of the page for log messages.
+    fn name(&self) -> &'static str;
+
+    /// Draw the page onto `display`, which has at least one temperature.
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()>;
+}
+
+/// Page selectable in the configuration.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// Tip temperatures with estimate and trend.
+    Tip,
+    /// Ambient temperatures.
+    Ambient,
+    /// Battery level and signal strength of each probe.
+    Battery,
+    /// Tip temperature trend across the whole display.
+    Graph,
+    /// Duration and temperature range of the cook session.
+    Session,
+    /// Time since the cook timer was started.
+    Timer,
+    /// Lowest and highest tip and ambient temperatures of each probe.
+    Extremes,
+    /// Firmware revision and serial number of each probe.
+    Info,
+    /// Version and statistics of all cooks.
+    About,
+}
+
+impl Kind {
+    fn create(self) -> Box<dyn Page> {
+        match self {
+            Self::Tip => Box::new(Tip),
+            Self::Ambient => Box::new(Ambient),
+            Self::Battery => Box::new(Battery),
+            Self::Graph => Box::new(Graph),
+            Self::Session => Box::new(Session),
+            Self::Timer => Box::new(Timer),
+            Self::Extremes => Box::new(Extremes),
+            Self::Info => Box::new(Info),
+            Self::About => Box::new(About),
+        }
+    }
+}
+
+/// Page settings.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Pages in the order they are cycled through, the first one is shown initially.
+    pub pages: Vec<Kind>,
+    /// Seconds after which the next page is shown automatically.
+    pub cycle_seconds: Option<f32>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            pages: vec![
+                Kind::Tip,
+                Kind::Ambient,
+                Kind::Battery,
+                Kind::Graph,
+                Kind::Session,
+                Kind::Timer,
+                Kind::Extremes,
+                Kind::Info,
+                Kind::About,
+            ],
+            cycle_seconds: None,
+        }
+    }
+}
+
+impl Options {
+    /// Time after which the next page is shown automatically.
+    pub fn cycle_interval(&self) -> Option<Duration> {
+        self.cycle_seconds
+            .map(|seconds| Duration::from_secs_f32(seconds.max(1.0)))
+    }
+}
+
+/// Configured pages and the one currently shown.
+pub struct Pages {
+    pages: Vec<Box<dyn Page>>,
+    current: usize,
+    cycle: Option<Duration>,
+    /// Time the current page was switched to.
+    switched: Instant,
+}
+
+impl Default for Pages {
+    fn default() -> Self {
+        Self::new(&Options::default())
+    }
+}
+
+impl Pages {
+    /// Create the pages of `options`, the tip page if there are none.
+    pub fn new(options: &Options) -> Self {
+        let mut pages: Vec<_> = options.pages.iter().map(|kind| kind.create()).collect();
+
+        if pages.is_empty() {
+            pages.push(Kind::Tip.create());
+        }
+
+        Self {
+            pages,
+            current: 0,
+            cycle: options.cycle_interval(),
+            switched: Instant::now(),
+        }
+    }
+
+    /// Page currently shown.
+    pub fn current(&self) -> &dyn Page {
+        self.pages[self.current].as_ref()
+    }
+
+    /// Show the next page.
+    pub fn next(&mut self) {
+        self.show((self.current + 1) % self.pages.len());
+    }
+
+    /// Show the previous page.
+    pub fn previous(&mut self) {
+        self.show((self.current + self.pages.len() - 1) % self.pages.len());
+    }
+
+    /// Show the next page if the current one was shown long enough and return `true` if it
+    /// switched.
+    pub fn tick(&mut self) -> bool {
+        let due = self
+            .cycle
+            .is_some_and(|cycle| self.switched.elapsed() >= cycle);
+
+        if due && self.pages.len() > 1 {
+            self.next();
+            return true;
+        }
+
+        false
+    }
+
+    /// Switch to the page at `index`, a manual switch keeps it for a full cycle as well.
+    fn show(&mut self, index: usize) {
+        self.current = index;
+        self.switched = Instant::now();
+        tracing::debug!(page = self.current().name(), "switching page");
+    }
+}
+
+/// Draw the tip temperature trend of `probe` into `area` once there is one.
+fn render_trend(
+    display: &mut dyn DisplayBackend,
+    model: &Model,
+    probe: usize,
+    area: Rectangle,
+) -> anyhow::Result<()> {
+    let values: Vec<f32> = model
+        .trends
+        .get(&probe)
+        .map(|trend| trend.samples().collect())
+        .unwrap_or_default();
+
+    if values.len() > 1 {
+        display.draw_sparkline(&values, area)?;
+    }
+
+    Ok(())
+}
+
+/// Draw one line per probe with the text returned by `format` for its tip and ambient
+/// temperature.
+fn render_lines(
+    display: &mut dyn DisplayBackend,
+    model: &Model,
+    format: impl Fn((f32, f32)) -> String,
+) -> anyhow::Result<()> {
+    for (line, (probe, temperatures)) in model.temperatures.iter().enumerate() {
+        let text = format!("{}: {}", probe + 1, format(*temperatures));
+        display.draw_text(&text, line_position(line), font::MEDIUM.style())?;
+    }
+
+    Ok(())
+}
+
+/// Position of the `line`-th line of text in the medium font.
+fn line_position(line: usize) -> Point {
+    Point::new(0, 12 + 15 * line as i32)
+}
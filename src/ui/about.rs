@@ -0,0 +1,35 @@
+//! Version and statistics of all cooks, in total and of the current day.
+
+use crate::display::DisplayBackend;
+use crate::font;
+
+use super::{Context, Page};
+
+pub struct About;
+
+impl Page for About {
+    fn name(&self) -> &'static str {
+        "about"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let stats = &context.model.stats;
+        let (total, today) = (stats.total(), stats.today());
+        let style = font::SMALL.style();
+
+        let max = |tip: Option<f32>| tip.map_or_else(|| "-".to_string(), |tip| context.format(tip));
+
+        let lines = [
+            format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            format!("Cooks {} ({} today)", total.cooks, today.cooks),
+            format!("Hours {:.1} ({:.1})", total.hours(), today.hours()),
+            format!("Max {} ({})", max(total.max_tip), max(today.max_tip)),
+        ];
+
+        for (line, text) in lines.iter().enumerate() {
+            display.draw_text(text, super::line_position(line), style)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+//! Ambient temperatures, in large if there is only one probe.
+
+use embedded_graphics::geometry::Point;
+
+use crate::display::DisplayBackend;
+use crate::font;
+
+use super::{Context, Page};
+
+pub struct Ambient;
+
+impl Page for Ambient {
+    fn name(&self) -> &'static str {
+        "ambient"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let model = context.model;
+        let probe_style = font::MEDIUM.style();
+
+        if model.temperatures.len() > 1 {
+            return super::render_lines(display, model, |(_, ambient)| {
+                format!("Amb {}", context.format(ambient))
+            });
+        }
+
+        let Some((tip, ambient)) = model.temperatures.values().next() else {
+            return Ok(());
+        };
+
+        display.draw_text("Ambient", Point::new(0, 12), probe_style)?;
+        display.draw_temperature(*ambient, context.unit, Point::new(0, 38), font::LARGE)?;
+
+        let text = format!("Tip {}", context.format(*tip));
+        display.draw_text(&text, Point::new(0, context.bottom - 4), probe_style)
+    }
+}
@@ -0,0 +1,44 @@
+//! Battery level, whether it is low or charging, and signal strength of each probe on one line
+//! each.
+
+use crate::display::DisplayBackend;
+use crate::font;
+
+use super::{Context, Page};
+
+pub struct Battery;
+
+impl Page for Battery {
+    fn name(&self) -> &'static str {
+        "battery"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let model = context.model;
+
+        for (line, probe) in model.temperatures.keys().enumerate() {
+            let battery = model
+                .batteries
+                .get(probe)
+                .map(|percent| format!("{percent}%"))
+                .unwrap_or_else(|| "-".to_string());
+
+            let state = match model.battery_states.get(probe) {
+                Some(meater::BatteryState::Low) => " low",
+                Some(meater::BatteryState::Charging) => " chg",
+                _ => "",
+            };
+
+            let rssi = model
+                .rssi
+                .get(probe)
+                .map(|rssi| format!("{rssi}dBm"))
+                .unwrap_or_default();
+
+            let text = format!("{}: {battery}{state} {rssi}", probe + 1);
+            display.draw_text(&text, super::line_position(line), font::MEDIUM.style())?;
+        }
+
+        Ok(())
+    }
+}
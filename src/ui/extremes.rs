@@ -0,0 +1,43 @@
+//! Lowest and highest tip and ambient temperatures of each probe, e.g. to see how much the pit
+//! temperature swung overnight.
+
+use embedded_graphics::geometry::Point;
+
+use crate::display::DisplayBackend;
+use crate::font;
+
+use super::{Context, Page};
+
+pub struct Extremes;
+
+impl Page for Extremes {
+    fn name(&self) -> &'static str {
+        "extremes"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let style = font::SMALL.style();
+
+        // Two lines per probe in the small font leave room for two probes.
+        for (index, (probe, extremes)) in context.model.extremes.iter().enumerate() {
+            let y = 10 + 22 * index as i32;
+
+            let text = format!(
+                "{}: tip {}-{}",
+                probe + 1,
+                context.format(extremes.tip_min),
+                context.format(extremes.tip_max)
+            );
+            display.draw_text(&text, Point::new(0, y), style)?;
+
+            let text = format!(
+                "   amb {}-{}",
+                context.format(extremes.ambient_min),
+                context.format(extremes.ambient_max)
+            );
+            display.draw_text(&text, Point::new(0, y + 10), style)?;
+        }
+
+        Ok(())
+    }
+}
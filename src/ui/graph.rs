@@ -0,0 +1,34 @@
+//! Tip temperature trend of the first probe across the whole width.
+
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::primitives::Rectangle;
+
+use crate::display::{DisplayBackend, WIDTH};
+use crate::font;
+
+use super::{Context, Page};
+
+pub struct Graph;
+
+impl Page for Graph {
+    fn name(&self) -> &'static str {
+        "graph"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let model = context.model;
+
+        let Some((probe, (tip, _))) = model.temperatures.iter().next() else {
+            return Ok(());
+        };
+
+        let text = format!("{}: {}", probe + 1, context.format(*tip));
+        display.draw_text(&text, Point::new(0, 9), font::SMALL.style())?;
+
+        let area = Rectangle::new(
+            Point::new(0, 14),
+            Size::new(WIDTH, context.bottom as u32 - 16),
+        );
+        super::render_trend(display, model, *probe, area)
+    }
+}
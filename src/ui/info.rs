@@ -0,0 +1,35 @@
+//! Firmware revision and serial number of each probe for troubleshooting.
+
+use embedded_graphics::geometry::Point;
+
+use crate::display::DisplayBackend;
+use crate::font;
+
+use super::{Context, Page};
+
+pub struct Info;
+
+impl Page for Info {
+    fn name(&self) -> &'static str {
+        "info"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let model = context.model;
+        let style = font::SMALL.style();
+
+        // Two lines per probe in the small font leave room for two probes.
+        for (index, probe) in model.temperatures.keys().enumerate() {
+            let device = model.devices.get(probe).cloned().unwrap_or_default();
+            let firmware = device.firmware.unwrap_or_else(|| "-".to_string());
+            let serial = device.serial.unwrap_or_else(|| "-".to_string());
+            let y = 10 + 22 * index as i32;
+
+            let text = format!("{}: fw {firmware}", probe + 1);
+            display.draw_text(&text, Point::new(0, y), style)?;
+            display.draw_text(&format!("   sn {serial}"), Point::new(0, y + 10), style)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+//! Duration of the cook session and the temperature range of each probe.
+
+use embedded_graphics::geometry::Point;
+
+use crate::display::DisplayBackend;
+use crate::font;
+use crate::session;
+
+use super::{Context, Page};
+
+pub struct Session;
+
+impl Page for Session {
+    fn name(&self) -> &'static str {
+        "session"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let Some(summary) = context.model.session.current() else {
+            return Ok(());
+        };
+
+        let text = format!("Cook {}", session::hours_minutes(summary.duration));
+        display.draw_text(&text, Point::new(0, 12), font::MEDIUM.style())?;
+
+        // Leave the first line to the duration.
+        for (line, (probe, probe_summary)) in summary.probes.iter().enumerate() {
+            let mut text = format!(
+                "{}: {}-{}",
+                probe + 1,
+                context.format(probe_summary.min),
+                context.format(probe_summary.max)
+            );
+
+            if probe_summary.target_reached.is_some() {
+                text.push_str(" ok");
+            }
+
+            let position = super::line_position(line + 1);
+            display.draw_text(&text, position, font::SMALL.style())?;
+        }
+
+        Ok(())
+    }
+}
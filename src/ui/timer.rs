@@ -0,0 +1,28 @@
+//! Time since the cook timer was started.
+
+use embedded_graphics::geometry::Point;
+
+use crate::display::DisplayBackend;
+use crate::font;
+use crate::session;
+
+use super::{Context, Page};
+
+pub struct Timer;
+
+impl Page for Timer {
+    fn name(&self) -> &'static str {
+        "timer"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let Some(started) = context.model.timer else {
+            return Ok(());
+        };
+
+        display.draw_text("Cook time", Point::new(0, 12), font::MEDIUM.style())?;
+
+        let text = session::hours_minutes(started.elapsed());
+        display.draw_text(&text, Point::new(0, 38), font::LARGE.style())
+    }
+}
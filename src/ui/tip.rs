@@ -0,0 +1,98 @@
+//! Tip temperatures, in large with estimate and trend if there is only one probe.
+
+use embedded_graphics::geometry::{OriginDimensions, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+
+use crate::alarm;
+use crate::carryover::Predictor;
+use crate::display::{DisplayBackend, Heat, WIDTH};
+use crate::font;
+use crate::model::TREND_SAMPLES;
+
+use super::{Context, Page};
+
+pub struct Tip;
+
+impl Page for Tip {
+    fn name(&self) -> &'static str {
+        "tip"
+    }
+
+    fn render(&self, display: &mut dyn DisplayBackend, context: &Context) -> anyhow::Result<()> {
+        let model = context.model;
+        let probe_style = font::MEDIUM.style();
+
+        if model.temperatures.len() > 1 {
+            // Several probes, e.g. from a MEATER Block, get one line each.
+            return super::render_lines(display, model, |(tip, ambient)| {
+                format!("{} {}", context.format(tip), context.format(ambient))
+            });
+        }
+
+        let Some((probe, (tip, ambient))) = model.temperatures.iter().next() else {
+            return Ok(());
+        };
+
+        let area = display.draw_temperature(*tip, context.unit, Point::new(0, 38), font::LARGE)?;
+
+        // Color displays show how close the tip is to its target.
+        if let Some(progress) = model.progress(*probe) {
+            display.highlight(area, Heat::new(progress));
+        }
+
+        // Predicted peak while resting in the top left corner.
+        let peak = model.carryover.get(probe).and_then(Predictor::peak);
+
+        // Otherwise the current rate of change, e.g. to judge whether to open the vents.
+        let rate = model
+            .estimators
+            .get(probe)
+            .and_then(|estimator| estimator.per_minute());
+
+        if let Some(peak) = peak {
+            let text = format!("rest->~{:.1}", context.unit.convert(peak));
+            display.draw_text(&text, Point::new(0, 9), font::SMALL.style())?;
+        } else if let Some(rate) = rate {
+            let text = format!("{:+.1}/m", context.unit.convert_difference(rate));
+            display.draw_text(&text, Point::new(0, 9), font::SMALL.style())?;
+        }
+
+        // The estimate is paused while the probe is pulled out of the meat.
+        if model.removed.contains(probe) {
+            let icon = &context.icons.removed;
+            let x = WIDTH as i32 - icon.size().width as i32;
+            display.draw_icon(icon, Point::new(x, 26))?;
+        }
+
+        // Estimated time until the target is reached right of the temperature, meaningless while
+        // the meat is in the stall or resting.
+        let eta = alarm::target(&model.targets, *probe)
+            .and_then(|target| model.estimators.get(probe)?.eta(target))
+            .filter(|_| peak.is_none());
+
+        let text = if model.stall.is_stalled(*probe) {
+            Some("stall".to_string())
+        } else {
+            eta.map(|eta| {
+                let minutes = eta.as_secs() / 60;
+                format!("~{}:{:02}", minutes / 60, minutes % 60)
+            })
+        };
+
+        if let Some(text) = text {
+            let x = font::MEDIUM.right_aligned(&text, WIDTH as i32);
+            display.draw_text(&text, Point::new(x, 38), probe_style)?;
+        }
+
+        let bottom = context.bottom;
+        let text = format!("Amb {}", context.format(*ambient));
+        display.draw_text(&text, Point::new(0, bottom - 4), probe_style)?;
+
+        // Trend of the last minutes in the bottom right corner.
+        let area = Rectangle::new(
+            Point::new(WIDTH as i32 - TREND_SAMPLES as i32, 43),
+            Size::new(TREND_SAMPLES as u32, bottom as u32 - 43),
+        );
+        super::render_trend(display, model, *probe, area)
+    }
+}
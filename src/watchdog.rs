@@ -0,0 +1,88 @@
+//! Integration with the systemd service manager.
+//!
+//! Readiness is signalled once the display is set up. If the service is configured with
+//! `WatchdogSec=`, the watchdog is pinged as long as events keep flowing: while a probe is
+//! connected its temperature notifications must not stall for longer than the watchdog timeout,
+//! otherwise systemd is left to restart the service.
+
+use std::time::{Duration, Instant};
+
+use sd_notify::NotifyState;
+use tokio::sync::mpsc;
+
+/// Notify systemd that startup is complete.
+pub fn ready() {
+    if let Err(err) = sd_notify::notify(false, &[NotifyState::Ready]) {
+        tracing::warn!("unable to notify systemd: {err}");
+    }
+}
+
+/// Return the watchdog timeout if the service manager requested pings.
+pub fn timeout() -> Option<Duration> {
+    let mut usec = 0;
+    sd_notify::watchdog_enabled(false, &mut usec).then(|| Duration::from_micros(usec))
+}
+
+/// Tracks whether the event stream is alive.
+struct Health {
+    connected: bool,
+    last_notification: Instant,
+}
+
+impl Health {
+    fn update(&mut self, event: &meater::Event) {
+        match event {
+            meater::Event::State(state) => {
+                self.connected = *state == meater::State::Connected;
+                self.last_notification = Instant::now();
+            }
+            meater::Event::Temperature { .. } | meater::Event::Battery { .. } => {
+                self.last_notification = Instant::now();
+            }
+            // Derived from the battery level and sent along with it.
+            meater::Event::BatteryState { .. } => {}
+            // The signal strength is polled by the client and says nothing about notifications.
+            meater::Event::Rssi { .. }
+            | meater::Event::Sensors { .. }
+            | meater::Event::ProbeRemoved { .. }
+            | meater::Event::ProbeInserted { .. }
+            | meater::Event::DecodeError { .. }
+            | meater::Event::DeviceInfo { .. }
+            | meater::Event::Error { .. }
+            | meater::Event::SessionEnded => {}
+        }
+    }
+
+    /// Return `true` unless a connected probe stopped sending notifications for `timeout`.
+    fn is_healthy(&self, timeout: Duration) -> bool {
+        !self.connected || self.last_notification.elapsed() < timeout
+    }
+}
+
+/// Ping the watchdog every half `timeout` while the events on `receiver` indicate a healthy client.
+pub async fn run(timeout: Duration, mut receiver: mpsc::Receiver<meater::Event>) {
+    let mut health = Health {
+        connected: false,
+        last_notification: Instant::now(),
+    };
+
+    let mut interval = tokio::time::interval(timeout / 2);
+
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Some(event) => health.update(&event),
+                None => break,
+            },
+            _ = interval.tick() => {
+                if health.is_healthy(timeout) {
+                    if let Err(err) = sd_notify::notify(false, &[NotifyState::Watchdog]) {
+                        tracing::warn!("unable to ping watchdog: {err}");
+                    }
+                } else {
+                    tracing::warn!("notifications stalled, no longer pinging watchdog");
+                }
+            }
+        }
+    }
+}
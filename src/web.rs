@@ -0,0 +1,454 @@
+//! Web dashboard and REST API showing the live values of all probes.
+//!
+//! The page served at `/` connects to the WebSocket at `/live`, which first sends the complete
+//! state including the temperatures of the current session and then every change as it happens.
+//! Targets in °C between 0 and 300 are set with a `PUT` of a JSON array to `/targets`.
+//!
+//! Other tools can poll `GET /state` for the current values and `GET /history?since=<seconds>`
+//! for the samples of the current session taken after the given Unix time, or subscribe to the
+//! WebSocket at `/ws` pushing every event of the client as JSON object tagged by its `type`. All
+//! temperatures are in °C.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::net::SocketAddr;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, Response};
+use axum::routing::{get, put};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, watch};
+
+use crate::input;
+use crate::json;
+use crate::units::Unit;
+
+const PAGE: &str = include_str!("assets/dashboard.html");
+
+/// Targets in °C accepted from the dashboard, covering anything a probe can be put into.
+const TARGETS: RangeInclusive<f32> = 0.0..=300.0;
+
+/// Minimum time between two samples of the same probe kept for the session graph.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Number of samples kept for the session graph, a day of a single probe.
+const MAX_SAMPLES: usize = 24 * 60 * 4;
+
+/// Web dashboard settings.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Address to serve the dashboard on, disabled if not set.
+    pub listen: Option<SocketAddr>,
+}
+
+/// Latest values of a probe.
+#[derive(Clone, Default, Serialize)]
+struct Probe {
+    tip: Option<f32>,
+    ambient: Option<f32>,
+    battery: Option<u16>,
+    /// Whether the battery is low or charging.
+    battery_state: Option<String>,
+    rssi: Option<i16>,
+    /// Temperature at the surface of the meat if the probe determines it.
+    surface: Option<f32>,
+    /// Pulled out of the meat.
+    removed: bool,
+    firmware: Option<String>,
+    serial: Option<String>,
+}
+
+/// Temperatures of a probe at a point in time.
+#[derive(Clone, Copy, Serialize)]
+struct Sample {
+    /// Seconds since the Unix epoch.
+    time: i64,
+    probe: usize,
+    tip: f32,
+    ambient: f32,
+}
+
+/// Current values as returned by `GET /state`.
+#[derive(Serialize)]
+struct Current {
+    state: String,
+    probes: BTreeMap<usize, Probe>,
+    targets: Vec<f32>,
+}
+
+/// Query of `GET /history`.
+#[derive(Deserialize)]
+struct HistoryQuery {
+    /// Only return samples taken after this Unix time.
+    since: Option<i64>,
+}
+
+/// Everything shown on the dashboard.
+struct Dashboard {
+    state: meater::State,
+    probes: BTreeMap<usize, Probe>,
+    /// Target temperatures in °C, the n-th applies to the n-th probe, the last to all others.
+    targets: Vec<f32>,
+    /// Samples of the current session, oldest first.
+    history: VecDeque<Sample>,
+    /// The session ended and the history is cleared with the next temperature.
+    ended: bool,
+}
+
+/// Message sent over the WebSocket.
+#[derive(Serialize)]
+struct Update {
+    /// Replace all samples instead of appending them.
+    snapshot: bool,
+    unit: Unit,
+    #[serde(flatten)]
+    current: Current,
+    samples: Vec<Sample>,
+}
+
+impl Dashboard {
+    fn new(targets: Vec<f32>) -> Self {
+        Self {
+            state: meater::State::Disconnected,
+            probes: BTreeMap::new(),
+            targets,
+            history: VecDeque::new(),
+            ended: false,
+        }
+    }
+
+    /// Apply `event` and return the sample it added to the history.
+    fn update(&mut self, event: &meater::Event) -> Option<Sample> {
+        match *event {
+            meater::Event::State(state) => {
+                self.state = state;
+
+                if state == meater::State::Disconnected {
+                    self.probes.clear();
+                    self.ended = true;
+                }
+
+                None
+            }
+            meater::Event::Temperature {
+                probe,
+                tip,
+                ambient,
+            } => {
+                if self.ended {
+                    self.history.clear();
+                    self.ended = false;
+                }
+
+                let values = self.probes.entry(probe).or_default();
+                values.tip = Some(tip);
+                values.ambient = Some(ambient);
+
+                let time = chrono::Local::now().timestamp();
+
+                let due = self
+                    .history
+                    .iter()
+                    .rev()
+                    .find(|sample| sample.probe == probe)
+                    .is_none_or(|sample| time - sample.time >= SAMPLE_INTERVAL.as_secs() as i64);
+
+                if !due {
+                    return None;
+                }
+
+                if self.history.len() == MAX_SAMPLES {
+                    self.history.pop_front();
+                }
+
+                let sample = Sample {
+                    time,
+                    probe,
+                    tip,
+                    ambient,
+                };
+
+                self.history.push_back(sample);
+                Some(sample)
+            }
+            meater::Event::Battery { probe, percent } => {
+                self.probes.entry(probe).or_default().battery = Some(percent);
+                None
+            }
+            meater::Event::BatteryState { probe, state } => {
+                self.probes.entry(probe).or_default().battery_state = Some(state.to_string());
+                None
+            }
+            meater::Event::Rssi { probe, rssi } => {
+                self.probes.entry(probe).or_default().rssi = Some(rssi);
+                None
+            }
+            meater::Event::Sensors { probe, surface, .. } => {
+                self.probes.entry(probe).or_default().surface = surface;
+                None
+            }
+            meater::Event::ProbeRemoved { probe } => {
+                self.probes.entry(probe).or_default().removed = true;
+                None
+            }
+            meater::Event::ProbeInserted { probe } => {
+                self.probes.entry(probe).or_default().removed = false;
+                None
+            }
+            meater::Event::DeviceInfo {
+                probe,
+                ref firmware,
+                ref serial,
+            } => {
+                let values = self.probes.entry(probe).or_default();
+                values.firmware = firmware.clone();
+                values.serial = serial.clone();
+                None
+            }
+            meater::Event::SessionEnded => {
+                self.ended = true;
+                None
+            }
+            meater::Event::DecodeError { .. } | meater::Event::Error { .. } => None,
+        }
+    }
+
+    fn current(&self) -> Current {
+        Current {
+            state: self.state.to_string(),
+            probes: self.probes.clone(),
+            targets: self.targets.clone(),
+        }
+    }
+
+    /// Return the JSON message containing the current values and `samples`.
+    fn message(&self, unit: Unit, snapshot: bool, samples: Vec<Sample>) -> String {
+        let update = Update {
+            snapshot,
+            unit,
+            current: self.current(),
+            samples,
+        };
+
+        serde_json::to_string(&update).unwrap_or_default()
+    }
+
+    /// Return the JSON message containing everything including the complete history.
+    fn snapshot(&self, unit: Unit) -> String {
+        self.message(unit, true, self.history.iter().copied().collect())
+    }
+}
+
+/// State shared by all requests.
+struct Shared {
+    unit: Unit,
+    dashboard: watch::Sender<Dashboard>,
+    /// Messages for all connected dashboards.
+    updates: broadcast::Sender<String>,
+    /// Events for all connected event streams.
+    events: broadcast::Sender<String>,
+    /// Forwards targets set on the dashboard to the UI.
+    input: mpsc::Sender<input::Event>,
+}
+
+async fn index() -> Html<&'static str> {
+    Html(PAGE)
+}
+
+async fn live(ws: WebSocketUpgrade, State(shared): State<Arc<Shared>>) -> Response {
+    ws.on_upgrade(move |socket| stream(socket, shared))
+}
+
+/// Send the current state and all following updates to `socket` until it is closed.
+async fn stream(mut socket: WebSocket, shared: Arc<Shared>) {
+    let mut updates = shared.updates.subscribe();
+    let snapshot = shared.dashboard.borrow().snapshot(shared.unit);
+
+    if socket.send(Message::Text(snapshot)).await.is_err() {
+        return;
+    }
+
+    loop {
+        let message = tokio::select! {
+            update = updates.recv() => match update {
+                Ok(update) => update,
+                // Samples were missed, start over.
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    shared.dashboard.borrow().snapshot(shared.unit)
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            received = socket.recv() => match received {
+                Some(Ok(_)) => continue,
+                _ => return,
+            },
+        };
+
+        if socket.send(Message::Text(message)).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn state(State(shared): State<Arc<Shared>>) -> Json<Current> {
+    Json(shared.dashboard.borrow().current())
+}
+
+async fn history(
+    State(shared): State<Arc<Shared>>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<Vec<Sample>> {
+    let since = query.since.unwrap_or(i64::MIN);
+
+    let samples = shared
+        .dashboard
+        .borrow()
+        .history
+        .iter()
+        .filter(|sample| sample.time > since)
+        .copied()
+        .collect();
+
+    Json(samples)
+}
+
+async fn event_stream(ws: WebSocketUpgrade, State(shared): State<Arc<Shared>>) -> Response {
+    ws.on_upgrade(move |socket| stream_events(socket, shared))
+}
+
+/// Send all following events to `socket` until it is closed.
+async fn stream_events(mut socket: WebSocket, shared: Arc<Shared>) {
+    let mut events = shared.events.subscribe();
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!("event stream is lagging behind, dropped {missed} events");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            received = socket.recv() => match received {
+                Some(Ok(_)) => continue,
+                _ => return,
+            },
+        };
+
+        if socket.send(Message::Text(event)).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn set_targets(
+    State(shared): State<Arc<Shared>>,
+    Json(targets): Json<Vec<f32>>,
+) -> StatusCode {
+    if !targets.iter().all(|target| TARGETS.contains(target)) {
+        return StatusCode::UNPROCESSABLE_ENTITY;
+    }
+
+    tracing::info!(targets = ?targets, "targets set on dashboard");
+
+    match shared.input.send(input::Event::Targets(targets)).await {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+/// Serve the dashboard on `address` and keep it up to date with the events received on
+/// `receiver` and changes of `targets`.
+pub async fn run(
+    address: SocketAddr,
+    unit: Unit,
+    mut targets: watch::Receiver<Vec<f32>>,
+    input: mpsc::Sender<input::Event>,
+    mut receiver: mpsc::Receiver<meater::Event>,
+) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(address)
+        .await
+        .with_context(|| format!("unable to listen on {address}"))?;
+
+    tracing::info!("serving dashboard on http://{address}");
+
+    let (updates, _) = broadcast::channel(16);
+    let (events, _) = broadcast::channel(64);
+    let (dashboard, _) = watch::channel(Dashboard::new(targets.borrow_and_update().clone()));
+
+    let shared = Arc::new(Shared {
+        unit,
+        dashboard,
+        updates,
+        events,
+        input,
+    });
+
+    let app = Router::new()
+        .route("/", get(index))
+        .route("/live", get(live))
+        .route("/targets", put(set_targets))
+        .route("/state", get(state))
+        .route("/history", get(history))
+        .route("/ws", get(event_stream))
+        .with_state(shared.clone());
+
+    let serving = async {
+        axum::serve(listener, app)
+            .await
+            .context("web server failed")
+    };
+
+    let updating = async {
+        loop {
+            let sample = tokio::select! {
+                event = receiver.recv() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+
+                    // Nobody may be subscribed.
+                    let _ = shared.events.send(json::event(&event).to_string());
+
+                    let mut sample = None;
+                    shared.dashboard.send_modify(|dashboard| sample = dashboard.update(&event));
+                    sample
+                }
+                changed = targets.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+
+                    let targets = targets.borrow_and_update().clone();
+                    shared.dashboard.send_modify(|dashboard| dashboard.targets = targets);
+                    None
+                }
+            };
+
+            let message =
+                shared
+                    .dashboard
+                    .borrow()
+                    .message(shared.unit, false, sample.into_iter().collect());
+
+            // Nobody may be watching.
+            let _ = shared.updates.send(message);
+        }
+
+        Ok(())
+    };
+
+    tokio::select! {
+        result = serving => result,
+        result = updating => result,
+    }
+}
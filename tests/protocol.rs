@@ -0,0 +1,353 @@
+use meater::combustion::{Advertisement, SENSORS};
+use meater::protocol::{
+    ambient, decode_battery, decode_classic, decode_sensors, layout, to_degree_celsius, to_u16,
+    NamePattern, NAME, PROBE, SERVICE_UUID,
+};
+use meater::{inkbird, onewire};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Number of random payloads decoded by the tests checking decoding never panics.
+const RANDOM_PAYLOADS: usize = 100_000;
+
+/// Return `count` payloads with random bytes and a length within `lengths`.
+fn random_payloads(
+    count: usize,
+    lengths: std::ops::RangeInclusive<usize>,
+) -> impl Iterator<Item = Vec<u8>> {
+    let mut rng = StdRng::seed_from_u64(0x6d65_6174_6572);
+
+    (0..count).map(move |_| {
+        let length = rng.gen_range(lengths.clone());
+        (0..length).map(|_| rng.gen()).collect()
+    })
+}
+
+#[test]
+fn combine_bytes() {
+    assert_eq!(to_u16(0x00, 0x00), 0);
+    assert_eq!(to_u16(0x00, 0xff), 255);
+    assert_eq!(to_u16(0x01, 0x00), 256);
+    assert_eq!(to_u16(0xff, 0xff), u16::MAX);
+}
+
+#[test]
+fn convert_to_degree_celsius() {
+    assert_eq!(to_degree_celsius(0), 0.5);
+    assert_eq!(to_degree_celsius(312), 20.0);
+    assert_eq!(to_degree_celsius(1112), 70.0);
+}
+
+#[test]
+fn compute_ambient() {
+    assert_eq!(ambient(312, 48, 48), Some(312));
+    assert_eq!(ambient(312, 52, 48), Some(312 + 25));
+    assert_eq!(ambient(312, 25, 20), Some(312 + 31));
+}
+
+#[test]
+fn decode_classic_payload() {
+    assert_eq!(
+        decode_classic(&[0x38, 0x01, 0x34, 0x00, 0x30, 0x00, 0x00, 0x00]),
+        Some((20.0, to_degree_celsius(312 + 25)))
+    );
+    assert_eq!(decode_classic(&[0x38, 0x01]), None);
+}
+
+#[test]
+fn decode_typical_classic_payloads() {
+    // Probe in the fridge, tip and ambient sensor at the same temperature.
+    assert_eq!(
+        decode_classic(&[0x38, 0x00, 0x30, 0x00, 0x30, 0x00, 0x00, 0x00]),
+        Some((4.0, 4.0))
+    );
+    // Probe in a roast, the ambient sensor above the tip.
+    assert_eq!(
+        decode_classic(&[0x68, 0x03, 0x36, 0x00, 0x38, 0x00, 0x00, 0x00]),
+        Some((55.0, to_degree_celsius(910)))
+    );
+    // The trailing bytes do not matter.
+    assert_eq!(
+        decode_classic(&[0x68, 0x03, 0x36, 0x00, 0x38, 0x00, 0xff, 0xff]),
+        Some((55.0, to_degree_celsius(910)))
+    );
+}
+
+#[test]
+fn decode_classic_boundaries() {
+    assert_eq!(
+        decode_classic(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+        Some((0.5, 0.5))
+    );
+    assert_eq!(decode_classic(&[]), None);
+    assert_eq!(decode_classic(&[0x00; 7]), None);
+    assert_eq!(decode_classic(&[0x00; 9]), None);
+}
+
+#[test]
+fn compute_ambient_out_of_range() {
+    // Used to underflow with ra below the smaller of oa and 48, the ambient is never below the
+    // tip.
+    assert_eq!(ambient(312, 20, 48), Some(312));
+    assert_eq!(ambient(312, 0, 20), Some(312));
+    assert_eq!(ambient(0, 0, 48), Some(0));
+    // Used to overflow with ra more than a few steps above.
+    assert_eq!(ambient(872, 106, 56), Some(1239));
+    assert_eq!(ambient(u16::MAX, 49, 48), None);
+    assert_eq!(ambient(0, u16::MAX, 0), None);
+}
+
+#[test]
+fn decode_classic_regressions() {
+    // Roast with the ambient sensor far above the tip.
+    assert_eq!(
+        decode_classic(&[0x68, 0x03, 0x6a, 0x00, 0x38, 0x00, 0x00, 0x00]),
+        Some((55.0, to_degree_celsius(1239)))
+    );
+    // Ambient sensor below the tip while the meat rests.
+    assert_eq!(
+        decode_classic(&[0x38, 0x01, 0x14, 0x00, 0x30, 0x00, 0x00, 0x00]),
+        Some((20.0, 20.0))
+    );
+    // Garbage sensor values.
+    assert_eq!(
+        decode_classic(&[0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00]),
+        None
+    );
+}
+
+#[test]
+fn decode_classic_never_panics() {
+    for payload in random_payloads(RANDOM_PAYLOADS, 8..=8) {
+        decode_classic(&payload);
+    }
+}
+
+#[test]
+fn decode_sensors_payload() {
+    // Internal sensors at 70, 60 and 70 °C followed by an ambient of 100 °C.
+    assert_eq!(
+        decode_sensors(&[0x58, 0x04, 0xb8, 0x03, 0x58, 0x04, 0x38, 0x06]),
+        Some((60.0, 100.0))
+    );
+    assert_eq!(
+        decode_sensors(&[0x58, 0x04, 0x38, 0x01]),
+        Some((70.0, 20.0))
+    );
+    assert_eq!(decode_sensors(&[0x58, 0x04]), None);
+    assert_eq!(decode_sensors(&[0x58, 0x04, 0x38]), None);
+}
+
+#[test]
+fn decode_sensors_never_panics() {
+    for payload in random_payloads(RANDOM_PAYLOADS, 0..=20) {
+        let decoded = decode_sensors(&payload);
+        assert_eq!(
+            decoded.is_some(),
+            payload.len() >= 4 && payload.len() % 2 == 0
+        );
+    }
+}
+
+#[test]
+fn decode_battery_payload() {
+    assert_eq!(decode_battery(&[0x0a, 0x00]), Some(100));
+    assert_eq!(decode_battery(&[0x08, 0x00]), Some(80));
+    assert_eq!(decode_battery(&[0x00, 0x00]), Some(0));
+    assert_eq!(decode_battery(&[0x0b, 0x00]), Some(100));
+    assert_eq!(decode_battery(&[0x64, 0x00]), Some(100));
+    assert_eq!(decode_battery(&[0xff, 0xff]), None);
+    assert_eq!(decode_battery(&[0x0a]), None);
+    assert_eq!(decode_battery(&[]), None);
+}
+
+#[test]
+fn decode_observed_battery_payloads() {
+    // A probe draining over a long cook, read right out of the charger and relayed by a block.
+    let observed: [(&[u8], u16); 7] = [
+        (&[0x0a, 0x00], 100),
+        (&[0x09, 0x00], 90),
+        (&[0x06, 0x00], 60),
+        (&[0x03, 0x00], 30),
+        (&[0x01, 0x00], 10),
+        (&[0x0c, 0x00], 100),
+        (&[0x08, 0x00, 0x00, 0x00], 80),
+    ];
+
+    for (payload, percent) in observed {
+        assert_eq!(decode_battery(payload), Some(percent), "{payload:02x?}");
+    }
+}
+
+#[test]
+fn decode_battery_never_panics() {
+    for payload in random_payloads(RANDOM_PAYLOADS, 0..=8) {
+        if let Some(percent) = decode_battery(&payload) {
+            assert!(percent <= 100);
+        }
+    }
+}
+
+/// Parse `patterns` as they are given in the configuration.
+fn patterns(patterns: &[&str]) -> Vec<NamePattern> {
+    patterns
+        .iter()
+        .map(|pattern| pattern.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn parse_name_patterns() {
+    let parsed = patterns(&["MEATER", "prefix:MEATER ", "regex:^MTR-\\d+$"]);
+    let (exact, prefix, regex) = (&parsed[0], &parsed[1], &parsed[2]);
+
+    assert!(matches!(exact, NamePattern::Exact(name) if name == "MEATER"));
+    assert!(matches!(prefix, NamePattern::Prefix(name) if name == "MEATER "));
+    assert!(matches!(regex, NamePattern::Regex(_)));
+
+    // Patterns are shown as they are given.
+    assert_eq!(exact.to_string(), "MEATER");
+    assert_eq!(prefix.to_string(), "prefix:MEATER ");
+    assert_eq!(regex.to_string(), "regex:^MTR-\\d+$");
+
+    assert!(exact.matches("MEATER"));
+    assert!(!exact.matches("MEATER 2"));
+    assert!(prefix.matches("MEATER 2"));
+    assert!(!prefix.matches("MEATER"));
+    assert!(regex.matches("MTR-42"));
+    assert!(!regex.matches("MTR-42x"));
+
+    // Only the known prefixes are special, anything else is an exact name.
+    let other = "block:MEATER".parse();
+    assert!(matches!(other, Ok(NamePattern::Exact(name)) if name == "block:MEATER"));
+}
+
+#[test]
+fn reject_invalid_regex() {
+    assert!("regex:(".parse::<NamePattern>().is_err());
+    assert!("regex:[a-".parse::<NamePattern>().is_err());
+    // Without the prefix, the text is a name and never invalid.
+    assert!("(".parse::<NamePattern>().is_ok());
+}
+
+#[test]
+fn recognize_layout_by_name() {
+    let default = patterns(&[NAME]);
+
+    assert_eq!(layout(Some("MEATER"), &[], &default), Some(PROBE));
+    assert_eq!(layout(Some("MEATER 2"), &[], &default), None);
+    assert_eq!(layout(None, &[], &default), None);
+
+    let names = patterns(&["prefix:MEATER ", "regex:^Grill \\d$"]);
+
+    assert_eq!(layout(Some("MEATER 2"), &[], &names), Some(PROBE));
+    assert_eq!(layout(Some("Grill 1"), &[], &names), Some(PROBE));
+    assert_eq!(layout(Some("MEATER"), &[], &names), None);
+}
+
+#[test]
+fn recognize_layout_by_service_only() {
+    assert_eq!(layout(Some("MEATER"), &[], &[]), None);
+    assert_eq!(layout(None, &[SERVICE_UUID], &[]), Some(PROBE));
+    assert_eq!(layout(Some("Grill"), &[SERVICE_UUID], &[]), Some(PROBE));
+}
+
+#[test]
+fn decode_inkbird_frames() {
+    // IBT-4XS with a probe in the meat, none in the second channel, one on the grill and one in
+    // the freezer.
+    assert_eq!(
+        inkbird::decode(&[0xfa, 0x00, 0xf6, 0xff, 0x4e, 0x02, 0xec, 0xff]),
+        [Some(25.0), None, Some(59.0), Some(-2.0)]
+    );
+    // IBT-2X with both probes plugged in.
+    assert_eq!(
+        inkbird::decode(&[0x0f, 0x01, 0xd2, 0x04]),
+        [Some(27.1), Some(123.4)]
+    );
+    // A trailing odd byte is no channel.
+    assert_eq!(inkbird::decode(&[0xfa, 0x00, 0x01]), [Some(25.0)]);
+    assert!(inkbird::decode(&[]).is_empty());
+}
+
+/// Manufacturer data of a probe with serial 0x12345678 and thermistors from 40 °C at the tip down
+/// to 5 °C at the handle, followed by `status`.
+fn combustion_frame(status: u8) -> Vec<u8> {
+    let mut frame = vec![
+        0x01, 0x78, 0x56, 0x34, 0x12, 0xb0, 0x84, 0x89, 0xa0, 0x0f, 0xc2, 0x01, 0x32, 0x78, 0x05,
+        0x96, 0xa0, 0x0f, 0x00,
+    ];
+    frame.push(status);
+    frame
+}
+
+#[test]
+fn parse_combustion_advertisement() {
+    // Low battery, core T3, surface T5 and ambient T8.
+    let advertisement = Advertisement::parse(&combustion_frame(0xd5)).unwrap();
+
+    assert_eq!(advertisement.serial, 0x1234_5678);
+    assert_eq!(advertisement.core, 2);
+    assert_eq!(advertisement.surface, 4);
+    assert_eq!(advertisement.ambient, 7);
+    assert!(advertisement.battery_low);
+
+    let expected: [f32; SENSORS] = [40.0, 35.0, 30.0, 25.0, 20.0, 15.0, 10.0, 5.0];
+
+    for (index, (sensor, expected)) in advertisement.sensors.iter().zip(expected).enumerate() {
+        assert!((sensor - expected).abs() < 0.01, "T{}: {sensor}", index + 1);
+    }
+}
+
+#[test]
+fn parse_combustion_status() {
+    // Battery fine, core T1, surface T4 and ambient T5.
+    let advertisement = Advertisement::parse(&combustion_frame(0x00)).unwrap();
+    assert_eq!(advertisement.core, 0);
+    assert_eq!(advertisement.surface, 3);
+    assert_eq!(advertisement.ambient, 4);
+    assert!(!advertisement.battery_low);
+
+    // The core is never further back than T6.
+    let advertisement = Advertisement::parse(&combustion_frame(0x0e)).unwrap();
+    assert_eq!(advertisement.core, 5);
+}
+
+#[test]
+fn reject_other_combustion_frames() {
+    let frame = combustion_frame(0x00);
+
+    assert_eq!(Advertisement::parse(&frame[..19]), None);
+    assert_eq!(Advertisement::parse(&[]), None);
+
+    // Another product than the probe, e.g. the display.
+    let mut other = frame;
+    other[0] = 0x02;
+    assert_eq!(Advertisement::parse(&other), None);
+}
+
+#[test]
+fn parse_onewire_readings() {
+    let room = "72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n72 01 4b 46 7f ff 0e 10 57 t=23125\n";
+    assert_eq!(onewire::parse(room), Some(23.125));
+
+    let freezer = "90 fe 4b 46 7f ff 0c 10 1c : crc=1c YES\n90 fe 4b 46 7f ff 0c 10 1c t=-22000\n";
+    assert_eq!(onewire::parse(freezer), Some(-22.0));
+}
+
+#[test]
+fn reject_failed_onewire_readings() {
+    // Garbled by a loose wire.
+    let corrupt = "72 01 4b 46 7f ff 0e 10 57 : crc=12 NO\n72 01 4b 46 7f ff 0e 10 57 t=23125\n";
+    assert_eq!(onewire::parse(corrupt), None);
+
+    assert_eq!(
+        onewire::parse("72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\n"),
+        None
+    );
+    assert_eq!(
+        onewire::parse("72 01 4b 46 7f ff 0e 10 57 : crc=57 YES\nt=\n"),
+        None
+    );
+    assert_eq!(onewire::parse(""), None);
+}